@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+use sql_parser::expr::{AggregateFunction, BinaryOp, ColumnRef, Expr};
+use crate::error::QueryExceptionError;
+use crate::eval;
+use crate::row::{OutputColumn, Row, RowSchema};
+use crate::value::Value;
+
+/// 查询中的聚合部分：分组表达式和需要计算的聚合函数
+///
+/// 聚合后的每一行先是分组表达式的值，再是各聚合函数的结果，
+/// 列名为表达式的 SQL 文本，之后的 HAVING、ORDER BY 和投影都改写为引用这些列
+#[derive(Debug, Clone)]
+pub(crate) struct Aggregation {
+    pub group_by: Vec<Expr>,
+    pub aggregates: Vec<Expr>
+}
+
+impl Aggregation {
+    /// 从给定表达式中收集所有（去重后的）聚合函数
+    pub fn new<'e>(group_by: Vec<Expr>, exprs: impl IntoIterator<Item = &'e Expr>) -> Self {
+        let mut aggregates = Vec::new();
+        for expr in exprs {
+            collect_aggregates(expr, &mut aggregates);
+        }
+        Self { group_by, aggregates }
+    }
+
+    /// 聚合结果中各列的描述
+    pub fn schema(&self) -> Rc<RowSchema> {
+        Rc::new(
+            self.group_by
+                .iter()
+                .chain(&self.aggregates)
+                .map(|expr| OutputColumn::new(expr.to_string()))
+                .collect()
+        )
+    }
+
+    /// 把分组表达式和聚合函数改写为对聚合结果列的引用
+    ///
+    /// 既不在 GROUP BY 中、也不在聚合函数内的列会报错
+    pub fn rewrite(&self, expr: &Expr) -> Result<Expr, QueryExceptionError> {
+        if self.group_by.contains(expr) || self.aggregates.contains(expr) {
+            return Ok(Expr::Column(ColumnRef::from(expr.to_string())));
+        }
+
+        match expr {
+            Expr::Column(column) => Err(QueryExceptionError::NotGrouped(column.to_string())),
+            Expr::Literal(_) | Expr::NextVal(_) => Ok(expr.clone()),
+            Expr::Aggregate { .. } => unreachable!("aggregates are collected before rewriting"),
+            Expr::Unary { op, expr } => Ok(Expr::Unary {
+                op: *op,
+                expr: Box::new(self.rewrite(expr)?)
+            }),
+            Expr::Binary { left, op, right } => Ok(Expr::Binary {
+                left: Box::new(self.rewrite(left)?),
+                op: *op,
+                right: Box::new(self.rewrite(right)?)
+            }),
+            Expr::InList { expr, list, negated } => Ok(Expr::InList {
+                expr: Box::new(self.rewrite(expr)?),
+                list: list.iter().map(|item| self.rewrite(item)).collect::<Result<_, _>>()?,
+                negated: *negated
+            })
+        }
+    }
+
+    /// 哈希聚合：按分组键把行分到各组并累加，分组按第一次出现的顺序输出
+    ///
+    /// 没有 GROUP BY 时即使没有输入行也会输出一行
+    pub fn execute(
+        &self,
+        rows: impl Iterator<Item = Result<Row, QueryExceptionError>>
+    ) -> Result<Vec<Row>, QueryExceptionError> {
+        let mut groups: HashMap<Vec<Value>, usize> = HashMap::new();
+        let mut states: Vec<(Vec<Value>, Vec<Accumulator>)> = Vec::new();
+
+        for row in rows {
+            let row = row?;
+            let key = self.group_by
+                .iter()
+                .map(|expr| eval::evaluate(expr, &row))
+                .collect::<Result<Vec<Value>, QueryExceptionError>>()?;
+
+            let idx = *groups.entry(key.clone()).or_insert_with(|| {
+                states.push((key, self.accumulators()));
+                states.len() - 1
+            });
+            for (accumulator, aggregate) in states[idx].1.iter_mut().zip(&self.aggregates) {
+                let Expr::Aggregate { arg, .. } = aggregate else {
+                    unreachable!("only aggregates are collected")
+                };
+                let value = arg.as_ref().map(|arg| eval::evaluate(arg, &row)).transpose()?;
+                accumulator.update(value)?;
+            }
+        }
+
+        if states.is_empty() && self.group_by.is_empty() {
+            states.push((Vec::new(), self.accumulators()));
+        }
+
+        let schema = self.schema();
+        Ok(states
+            .into_iter()
+            .map(|(key, accumulators)| {
+                let mut values = key;
+                values.extend(accumulators.into_iter().map(Accumulator::finish));
+                Row::new(schema.clone(), values)
+            })
+            .collect())
+    }
+
+    fn accumulators(&self) -> Vec<Accumulator> {
+        self.aggregates
+            .iter()
+            .map(|aggregate| match aggregate {
+                Expr::Aggregate { func, .. } => Accumulator::new(*func),
+                _ => unreachable!("only aggregates are collected")
+            })
+            .collect()
+    }
+}
+
+/// `group_by=[..], aggregates=[..]`
+impl fmt::Display for Aggregation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |exprs: &[Expr]| exprs.iter().map(|e| e.to_string()).collect::<Vec<_>>().join(", ");
+        write!(f, "group_by=[{}], aggregates=[{}]", list(&self.group_by), list(&self.aggregates))
+    }
+}
+
+fn collect_aggregates(expr: &Expr, aggregates: &mut Vec<Expr>) {
+    match expr {
+        Expr::Aggregate { .. } => {
+            if !aggregates.contains(expr) {
+                aggregates.push(expr.clone());
+            }
+        }
+        Expr::Column(_) | Expr::Literal(_) | Expr::NextVal(_) => {}
+        Expr::Unary { expr, .. } => collect_aggregates(expr, aggregates),
+        Expr::Binary { left, right, .. } => {
+            collect_aggregates(left, aggregates);
+            collect_aggregates(right, aggregates);
+        }
+        Expr::InList { expr, list, .. } => {
+            collect_aggregates(expr, aggregates);
+            list.iter().for_each(|item| collect_aggregates(item, aggregates));
+        }
+    }
+}
+
+/// 单个聚合函数在一个分组内的中间状态，NULL 输入都会被忽略
+#[derive(Debug)]
+enum Accumulator {
+    Count(i64),
+    Sum(Value),
+    Avg { sum: f64, count: i64 },
+    Min(Value),
+    Max(Value)
+}
+
+impl Accumulator {
+    fn new(func: AggregateFunction) -> Self {
+        match func {
+            AggregateFunction::Count => Accumulator::Count(0),
+            AggregateFunction::Sum => Accumulator::Sum(Value::Null),
+            AggregateFunction::Avg => Accumulator::Avg { sum: 0.0, count: 0 },
+            AggregateFunction::Min => Accumulator::Min(Value::Null),
+            AggregateFunction::Max => Accumulator::Max(Value::Null)
+        }
+    }
+
+    /// `value` 为 `None` 表示 `COUNT(*)`，每行都计数
+    fn update(&mut self, value: Option<Value>) -> Result<(), QueryExceptionError> {
+        let value = match value {
+            None => {
+                if let Accumulator::Count(count) = self {
+                    *count += 1;
+                }
+                return Ok(());
+            }
+            Some(Value::Null) => return Ok(()),
+            Some(value) => value
+        };
+
+        match self {
+            Accumulator::Count(count) => *count += 1,
+            Accumulator::Sum(sum) => {
+                if !matches!(value, Value::Int(_) | Value::Float(_)) {
+                    return Err(QueryExceptionError::TypeMismatch(format!("cannot SUM {value}")));
+                }
+                *sum = match sum {
+                    Value::Null => value,
+                    _ => sum.arithmetic(BinaryOp::Plus, &value)?
+                };
+            }
+            Accumulator::Avg { sum, count } => {
+                *sum += match value {
+                    Value::Int(i) => i as f64,
+                    Value::Float(f) => f,
+                    other => return Err(QueryExceptionError::TypeMismatch(format!("cannot AVG {other}")))
+                };
+                *count += 1;
+            }
+            Accumulator::Min(min) => {
+                if min.is_null() || value.compare(min)?.is_some_and(|o| o.is_lt()) {
+                    *min = value;
+                }
+            }
+            Accumulator::Max(max) => {
+                if max.is_null() || value.compare(max)?.is_some_and(|o| o.is_gt()) {
+                    *max = value;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Value {
+        match self {
+            Accumulator::Count(count) => Value::Int(count),
+            Accumulator::Sum(sum) => sum,
+            Accumulator::Avg { count: 0, .. } => Value::Null,
+            Accumulator::Avg { sum, count } => Value::Float(sum / count as f64),
+            Accumulator::Min(value) | Accumulator::Max(value) => value
+        }
+    }
+}
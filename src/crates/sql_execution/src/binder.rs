@@ -0,0 +1,309 @@
+use std::collections::{HashMap, HashSet};
+use sql_parser::commands::{FromClause, SelectItem, SelectStatement, TableRef};
+use sql_parser::expr::{ColumnRef, Expr};
+use crate::aggregate::Aggregation;
+use crate::error::QueryExceptionError;
+use crate::plan::LogicalPlan;
+use crate::row::{self, OutputColumn, RowSchema};
+use crate::table::Table;
+
+/// 把 AST 转换为逻辑计划，并按目录中的表解析表名和列名
+///
+/// 绑定后所有列引用都带上所属表的别名（没有别名时为表名），
+/// 这样 `grp` 和 `t.grp` 会被视为同一列
+pub(crate) struct Binder<'c> {
+    tables: &'c HashMap<String, Table>
+}
+
+impl<'c> Binder<'c> {
+    pub fn new(tables: &'c HashMap<String, Table>) -> Self {
+        Self { tables }
+    }
+
+    /// 依次为：扫描并连接 -> 过滤 -> 聚合 -> HAVING -> 排序 -> LIMIT -> 投影
+    pub fn bind_select(&self, select: SelectStatement) -> Result<LogicalPlan, QueryExceptionError> {
+        let mut plan = self.bind_from(&select.from)?;
+        let input_schema = plan.schema();
+
+        if let Some(predicate) = &select.where_clause {
+            plan = LogicalPlan::Filter {
+                input: Box::new(plan),
+                predicate: bind_scalar(predicate, &input_schema)?
+            };
+        }
+
+        let mut projection = Vec::new();
+        for (output, expr) in expand_projection(&select.fields, &input_schema)? {
+            projection.push((output, bind_expr(&expr, &input_schema)?));
+        }
+        let mut order_by = select.order_by;
+        for item in order_by.iter_mut() {
+            let expr = replace_aliases(&item.expr, &select.fields)?;
+            reject_nextval(&expr)?;
+            item.expr = bind_expr(&expr, &input_schema)?;
+        }
+        if let Some(having) = &select.having {
+            reject_nextval(having)?;
+        }
+        let mut having = select.having.as_ref().map(|expr| bind_expr(expr, &input_schema)).transpose()?;
+        let group_by = select.group_by
+            .iter()
+            .map(|expr| bind_scalar(expr, &input_schema))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let is_aggregate = !group_by.is_empty()
+            || having.is_some()
+            || projection.iter().any(|(_, expr)| expr.contains_aggregate())
+            || order_by.iter().any(|item| item.expr.contains_aggregate());
+        if is_aggregate {
+            let aggregation = Aggregation::new(
+                group_by,
+                projection.iter().map(|(_, expr)| expr)
+                    .chain(having.iter())
+                    .chain(order_by.iter().map(|item| &item.expr))
+            );
+            for (_, expr) in projection.iter_mut() {
+                *expr = aggregation.rewrite(expr)?;
+            }
+            for item in order_by.iter_mut() {
+                item.expr = aggregation.rewrite(&item.expr)?;
+            }
+            having = having.map(|expr| aggregation.rewrite(&expr)).transpose()?;
+
+            plan = LogicalPlan::Aggregate { input: Box::new(plan), aggregation };
+            if let Some(predicate) = having {
+                plan = LogicalPlan::Filter { input: Box::new(plan), predicate };
+            }
+        }
+
+        if !order_by.is_empty() {
+            plan = LogicalPlan::Sort { input: Box::new(plan), order_by, fetch: None };
+        }
+        if select.limit.is_some() || select.offset.is_some() {
+            plan = LogicalPlan::Limit {
+                input: Box::new(plan),
+                offset: select.offset.unwrap_or(0),
+                limit: select.limit
+            };
+        }
+        Ok(LogicalPlan::Project { input: Box::new(plan), exprs: projection })
+    }
+
+    /// 从左到右依次连接 `FROM` 中的表
+    fn bind_from(&self, from: &FromClause) -> Result<LogicalPlan, QueryExceptionError> {
+        let mut qualifiers = HashSet::new();
+        for table_ref in from.tables() {
+            if !qualifiers.insert(table_ref.qualifier()) {
+                return Err(QueryExceptionError::DuplicateTableReference(table_ref.qualifier().to_owned()));
+            }
+        }
+
+        let mut plan = self.bind_table(&from.table)?;
+        for join in &from.joins {
+            let right = self.bind_table(&join.table)?;
+            let schema: RowSchema = plan.schema().iter().chain(right.schema().iter()).cloned().collect();
+            let on = join.on.as_ref().map(|on| bind_scalar(on, &schema)).transpose()?;
+            plan = LogicalPlan::Join {
+                left: Box::new(plan),
+                right: Box::new(right),
+                kind: join.kind,
+                on
+            };
+        }
+        Ok(plan)
+    }
+
+    fn bind_table(&self, table_ref: &TableRef) -> Result<LogicalPlan, QueryExceptionError> {
+        let table = self
+            .tables
+            .get(&table_ref.name)
+            .ok_or_else(|| QueryExceptionError::TableNotFound(table_ref.name.to_owned()))?;
+        Ok(LogicalPlan::Scan {
+            table: table_ref.name.to_owned(),
+            qualifier: table_ref.qualifier().to_owned(),
+            schema: table.qualified_schema(table_ref.qualifier()),
+            projection: None,
+            index: None
+        })
+    }
+}
+
+/// 把 `*` 和 `t.*` 展开为输入列，每项带上输出列名
+///
+/// 直接引用一列且没有别名时输出列名为列名本身，不带表名
+fn expand_projection(
+    fields: &[SelectItem],
+    schema: &RowSchema
+) -> Result<Vec<(OutputColumn, Expr)>, QueryExceptionError> {
+    let column = |col: &OutputColumn| {
+        let expr = Expr::Column(ColumnRef { table: col.table.clone(), name: col.name.to_owned() });
+        (col.clone(), expr)
+    };
+
+    let mut projection = Vec::new();
+    for field in fields {
+        match field {
+            SelectItem::Wildcard => projection.extend(schema.iter().map(column)),
+            SelectItem::QualifiedWildcard(table) => {
+                let len = projection.len();
+                projection.extend(
+                    schema.iter()
+                        .filter(|col| col.table.as_ref() == Some(table))
+                        .map(column)
+                );
+                if projection.len() == len {
+                    return Err(QueryExceptionError::TableNotFound(table.to_owned()));
+                }
+            }
+            SelectItem::Expr { expr, alias } => {
+                let output = match (alias, expr) {
+                    (Some(alias), _) => OutputColumn::new(alias.to_owned()),
+                    (None, Expr::Column(col)) => {
+                        let idx = row::resolve(schema, col)?;
+                        schema[idx].clone()
+                    }
+                    (None, expr) => OutputColumn::new(expr.to_string())
+                };
+                projection.push((output, expr.clone()));
+            }
+        }
+    }
+    Ok(projection)
+}
+
+/// 把 ORDER BY 中不带表名、与输出列别名同名的列替换为别名对应的表达式，其余的列之后按输入列解析
+fn replace_aliases(expr: &Expr, fields: &[SelectItem]) -> Result<Expr, QueryExceptionError> {
+    Ok(match expr {
+        Expr::Column(column) if column.table.is_none() => {
+            let mut aliased = fields.iter().filter_map(|field| match field {
+                SelectItem::Expr { expr, alias: Some(alias) } if *alias == column.name => Some(expr),
+                _ => None
+            });
+            match (aliased.next(), aliased.next()) {
+                (Some(_), Some(_)) => return Err(QueryExceptionError::AmbiguousColumn(column.to_string())),
+                (Some(aliased), None) => aliased.clone(),
+                (None, _) => expr.clone()
+            }
+        }
+        Expr::Column(_) | Expr::Literal(_) | Expr::NextVal(_) | Expr::Aggregate { .. } => expr.clone(),
+        Expr::Unary { op, expr } => Expr::Unary { op: *op, expr: Box::new(replace_aliases(expr, fields)?) },
+        Expr::Binary { left, op, right } => Expr::Binary {
+            left: Box::new(replace_aliases(left, fields)?),
+            op: *op,
+            right: Box::new(replace_aliases(right, fields)?)
+        },
+        Expr::InList { expr, list, negated } => Expr::InList {
+            expr: Box::new(replace_aliases(expr, fields)?),
+            list: list.iter().map(|item| replace_aliases(item, fields)).collect::<Result<_, _>>()?,
+            negated: *negated
+        }
+    })
+}
+
+/// 绑定不允许出现聚合函数的表达式，如 WHERE、ON 和 GROUP BY
+fn bind_scalar(expr: &Expr, schema: &RowSchema) -> Result<Expr, QueryExceptionError> {
+    if expr.contains_aggregate() {
+        return Err(QueryExceptionError::MisplacedAggregate(expr.to_string()));
+    }
+    reject_nextval(expr)?;
+    bind_expr(expr, schema)
+}
+
+/// 查询中只有输出列可以使用 `nextval`，每输出一行取一个值
+fn reject_nextval(expr: &Expr) -> Result<(), QueryExceptionError> {
+    if expr.contains_nextval() {
+        return Err(QueryExceptionError::MisplacedSequence(expr.to_string()));
+    }
+    Ok(())
+}
+
+/// 把表达式中的列引用解析为 `schema` 中带表名的列，列不存在或有歧义时报错
+pub(crate) fn bind_expr(expr: &Expr, schema: &RowSchema) -> Result<Expr, QueryExceptionError> {
+    Ok(match expr {
+        Expr::Column(column) => {
+            let col = &schema[row::resolve(schema, column)?];
+            Expr::Column(ColumnRef { table: col.table.clone(), name: col.name.to_owned() })
+        }
+        Expr::Literal(_) | Expr::NextVal(_) | Expr::Aggregate { arg: None, .. } => expr.clone(),
+        Expr::Aggregate { func, arg: Some(arg) } => Expr::Aggregate {
+            func: *func,
+            arg: Some(Box::new(bind_expr(arg, schema)?))
+        },
+        Expr::Unary { op, expr } => Expr::Unary { op: *op, expr: Box::new(bind_expr(expr, schema)?) },
+        Expr::Binary { left, op, right } => Expr::Binary {
+            left: Box::new(bind_expr(left, schema)?),
+            op: *op,
+            right: Box::new(bind_expr(right, schema)?)
+        },
+        Expr::InList { expr, list, negated } => Expr::InList {
+            expr: Box::new(bind_expr(expr, schema)?),
+            list: list.iter().map(|item| bind_expr(item, schema)).collect::<Result<_, _>>()?,
+            negated: *negated
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sql_parser::ast::{parse_sql_query, SqlQuery};
+    use sql_parser::{Column, SqlTypeInfo};
+
+    fn catalog() -> HashMap<String, Table> {
+        let table = |name: &str, columns: &[&str]| {
+            let columns = columns.iter().map(|name| Column::new(*name, SqlTypeInfo::Int)).collect();
+            (name.to_string(), Table::new(name, columns, &[]).unwrap())
+        };
+        HashMap::from([table("users", &["id", "age"]), table("orders", &["id", "uid", "total"])])
+    }
+
+    fn bind(sql: &str) -> Result<LogicalPlan, QueryExceptionError> {
+        let SqlQuery::Select(select) = parse_sql_query(&format!("{sql};")).unwrap() else {
+            panic!("expected a select statement")
+        };
+        Binder::new(&catalog()).bind_select(select)
+    }
+
+    #[test]
+    fn test_bind_select() {
+        let plan = bind(
+            "SELECT age, SUM(o.total) AS spent FROM users u JOIN orders o ON u.id = uid \
+             WHERE total > 1 GROUP BY u.age HAVING COUNT(*) > 1 ORDER BY age DESC LIMIT 5"
+        ).unwrap();
+        assert_eq!(
+            plan.to_string(),
+            "Project: u.age AS age, SUM(o.total) AS spent\n\
+             \x20 Limit: 5 OFFSET 0\n\
+             \x20   Sort: u.age DESC NULLS FIRST\n\
+             \x20     Filter: COUNT(*) > 1\n\
+             \x20       Aggregate: group_by=[u.age], aggregates=[SUM(o.total), COUNT(*)]\n\
+             \x20         Filter: o.total > 1\n\
+             \x20           Inner Join: u.id = o.uid\n\
+             \x20             Scan: users AS u\n\
+             \x20             Scan: orders AS o\n"
+        );
+        assert_eq!(
+            plan.schema().iter().map(|col| col.name.as_str()).collect::<Vec<_>>(),
+            vec!["age", "spent"]
+        );
+
+        let plan = bind("SELECT * FROM users").unwrap();
+        assert_eq!(plan.to_string(), "Project: users.id, users.age\n  Scan: users\n");
+    }
+
+    #[test]
+    fn test_bind_errors() {
+        assert!(matches!(bind("SELECT id FROM users, orders"), Err(QueryExceptionError::AmbiguousColumn(_))));
+        assert!(matches!(bind("SELECT nope FROM users"), Err(QueryExceptionError::ColumnDoesNotExists(_))));
+        assert!(matches!(bind("SELECT * FROM nope"), Err(QueryExceptionError::TableNotFound(_))));
+        assert!(matches!(bind("SELECT o.* FROM users"), Err(QueryExceptionError::TableNotFound(_))));
+        assert!(matches!(
+            bind("SELECT id FROM users WHERE COUNT(*) > 1"),
+            Err(QueryExceptionError::MisplacedAggregate(_))
+        ));
+        assert!(matches!(
+            bind("SELECT age, id FROM users GROUP BY users.age"),
+            Err(QueryExceptionError::NotGrouped(_))
+        ));
+    }
+}
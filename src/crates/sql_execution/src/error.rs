@@ -13,7 +13,94 @@ pub enum QueryExceptionError {
     TableAlreadyExists(String),
 
     #[error("Column ${0} does not exist")]
-    ColumnDoesNotExists(String)
+    ColumnDoesNotExists(String),
+
+    #[error("Column reference {0} is ambiguous")]
+    AmbiguousColumn(String),
+
+    #[error("Table name {0} specified more than once")]
+    DuplicateTableReference(String),
+
+    #[error("Column {0} specified more than once")]
+    DuplicateColumn(String),
+
+    #[error("Expected {expected} values but got {found}")]
+    ValueCountMismatch { expected: usize, found: usize },
+
+    #[error("Type mismatch: {0}")]
+    TypeMismatch(String),
+
+    #[error("Arithmetic error: {0}")]
+    ArithmeticError(String),
+
+    #[error("Aggregate function {0} is not allowed here")]
+    MisplacedAggregate(String),
+
+    #[error("Column {0} must appear in the GROUP BY clause or be used in an aggregate function")]
+    NotGrouped(String),
+
+    #[error("Index {0} already exists")]
+    IndexAlreadyExists(String),
+
+    #[error("Index {0} was not found")]
+    IndexNotFound(String),
+
+    #[error("Duplicate key ({columns})=({values}) violates unique index {index}")]
+    DuplicateKey { index: String, columns: String, values: String },
+
+    #[error("Duplicate key ({columns})=({values}) violates unique constraint {constraint}")]
+    UniqueViolation { constraint: String, columns: String, values: String },
+
+    #[error("Multiple primary keys are not allowed")]
+    MultiplePrimaryKeys,
+
+    #[error("Primary key column {0} cannot be NULL")]
+    NullPrimaryKey(String),
+
+    #[error("Duplicate primary key ({columns})=({values})")]
+    DuplicatePrimaryKey { columns: String, values: String },
+
+    #[error("Column {0} cannot be NULL")]
+    NotNullViolation(String),
+
+    #[error("Row violates check constraint {0}")]
+    CheckViolation(String),
+
+    #[error("Invalid foreign key {0}")]
+    InvalidForeignKey(String),
+
+    #[error("Key ({columns})=({values}) is not present in table {table}")]
+    ForeignKeyViolation { table: String, columns: String, values: String },
+
+    #[error("Key ({columns})=({values}) is still referenced from table {table}")]
+    ForeignKeyRestrict { table: String, columns: String, values: String },
+
+    #[error("Table {table} is referenced by a foreign key on table {referencing}")]
+    TableReferenced { table: String, referencing: String },
+
+    #[error("Index {index} is required by a foreign key on table {table}")]
+    IndexInUse { index: String, table: String },
+
+    #[error("Invalid AUTOINCREMENT column {0}")]
+    InvalidAutoIncrement(String),
+
+    #[error("Sequence {0} already exists")]
+    SequenceAlreadyExists(String),
+
+    #[error("Sequence {0} was not found")]
+    SequenceNotFound(String),
+
+    #[error("Sequence increment must be positive, got {0}")]
+    InvalidSequenceIncrement(i64),
+
+    #[error("Sequence {sequence} is used by a column default on table {table}")]
+    SequenceInUse { sequence: String, table: String },
+
+    #[error("Sequence {0} has reached its maximum value")]
+    SequenceExhausted(String),
+
+    #[error("Sequence function nextval is not allowed here: {0}")]
+    MisplacedSequence(String)
 }
 
 /// Errors at any point in the SQL "pipeline"
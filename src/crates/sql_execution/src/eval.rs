@@ -0,0 +1,126 @@
+use std::cmp::Ordering;
+use sql_parser::expr::{BinaryOp, Expr, UnaryOp};
+use crate::error::QueryExceptionError;
+use crate::row::{self, Row, RowSchema};
+use crate::value::Value;
+
+/// 判断一行是否满足谓词，结果为 NULL 时视为不满足
+pub(crate) fn matches(expr: &Expr, row: &Row) -> Result<bool, QueryExceptionError> {
+    Ok(as_bool(evaluate(expr, row)?)?.unwrap_or(false))
+}
+
+/// 判断一行是否满足 CHECK 约束，结果为 NULL 时视为满足
+pub(crate) fn satisfies(expr: &Expr, row: &Row) -> Result<bool, QueryExceptionError> {
+    Ok(as_bool(evaluate(expr, row)?)?.unwrap_or(true))
+}
+
+/// 在执行前检查表达式引用的列都存在且没有歧义，这样空表上的查询也能报告错误
+pub(crate) fn check_columns(expr: &Expr, schema: &RowSchema) -> Result<(), QueryExceptionError> {
+    match expr {
+        Expr::Column(column) => row::resolve(schema, column).map(|_| ()),
+        Expr::Literal(_) | Expr::NextVal(_) | Expr::Aggregate { arg: None, .. } => Ok(()),
+        Expr::Aggregate { arg: Some(expr), .. } | Expr::Unary { expr, .. } => check_columns(expr, schema),
+        Expr::Binary { left, right, .. } => {
+            check_columns(left, schema)?;
+            check_columns(right, schema)
+        }
+        Expr::InList { expr, list, .. } => {
+            check_columns(expr, schema)?;
+            list.iter().try_for_each(|item| check_columns(item, schema))
+        }
+    }
+}
+
+/// 针对一行计算表达式的值
+pub(crate) fn evaluate(expr: &Expr, row: &Row) -> Result<Value, QueryExceptionError> {
+    match expr {
+        Expr::Column(column) => row.lookup(column),
+        Expr::Literal(literal) => Ok(literal.into()),
+        // 聚合函数在聚合阶段已被替换为对结果列的引用，剩下的都出现在不允许的位置
+        Expr::Aggregate { .. } => Err(QueryExceptionError::MisplacedAggregate(expr.to_string())),
+        // 允许出现 nextval 的位置在求值前已把它替换为取出的值
+        Expr::NextVal(_) => Err(QueryExceptionError::MisplacedSequence(expr.to_string())),
+        Expr::Unary { op: UnaryOp::Not, expr } => {
+            Ok(bool_value(as_bool(evaluate(expr, row)?)?.map(|b| !b)))
+        }
+        Expr::Unary { op: UnaryOp::Neg, expr } => evaluate(expr, row)?.negate(),
+        Expr::Binary { left, op: BinaryOp::And, right } => {
+            // 三值逻辑：任意一边为 false 结果即为 false
+            let left = as_bool(evaluate(left, row)?)?;
+            if left == Some(false) {
+                return Ok(Value::Bool(false));
+            }
+            let right = as_bool(evaluate(right, row)?)?;
+            Ok(match (left, right) {
+                (_, Some(false)) => Value::Bool(false),
+                (Some(true), Some(true)) => Value::Bool(true),
+                _ => Value::Null,
+            })
+        }
+        Expr::Binary { left, op: BinaryOp::Or, right } => {
+            // 三值逻辑：任意一边为 true 结果即为 true
+            let left = as_bool(evaluate(left, row)?)?;
+            if left == Some(true) {
+                return Ok(Value::Bool(true));
+            }
+            let right = as_bool(evaluate(right, row)?)?;
+            Ok(match (left, right) {
+                (_, Some(true)) => Value::Bool(true),
+                (Some(false), Some(false)) => Value::Bool(false),
+                _ => Value::Null,
+            })
+        }
+        Expr::Binary {
+            left,
+            op: op @ (BinaryOp::Plus | BinaryOp::Minus | BinaryOp::Multiply | BinaryOp::Divide | BinaryOp::Modulo),
+            right,
+        } => evaluate(left, row)?.arithmetic(*op, &evaluate(right, row)?),
+        Expr::Binary { left, op, right } => {
+            let ordering = evaluate(left, row)?.compare(&evaluate(right, row)?)?;
+            Ok(bool_value(ordering.map(|ordering| compare_op(*op, ordering))))
+        }
+        Expr::InList { expr, list, negated } => {
+            // 等于其中一项时为 true；否则有 NULL 参与比较时结果未知
+            let value = evaluate(expr, row)?;
+            let mut found = Some(false);
+            for item in list {
+                match value.compare(&evaluate(item, row)?)? {
+                    Some(Ordering::Equal) => {
+                        found = Some(true);
+                        break;
+                    }
+                    Some(_) => {}
+                    None => found = None
+                }
+            }
+            Ok(bool_value(found.map(|found| found != *negated)))
+        }
+    }
+}
+
+fn compare_op(op: BinaryOp, ordering: Ordering) -> bool {
+    match op {
+        BinaryOp::Eq => ordering == Ordering::Equal,
+        BinaryOp::NotEq => ordering != Ordering::Equal,
+        BinaryOp::Lt => ordering == Ordering::Less,
+        BinaryOp::LtEq => ordering != Ordering::Greater,
+        BinaryOp::Gt => ordering == Ordering::Greater,
+        BinaryOp::GtEq => ordering != Ordering::Less,
+        _ => unreachable!("{op:?} is not a comparison"),
+    }
+}
+
+fn bool_value(value: Option<bool>) -> Value {
+    value.map_or(Value::Null, Value::Bool)
+}
+
+/// NULL 映射为 `None`，其它非布尔值报错
+fn as_bool(value: Value) -> Result<Option<bool>, QueryExceptionError> {
+    match value {
+        Value::Bool(b) => Ok(Some(b)),
+        Value::Null => Ok(None),
+        other => Err(QueryExceptionError::TypeMismatch(format!(
+            "expected a boolean, got {other}"
+        ))),
+    }
+}
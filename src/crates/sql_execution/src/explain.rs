@@ -0,0 +1,91 @@
+use std::fmt::Write;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use crate::error::QueryExceptionError;
+use crate::physical::{BoxedOperator, PhysicalOperator};
+use crate::row::{Row, RowSchema};
+
+/// EXPLAIN ANALYZE 为每个算子统计的运行信息
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct OperatorMetrics {
+    /// 输出的行数
+    pub rows: usize,
+    /// 花在 `open`、`next` 和 `close` 中的时间，包括子算子的时间
+    pub elapsed: Duration
+}
+
+/// 包装一个算子并统计它的运行信息，其它行为与被包装的算子相同
+pub(crate) struct Instrumented<'a> {
+    input: BoxedOperator<'a>,
+    metrics: OperatorMetrics
+}
+
+impl<'a> Instrumented<'a> {
+    pub fn new(input: BoxedOperator<'a>) -> Self {
+        Self { input, metrics: OperatorMetrics::default() }
+    }
+
+    fn timed<T>(&mut self, f: impl FnOnce(&mut BoxedOperator<'a>) -> T) -> T {
+        let start = Instant::now();
+        let result = f(&mut self.input);
+        self.metrics.elapsed += start.elapsed();
+        result
+    }
+}
+
+impl PhysicalOperator for Instrumented<'_> {
+    fn schema(&self) -> Rc<RowSchema> {
+        self.input.schema()
+    }
+
+    fn open(&mut self) -> Result<(), QueryExceptionError> {
+        self.timed(|input| input.open())
+    }
+
+    fn next(&mut self) -> Result<Option<Row>, QueryExceptionError> {
+        let row = self.timed(|input| input.next())?;
+        if row.is_some() {
+            self.metrics.rows += 1;
+        }
+        Ok(row)
+    }
+
+    fn close(&mut self) {
+        self.timed(|input| input.close())
+    }
+
+    fn describe(&self) -> String {
+        self.input.describe()
+    }
+
+    fn children(&self) -> Vec<&dyn PhysicalOperator> {
+        self.input.children()
+    }
+
+    fn metrics(&self) -> Option<OperatorMetrics> {
+        Some(self.metrics)
+    }
+}
+
+/// 每个算子一行，子算子依次缩进；有运行信息时附在行尾
+pub(crate) fn format_plan(operator: &dyn PhysicalOperator) -> String {
+    fn write_tree(operator: &dyn PhysicalOperator, depth: usize, out: &mut String) {
+        write!(out, "{:indent$}{}", "", operator.describe(), indent = depth * 2).unwrap();
+        if let Some(metrics) = operator.metrics() {
+            write!(out, " (rows={} time={})", metrics.rows, format_duration(metrics.elapsed)).unwrap();
+        }
+        out.push('\n');
+        for child in operator.children() {
+            write_tree(child, depth + 1, out);
+        }
+    }
+
+    let mut out = String::new();
+    write_tree(operator, 0, &mut out);
+    out
+}
+
+/// 以毫秒为单位，保留三位小数
+pub(crate) fn format_duration(duration: Duration) -> String {
+    format!("{:.3}ms", duration.as_secs_f64() * 1000.0)
+}
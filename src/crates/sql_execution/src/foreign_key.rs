@@ -0,0 +1,297 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use sql_parser::commands::{ReferentialAction, TableConstraint};
+use crate::error::QueryExceptionError;
+use crate::index::IndexKey;
+use crate::table::{RowKey, RowWrite, StoreRow, Table};
+use crate::value::Value;
+
+/// 子表上的外键：`columns` 引用父表 `table` 的 `referenced` 列
+///
+/// 被引用的列是父表的主键或唯一索引的列；外键列中有 NULL 的行不做检查
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ForeignKey {
+    /// 外键列在子表中的下标
+    pub columns: Vec<usize>,
+    pub table: String,
+    /// 被引用的列在父表中的下标
+    pub referenced: Vec<usize>,
+    pub on_delete: ReferentialAction
+}
+
+/// 一行在 `columns` 上的值，含 NULL 时为 `None`
+fn project(row: &StoreRow, columns: &[usize]) -> Option<IndexKey> {
+    let values: Vec<Value> = columns.iter().map(|&idx| row[idx].clone()).collect();
+    if values.iter().any(Value::is_null) {
+        return None;
+    }
+    Some(IndexKey(values))
+}
+
+fn column_names(table: &Table, columns: &[usize]) -> String {
+    columns.iter().map(|&idx| table.columns()[idx].name.as_str()).collect::<Vec<_>>().join(", ")
+}
+
+fn key_values(key: &IndexKey) -> String {
+    key.0.iter().map(Value::to_string).collect::<Vec<_>>().join(", ")
+}
+
+/// `columns` 是否为主键的列或唯一索引的列，不考虑顺序，也不考虑名为 `except` 的索引
+fn is_unique_key(table: &Table, columns: &[usize], except: Option<&str>) -> bool {
+    let sorted = |columns: &[usize]| {
+        let mut columns = columns.to_vec();
+        columns.sort_unstable();
+        columns
+    };
+    let columns = sorted(columns);
+    table.primary_key_columns().is_some_and(|key| sorted(key) == columns)
+        || table
+            .indexes()
+            .iter()
+            .any(|index| index.unique && Some(index.name.as_str()) != except && sorted(&index.columns) == columns)
+}
+
+/// 解析新建的表 `name` 上的外键，父表可以是这张表自己
+pub(crate) fn resolve(
+    tables: &HashMap<String, Table>,
+    name: &str,
+    table: &Table,
+    constraints: &[TableConstraint]
+) -> Result<Vec<ForeignKey>, QueryExceptionError> {
+    let column_references = table
+        .columns()
+        .iter()
+        .flat_map(|col| col.references().map(|reference| (vec![col.name.to_owned()], reference)));
+    let table_references = constraints.iter().filter_map(|constraint| match constraint {
+        TableConstraint::ForeignKey { columns, reference } => Some((columns.clone(), reference)),
+        _ => None
+    });
+
+    column_references
+        .chain(table_references)
+        .map(|(columns, reference)| {
+            let parent = if reference.table == name {
+                table
+            } else {
+                tables
+                    .get(&reference.table)
+                    .ok_or_else(|| QueryExceptionError::TableNotFound(reference.table.to_owned()))?
+            };
+            let invalid = |reason: &str| QueryExceptionError::InvalidForeignKey(format!(
+                "({}) references {} ({}): {reason}",
+                columns.join(", "),
+                reference.table,
+                reference.columns.join(", ")
+            ));
+
+            let foreign_key = ForeignKey {
+                columns: columns.iter().map(|col| table.column_index(col)).collect::<Result<_, _>>()?,
+                table: reference.table.to_owned(),
+                referenced: reference.columns.iter().map(|col| parent.column_index(col)).collect::<Result<_, _>>()?,
+                on_delete: reference.on_delete
+            };
+            if foreign_key.columns.len() != foreign_key.referenced.len() {
+                return Err(invalid("number of columns does not match"));
+            }
+            let types_match = foreign_key
+                .columns
+                .iter()
+                .zip(&foreign_key.referenced)
+                .all(|(&col, &referenced)| table.columns()[col].type_info == parent.columns()[referenced].type_info);
+            if !types_match {
+                return Err(invalid("column types do not match"));
+            }
+            if !is_unique_key(parent, &foreign_key.referenced, None) {
+                return Err(invalid("referenced columns are not a primary key or unique index"));
+            }
+            Ok(foreign_key)
+        })
+        .collect()
+}
+
+/// 引用表 `name` 的外键，包括自引用，连同外键所在的表名
+fn referencing<'a>(tables: &'a HashMap<String, Table>, name: &'a str) -> impl Iterator<Item = (&'a str, &'a ForeignKey)> + 'a {
+    tables.iter().flat_map(move |(child, table)| {
+        table
+            .foreign_keys()
+            .iter()
+            .filter(move |foreign_key| foreign_key.table == name)
+            .map(move |foreign_key| (child.as_str(), foreign_key))
+    })
+}
+
+/// 表 `name` 是否被外键引用，包括自引用
+pub(crate) fn is_referenced(tables: &HashMap<String, Table>, name: &str) -> bool {
+    referencing(tables, name).next().is_some()
+}
+
+/// 被其它表引用的表不能删除或清空
+pub(crate) fn check_drop_table(tables: &HashMap<String, Table>, name: &str) -> Result<(), QueryExceptionError> {
+    match referencing(tables, name).find(|(child, _)| *child != name) {
+        Some((child, _)) => Err(QueryExceptionError::TableReferenced { table: name.to_owned(), referencing: child.to_owned() }),
+        None => Ok(())
+    }
+}
+
+/// 外键依赖唯一索引保证被引用的行唯一，这样的索引不能删除
+pub(crate) fn check_drop_index(tables: &HashMap<String, Table>, index: &str) -> Result<(), QueryExceptionError> {
+    for (name, table) in tables {
+        if !table.indexes().iter().any(|idx| idx.name == index) {
+            continue;
+        }
+        let dependent = referencing(tables, name)
+            .find(|(_, foreign_key)| !is_unique_key(table, &foreign_key.referenced, Some(index)));
+        if let Some((child, _)) = dependent {
+            return Err(QueryExceptionError::IndexInUse { index: index.to_owned(), table: child.to_owned() });
+        }
+    }
+    Ok(())
+}
+
+/// 检查写入 `rows` 后表 `name` 中这些行的外键都能在父表中找到
+///
+/// 自引用时，同一条语句写入的行也可以被引用，被替换的行则不能
+pub(crate) fn check_references(tables: &HashMap<String, Table>, name: &str, rows: &[RowWrite]) -> Result<(), QueryExceptionError> {
+    let table = &tables[name];
+    let replaced: HashSet<&RowKey> = rows.iter().filter_map(|(key, _)| key.as_ref()).collect();
+    for foreign_key in table.foreign_keys() {
+        let parent = &tables[&foreign_key.table];
+        let self_reference = foreign_key.table == name;
+        let written: HashSet<IndexKey> = if self_reference {
+            rows.iter().filter_map(|(_, row)| project(row, &foreign_key.referenced)).collect()
+        } else {
+            HashSet::new()
+        };
+
+        for (_, row) in rows {
+            let Some(key) = project(row, &foreign_key.columns) else {
+                continue;
+            };
+            let found = written.contains(&key)
+                || parent
+                    .find(&foreign_key.referenced, &key)
+                    .iter()
+                    .any(|row_key| !self_reference || !replaced.contains(row_key));
+            if !found {
+                return Err(QueryExceptionError::ForeignKeyViolation {
+                    table: foreign_key.table.to_owned(),
+                    columns: column_names(parent, &foreign_key.referenced),
+                    values: key_values(&key)
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 检查更新表 `name` 后，被引用的旧值仍然存在
+///
+/// 被引用的列改变时，如果同一条语句中没有其它行取用旧值，就不能再有行引用旧值
+pub(crate) fn check_referenced(tables: &HashMap<String, Table>, name: &str, rows: &[RowWrite]) -> Result<(), QueryExceptionError> {
+    let table = &tables[name];
+    for (child_name, foreign_key) in referencing(tables, name) {
+        let child = &tables[child_name];
+        let written: HashSet<IndexKey> = rows.iter().filter_map(|(_, row)| project(row, &foreign_key.referenced)).collect();
+        // 自引用时，同一条语句改写的行由 `check_references` 按新值检查
+        let rewritten: HashSet<&RowKey> = if child_name == name {
+            rows.iter().filter_map(|(key, _)| key.as_ref()).collect()
+        } else {
+            HashSet::new()
+        };
+
+        for key in rows.iter().filter_map(|(key, _)| key.as_ref()) {
+            let old = table.values(key).expect("updated rows come from this table");
+            let Some(old) = project(old, &foreign_key.referenced).filter(|old| !written.contains(old)) else {
+                continue;
+            };
+            if child.find(&foreign_key.columns, &old).iter().any(|row_key| !rewritten.contains(row_key)) {
+                return Err(QueryExceptionError::ForeignKeyRestrict {
+                    table: child_name.to_owned(),
+                    columns: column_names(table, &foreign_key.referenced),
+                    values: key_values(&old)
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 删除表 `name` 中的 `keys`，按外键的 ON DELETE 处理引用它们的行，返回从这张表删除的行数
+///
+/// 先求出所有要级联删除和置为 NULL 的行并完成检查，再统一修改，出错时不会修改任何表。
+/// RESTRICT 在语句结束时检查，引用被删除的行的行如果也被级联删除则不算违反约束
+pub(crate) fn delete(tables: &mut HashMap<String, Table>, name: &str, keys: Vec<RowKey>) -> Result<usize, QueryExceptionError> {
+    let mut deleted: HashMap<String, BTreeSet<RowKey>> = HashMap::new();
+    let mut set_null: HashMap<String, BTreeMap<RowKey, Vec<usize>>> = HashMap::new();
+    let mut restricted: Vec<(String, RowKey, QueryExceptionError)> = Vec::new();
+
+    let mut pending = vec![(name.to_owned(), keys)];
+    while let Some((table_name, keys)) = pending.pop() {
+        let seen = deleted.entry(table_name.clone()).or_default();
+        let keys: Vec<RowKey> = keys.into_iter().filter(|key| seen.insert(key.clone())).collect();
+        let table = &tables[&table_name];
+        for (child_name, foreign_key) in referencing(tables, &table_name) {
+            let child = &tables[child_name];
+            for key in &keys {
+                let row = table.values(key).expect("deleted rows come from this table");
+                let Some(value) = project(row, &foreign_key.referenced) else {
+                    continue;
+                };
+                let children = child.find(&foreign_key.columns, &value);
+                if children.is_empty() {
+                    continue;
+                }
+                match foreign_key.on_delete {
+                    ReferentialAction::Restrict => restricted.extend(children.into_iter().map(|child_key| {
+                        let error = QueryExceptionError::ForeignKeyRestrict {
+                            table: child_name.to_owned(),
+                            columns: column_names(table, &foreign_key.referenced),
+                            values: key_values(&value)
+                        };
+                        (child_name.to_owned(), child_key, error)
+                    })),
+                    ReferentialAction::Cascade => pending.push((child_name.to_owned(), children)),
+                    ReferentialAction::SetNull => {
+                        let rows = set_null.entry(child_name.to_owned()).or_default();
+                        for child_key in children {
+                            rows.entry(child_key).or_default().extend(&foreign_key.columns);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let is_deleted = |table: &str, key: &RowKey| deleted.get(table).is_some_and(|keys| keys.contains(key));
+    if let Some((_, _, error)) = restricted.into_iter().find(|(table, key, _)| !is_deleted(table, key)) {
+        return Err(error);
+    }
+
+    // 置为 NULL 后的行仍然要满足子表的列约束
+    let mut updates = Vec::with_capacity(set_null.len());
+    for (table_name, rows) in set_null {
+        let table = &tables[&table_name];
+        let rows: Vec<RowWrite> = rows
+            .into_iter()
+            .filter(|(key, _)| !is_deleted(&table_name, key))
+            .map(|(key, columns)| {
+                let mut row = table.values(&key).expect("referencing rows come from this table").clone();
+                for idx in columns {
+                    row[idx] = Value::Null;
+                }
+                (Some(key), row)
+            })
+            .collect();
+        table.check_constraints(&rows)?;
+        updates.push((table_name, rows));
+    }
+
+    let count = deleted.get(name).map_or(0, BTreeSet::len);
+    for (table_name, keys) in &deleted {
+        tables.get_mut(table_name).expect("deleted rows come from existing tables").delete_keys(keys);
+    }
+    for (table_name, rows) in updates {
+        tables.get_mut(&table_name).expect("updated rows come from existing tables").write(rows)?;
+    }
+    Ok(count)
+}
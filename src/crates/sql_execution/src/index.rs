@@ -0,0 +1,319 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::hash::{Hash, Hasher};
+use std::ops::Bound;
+use serde::{Deserialize, Serialize};
+use sql_parser::SqlTypeInfo;
+use sql_parser::commands::IndexKind;
+use crate::table::{RowKey, StoreRow};
+use crate::value::Value;
+
+/// 索引键，按列依次比较
+///
+/// 整数和浮点数按数值比较，这样 `WHERE f = 1` 也能在 `float` 列的索引中找到 `1.0`；
+/// 哈希时整数值的浮点数与对应的整数相同
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct IndexKey(pub Vec<Value>);
+
+impl Ord for IndexKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        for (l, r) in self.0.iter().zip(&other.0) {
+            match compare_values(l, r) {
+                Ordering::Equal => continue,
+                ordering => return ordering
+            }
+        }
+        // 前缀更短的键排在前面
+        self.0.len().cmp(&other.0.len())
+    }
+}
+
+impl PartialOrd for IndexKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for IndexKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for IndexKey {}
+
+impl Hash for IndexKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for value in &self.0 {
+            match value {
+                Value::Null => 0u8.hash(state),
+                Value::Bool(b) => (1u8, b).hash(state),
+                Value::Int(i) => (2u8, i).hash(state),
+                Value::Float(f) if f.fract() == 0.0 && *f >= i64::MIN as f64 && *f < i64::MAX as f64 => {
+                    (2u8, *f as i64).hash(state)
+                }
+                Value::Float(f) => (2u8, f.to_bits()).hash(state),
+                Value::Text(s) => (3u8, s).hash(state)
+            }
+        }
+    }
+}
+
+/// 索引中同一列的值类型相同；不同类型之间只需要一个固定的顺序
+fn compare_values(l: &Value, r: &Value) -> Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Null => 0,
+            Value::Bool(_) => 1,
+            Value::Int(_) | Value::Float(_) => 2,
+            Value::Text(_) => 3
+        }
+    }
+    match l.compare(r) {
+        Ok(Some(ordering)) => ordering,
+        _ => rank(l).cmp(&rank(r))
+    }
+}
+
+/// 索引上的查找范围：前几列取等值，紧接着的一列可以再加上下界
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct IndexRange {
+    pub prefix: Vec<Value>,
+    /// 下界和是否包含下界
+    pub lower: Option<(Value, bool)>,
+    /// 上界和是否包含上界
+    pub upper: Option<(Value, bool)>
+}
+
+/// 索引键到行 id 的映射
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum Entries {
+    BTree(BTreeMap<IndexKey, BTreeSet<RowKey>>),
+    Hash(HashMap<IndexKey, BTreeSet<RowKey>>)
+}
+
+/// 表上的索引，从索引键映射到行在表中的键
+///
+/// 含 NULL 的键不进入索引：NULL 不满足任何等值或范围条件，唯一索引也允许多个 NULL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Index {
+    pub name: String,
+    /// 索引列在表中的下标
+    pub columns: Vec<usize>,
+    pub unique: bool,
+    /// 为 UNIQUE 约束自动建立的索引
+    pub constraint: bool,
+    entries: Entries
+}
+
+impl Index {
+    pub fn new(name: String, columns: Vec<usize>, unique: bool, kind: IndexKind) -> Self {
+        let entries = match kind {
+            IndexKind::BTree => Entries::BTree(BTreeMap::new()),
+            IndexKind::Hash => Entries::Hash(HashMap::new())
+        };
+        Self { name, columns, unique, constraint: false, entries }
+    }
+
+    pub fn kind(&self) -> IndexKind {
+        match self.entries {
+            Entries::BTree(_) => IndexKind::BTree,
+            Entries::Hash(_) => IndexKind::Hash
+        }
+    }
+
+    /// 一行的索引键，含 NULL 时为 `None`
+    pub fn key(&self, row: &StoreRow) -> Option<IndexKey> {
+        let values: Vec<Value> = self.columns.iter().map(|&idx| row[idx].clone()).collect();
+        if values.iter().any(Value::is_null) {
+            return None;
+        }
+        Some(IndexKey(values))
+    }
+
+    pub fn insert(&mut self, row: &StoreRow, row_key: RowKey) {
+        let Some(key) = self.key(row) else {
+            return;
+        };
+        match &mut self.entries {
+            Entries::BTree(entries) => entries.entry(key).or_default().insert(row_key),
+            Entries::Hash(entries) => entries.entry(key).or_default().insert(row_key)
+        };
+    }
+
+    pub fn remove(&mut self, row: &StoreRow, row_key: &RowKey) {
+        let Some(key) = self.key(row) else {
+            return;
+        };
+        let ids = match &mut self.entries {
+            Entries::BTree(entries) => entries.get_mut(&key),
+            Entries::Hash(entries) => entries.get_mut(&key)
+        };
+        let Some(ids) = ids else {
+            return;
+        };
+        ids.remove(row_key);
+        if ids.is_empty() {
+            match &mut self.entries {
+                Entries::BTree(entries) => entries.remove(&key),
+                Entries::Hash(entries) => entries.remove(&key)
+            };
+        }
+    }
+
+    pub fn clear(&mut self) {
+        match &mut self.entries {
+            Entries::BTree(entries) => entries.clear(),
+            Entries::Hash(entries) => entries.clear()
+        }
+    }
+
+    /// 索引键等于 `key` 的行
+    pub fn get(&self, key: &IndexKey) -> impl Iterator<Item = &RowKey> + '_ {
+        let ids = match &self.entries {
+            Entries::BTree(entries) => entries.get(key),
+            Entries::Hash(entries) => entries.get(key)
+        };
+        ids.into_iter().flatten()
+    }
+
+    /// 满足 `range` 的行，有序索引按索引键排序
+    ///
+    /// 哈希索引只支持所有索引列都取等值的查找
+    pub fn lookup(&self, range: &IndexRange) -> Vec<RowKey> {
+        match &self.entries {
+            Entries::BTree(entries) => range_entries(entries, range)
+                .into_iter()
+                .flat_map(|(_, row_ids)| row_ids.iter().cloned())
+                .collect(),
+            Entries::Hash(_) => {
+                debug_assert!(range.prefix.len() == self.columns.len() && range.lower.is_none() && range.upper.is_none());
+                self.get(&IndexKey(range.prefix.clone())).cloned().collect()
+            }
+        }
+    }
+}
+
+/// 有序映射中键满足 `range` 的项，按键排序
+///
+/// 索引的键和按主键存储的表中行的键都用它做范围查找
+pub(crate) fn range_entries<'m, V>(entries: &'m BTreeMap<IndexKey, V>, range: &IndexRange) -> Vec<(&'m IndexKey, &'m V)> {
+    let column = range.prefix.len();
+    let mut start = range.prefix.clone();
+    if let Some((lower, _)) = &range.lower {
+        start.push(lower.clone());
+    }
+
+    let mut found = Vec::new();
+    for (key, value) in entries.range((Bound::Included(IndexKey(start)), Bound::Unbounded)) {
+        let matches_prefix = key.0[..column]
+            .iter()
+            .zip(&range.prefix)
+            .all(|(l, r)| compare_values(l, r) == Ordering::Equal);
+        if !matches_prefix {
+            break;
+        }
+        if column < key.0.len() {
+            let value = &key.0[column];
+            if let Some((lower, false)) = &range.lower {
+                if compare_values(value, lower) == Ordering::Equal {
+                    continue;
+                }
+            }
+            if let Some((upper, inclusive)) = &range.upper {
+                match compare_values(value, upper) {
+                    Ordering::Greater => break,
+                    Ordering::Equal if !inclusive => break,
+                    _ => {}
+                }
+            }
+        }
+        found.push((key, value));
+    }
+    found
+}
+
+/// 查询中的常量能否直接和这种类型的列在索引中比较
+pub(crate) fn comparable(value: &Value, type_info: &SqlTypeInfo) -> bool {
+    matches!(
+        (value, type_info),
+        (Value::Int(_) | Value::Float(_), SqlTypeInfo::Int | SqlTypeInfo::Float) | (Value::Text(_), SqlTypeInfo::String)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row_key(id: i64) -> RowKey {
+        IndexKey(vec![Value::Int(id)])
+    }
+
+    fn ids(keys: Vec<RowKey>) -> Vec<i64> {
+        keys.into_iter()
+            .map(|key| match key.0[..] {
+                [Value::Int(id)] => id,
+                _ => panic!("unexpected row key {key:?}")
+            })
+            .collect()
+    }
+
+    fn index(rows: &[(i64, i64)]) -> Index {
+        let mut index = Index::new("idx".into(), vec![0, 1], false, IndexKind::BTree);
+        for (id, (a, b)) in rows.iter().enumerate() {
+            index.insert(&vec![Value::Int(*a), Value::Int(*b)], row_key(id as i64));
+        }
+        index
+    }
+
+    fn range(prefix: &[i64], lower: Option<(i64, bool)>, upper: Option<(i64, bool)>) -> IndexRange {
+        IndexRange {
+            prefix: prefix.iter().map(|v| Value::Int(*v)).collect(),
+            lower: lower.map(|(v, inclusive)| (Value::Int(v), inclusive)),
+            upper: upper.map(|(v, inclusive)| (Value::Int(v), inclusive))
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        let index = index(&[(1, 1), (1, 2), (1, 3), (2, 1), (2, 2), (3, 5), (1, 2)]);
+        assert_eq!(ids(index.lookup(&range(&[1], None, None))), vec![0, 1, 6, 2]);
+        assert_eq!(ids(index.lookup(&range(&[1, 2], None, None))), vec![1, 6]);
+        assert_eq!(ids(index.lookup(&range(&[], Some((2, true)), None))), vec![3, 4, 5]);
+        assert_eq!(ids(index.lookup(&range(&[], Some((1, false)), Some((3, false))))), vec![3, 4]);
+        assert_eq!(ids(index.lookup(&range(&[1], Some((1, false)), Some((3, true))))), vec![1, 6, 2]);
+        assert_eq!(ids(index.lookup(&range(&[1], None, Some((2, false))))), vec![0]);
+        assert!(index.lookup(&range(&[4], None, None)).is_empty());
+    }
+
+    #[test]
+    fn test_maintain() {
+        let mut index = Index::new("idx".into(), vec![0], true, IndexKind::BTree);
+        index.insert(&vec![Value::Float(1.0)], row_key(0));
+        index.insert(&vec![Value::Null], row_key(1));
+        index.insert(&vec![Value::Null], row_key(2));
+        assert_eq!(ids(index.get(&IndexKey(vec![Value::Int(1)])).cloned().collect()), vec![0]);
+        assert_eq!(ids(index.lookup(&range(&[], None, Some((5, true))))), vec![0]);
+
+        index.remove(&vec![Value::Float(1.0)], &row_key(0));
+        assert!(index.lookup(&range(&[], None, None)).is_empty());
+        assert!(index.key(&vec![Value::Null]).is_none());
+    }
+
+    #[test]
+    fn test_hash() {
+        let mut index = Index::new("idx".into(), vec![0, 1], false, IndexKind::Hash);
+        index.insert(&vec![Value::Float(2.0), Value::Text("a".into())], row_key(0));
+        index.insert(&vec![Value::Float(2.5), Value::Text("a".into())], row_key(1));
+        index.insert(&vec![Value::Int(2), Value::Text("a".into())], row_key(2));
+        let key = |v: Value| IndexRange { prefix: vec![v, Value::Text("a".into())], lower: None, upper: None };
+        assert_eq!(ids(index.lookup(&key(Value::Int(2)))), vec![0, 2]);
+        assert_eq!(ids(index.lookup(&key(Value::Float(2.5)))), vec![1]);
+        assert!(index.lookup(&key(Value::Int(3))).is_empty());
+
+        index.remove(&vec![Value::Int(2), Value::Text("a".into())], &row_key(0));
+        assert_eq!(ids(index.lookup(&key(Value::Float(2.0)))), vec![2]);
+        index.clear();
+        assert!(index.lookup(&key(Value::Float(2.5))).is_empty());
+    }
+}
@@ -0,0 +1,460 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+use sql_parser::commands::JoinKind;
+use sql_parser::expr::{BinaryOp, Expr};
+use crate::error::QueryExceptionError;
+use crate::eval;
+use crate::row::{Row, RowSchema};
+use crate::value::Value;
+
+/// 连接算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum JoinStrategy {
+    /// 没有等值条件时逐对比较
+    NestedLoop,
+    /// 用右侧建哈希表，左侧逐行探测
+    Hash,
+    /// 两侧都已按连接键有序时直接归并
+    SortMerge
+}
+
+impl fmt::Display for JoinStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            JoinStrategy::NestedLoop => "Nested Loop",
+            JoinStrategy::Hash => "Hash Join",
+            JoinStrategy::SortMerge => "Merge Join"
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// 拆分后的连接条件
+///
+/// `ON` 中用 AND 连接的 `左侧表达式 = 右侧表达式` 成为连接键，其余部分在拼接后的行上计算
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct JoinCondition {
+    pub left_keys: Vec<Expr>,
+    pub right_keys: Vec<Expr>,
+    pub residual: Option<Expr>
+}
+
+impl JoinCondition {
+    /// `on` 中的列应已在拼接后的列描述上检查过
+    pub fn new(on: Option<&Expr>, left: &RowSchema, right: &RowSchema) -> Self {
+        let mut condition = JoinCondition { left_keys: Vec::new(), right_keys: Vec::new(), residual: None };
+        let mut conjuncts = Vec::new();
+        if let Some(on) = on {
+            split_conjuncts(on, &mut conjuncts);
+        }
+
+        for expr in conjuncts {
+            if let Expr::Binary { left: l, op: BinaryOp::Eq, right: r } = expr {
+                if only_references(l, left) && only_references(r, right) {
+                    condition.left_keys.push(l.as_ref().clone());
+                    condition.right_keys.push(r.as_ref().clone());
+                    continue;
+                }
+                if only_references(l, right) && only_references(r, left) {
+                    condition.left_keys.push(r.as_ref().clone());
+                    condition.right_keys.push(l.as_ref().clone());
+                    continue;
+                }
+            }
+            condition.residual = Some(match condition.residual.take() {
+                Some(residual) => Expr::Binary {
+                    left: Box::new(residual),
+                    op: BinaryOp::And,
+                    right: Box::new(expr.clone())
+                },
+                None => expr.clone()
+            });
+        }
+        condition
+    }
+
+    /// 按两侧输入已知的顺序选择连接算法，`left_order` 和 `right_order` 为输入按其升序排列（NULL 在最后）的表达式
+    ///
+    /// 有连接键时使用哈希连接，两侧都已按连接键有序时改用归并连接
+    pub fn strategy(&self, left_order: &[Expr], right_order: &[Expr]) -> JoinStrategy {
+        if self.left_keys.is_empty() {
+            JoinStrategy::NestedLoop
+        } else if left_order.starts_with(&self.left_keys) && right_order.starts_with(&self.right_keys) {
+            JoinStrategy::SortMerge
+        } else {
+            JoinStrategy::Hash
+        }
+    }
+}
+
+impl fmt::Display for JoinCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = self.left_keys
+            .iter()
+            .zip(&self.right_keys)
+            .map(|(l, r)| format!("{l} = {r}"))
+            .chain(self.residual.iter().map(|expr| expr.to_string()))
+            .collect();
+        if parts.is_empty() {
+            write!(f, "true")
+        } else {
+            write!(f, "{}", parts.join(" AND "))
+        }
+    }
+}
+
+fn split_conjuncts<'e>(expr: &'e Expr, conjuncts: &mut Vec<&'e Expr>) {
+    match expr {
+        Expr::Binary { left, op: BinaryOp::And, right } => {
+            split_conjuncts(left, conjuncts);
+            split_conjuncts(right, conjuncts);
+        }
+        _ => conjuncts.push(expr)
+    }
+}
+
+/// 表达式至少引用一列，且只引用 `schema` 中的列
+fn only_references(expr: &Expr, schema: &RowSchema) -> bool {
+    fn has_column(expr: &Expr) -> bool {
+        match expr {
+            Expr::Column(_) => true,
+            Expr::Literal(_) | Expr::NextVal(_) | Expr::Aggregate { .. } => false,
+            Expr::Unary { expr, .. } => has_column(expr),
+            Expr::Binary { left, right, .. } => has_column(left) || has_column(right),
+            Expr::InList { expr, list, .. } => has_column(expr) || list.iter().any(has_column)
+        }
+    }
+    !expr.contains_aggregate() && has_column(expr) && eval::check_columns(expr, schema).is_ok()
+}
+
+/// 连接的右侧，`new` 时读取右侧所有行，之后左侧的行逐行与之匹配，不需要保留左侧的行
+///
+/// 哈希连接用右侧建哈希表；归并连接要求两侧都已按连接键升序排列，右侧的游标随左侧的键前进
+pub(crate) struct JoinState {
+    kind: JoinKind,
+    strategy: JoinStrategy,
+    right: Vec<Row>,
+    right_keys: Vec<Vec<Value>>,
+    /// 哈希连接中连接键到右侧行的下标
+    buckets: HashMap<Vec<Value>, Vec<usize>>,
+    /// 归并连接中第一个连接键不小于当前左侧键的右侧行
+    cursor: usize,
+    /// 右侧每个连接键第一个不为 NULL 的值，探测前用它检查两侧的键能否比较，使结果与连接算法无关
+    right_samples: Vec<Option<Value>>,
+    /// 右侧每一行是否匹配过，用于右外连接和全外连接补齐
+    right_matched: Vec<bool>
+}
+
+impl JoinState {
+    pub fn new(
+        right: Vec<Row>,
+        kind: JoinKind,
+        condition: &JoinCondition,
+        strategy: JoinStrategy
+    ) -> Result<Self, QueryExceptionError> {
+        let right_keys = right
+            .iter()
+            .map(|row| evaluate_key(row, &condition.right_keys))
+            .collect::<Result<Vec<_>, _>>()?;
+        let right_samples = (0..condition.right_keys.len())
+            .map(|k| right_keys.iter().map(|key| &key[k]).find(|value| !value.is_null()).cloned())
+            .collect();
+        let mut buckets: HashMap<Vec<Value>, Vec<usize>> = HashMap::new();
+        if strategy == JoinStrategy::Hash {
+            for (r, key) in right_keys.iter().enumerate() {
+                if let Some(key) = hash_key(key.clone()) {
+                    buckets.entry(key).or_default().push(r);
+                }
+            }
+        }
+        Ok(Self {
+            kind,
+            strategy,
+            right_matched: vec![false; right.len()],
+            right,
+            right_keys,
+            buckets,
+            cursor: 0,
+            right_samples
+        })
+    }
+
+    /// 左侧一行的所有连接结果；外连接中没有匹配时用 NULL 补齐右侧
+    ///
+    /// 连接键含 NULL 的行不会匹配任何行；键与右侧的键不能比较时报错，与逐对比较的结果一致
+    pub fn probe(
+        &mut self,
+        left: &Row,
+        condition: &JoinCondition,
+        schema: &Rc<RowSchema>
+    ) -> Result<Vec<Row>, QueryExceptionError> {
+        let key = evaluate_key(left, &condition.left_keys)?;
+        for (value, sample) in key.iter().zip(&self.right_samples) {
+            if let Some(sample) = sample {
+                value.compare(sample)?;
+            }
+        }
+        let candidates: Vec<usize> = match self.strategy {
+            JoinStrategy::NestedLoop => (0..self.right.len()).collect(),
+            JoinStrategy::Hash => hash_key(key)
+                .and_then(|key| self.buckets.get(&key))
+                .cloned()
+                .unwrap_or_default(),
+            // 有序时含 NULL 的键都在最后，NULL 视为最大值，所以游标不会越过右侧的 NULL
+            JoinStrategy::SortMerge if has_null(&key) => Vec::new(),
+            JoinStrategy::SortMerge => {
+                while self.cursor < self.right.len() && compare(&self.right_keys[self.cursor], &key)? == Ordering::Less {
+                    self.cursor += 1;
+                }
+                let mut end = self.cursor;
+                while end < self.right.len() && compare(&self.right_keys[end], &key)? == Ordering::Equal {
+                    end += 1;
+                }
+                (self.cursor..end).collect()
+            }
+        };
+
+        let mut rows = Vec::new();
+        for r in candidates {
+            let row = concat(schema, left.values(), self.right[r].values());
+            let keep = match &condition.residual {
+                Some(residual) => eval::matches(residual, &row)?,
+                None => true
+            };
+            if keep {
+                self.right_matched[r] = true;
+                rows.push(row);
+            }
+        }
+        if rows.is_empty() && matches!(self.kind, JoinKind::Left | JoinKind::Full) {
+            let nulls = vec![Value::Null; schema.len() - left.values().len()];
+            rows.push(concat(schema, left.values(), &nulls));
+        }
+        Ok(rows)
+    }
+
+    /// 左侧读完后输出右侧没有匹配过的行，只有右外连接和全外连接会输出
+    pub fn finish(self, schema: &Rc<RowSchema>) -> Vec<Row> {
+        if !matches!(self.kind, JoinKind::Right | JoinKind::Full) {
+            return Vec::new();
+        }
+        self.right
+            .iter()
+            .zip(&self.right_matched)
+            .filter(|(_, matched)| !**matched)
+            .map(|(row, _)| {
+                let nulls = vec![Value::Null; schema.len() - row.values().len()];
+                concat(schema, &nulls, row.values())
+            })
+            .collect()
+    }
+}
+
+fn concat(schema: &Rc<RowSchema>, left: &[Value], right: &[Value]) -> Row {
+    Row::new(schema.clone(), left.iter().chain(right).cloned().collect())
+}
+
+fn evaluate_key(row: &Row, keys: &[Expr]) -> Result<Vec<Value>, QueryExceptionError> {
+    keys.iter().map(|key| eval::evaluate(key, row)).collect()
+}
+
+fn has_null(key: &[Value]) -> bool {
+    key.iter().any(Value::is_null)
+}
+
+/// 含 NULL 的键不参与匹配；整数值的浮点数转为整数，使 `1 = 1.0` 能在哈希表中匹配
+fn hash_key(key: Vec<Value>) -> Option<Vec<Value>> {
+    if has_null(&key) {
+        return None;
+    }
+    Some(key
+        .into_iter()
+        .map(|value| match value {
+            Value::Float(f) if f.fract() == 0.0 && f >= i64::MIN as f64 && f < i64::MAX as f64 => Value::Int(f as i64),
+            value => value
+        })
+        .collect())
+}
+
+/// 按升序比较两组连接键，NULL 视为最大值
+fn compare(left: &[Value], right: &[Value]) -> Result<Ordering, QueryExceptionError> {
+    for (l, r) in left.iter().zip(right) {
+        let ordering = match (l.is_null(), r.is_null()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => l.compare(r)?.expect("neither value is NULL")
+        };
+        if ordering != Ordering::Equal {
+            return Ok(ordering);
+        }
+    }
+    Ok(Ordering::Equal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sql_parser::expr::{ColumnRef, Literal};
+    use crate::row::OutputColumn;
+
+    fn schema(table: &str, columns: &[&str]) -> Rc<RowSchema> {
+        Rc::new(
+            columns
+                .iter()
+                .map(|name| OutputColumn { table: Some(table.into()), name: name.to_string() })
+                .collect()
+        )
+    }
+
+    fn rows(schema: &Rc<RowSchema>, values: impl IntoIterator<Item = Option<i64>>) -> Vec<Row> {
+        values
+            .into_iter()
+            .map(|v| Row::new(schema.clone(), vec![v.map_or(Value::Null, Value::Int)]))
+            .collect()
+    }
+
+    fn col(table: &str, name: &str) -> Expr {
+        Expr::Column(ColumnRef::qualified(table, name))
+    }
+
+    fn binary(left: Expr, op: BinaryOp, right: Expr) -> Expr {
+        Expr::Binary { left: Box::new(left), op, right: Box::new(right) }
+    }
+
+    /// 逐行探测左侧，再补齐右侧没有匹配的行
+    fn join_rows(
+        left: &[Row],
+        right: &[Row],
+        kind: JoinKind,
+        condition: &JoinCondition,
+        strategy: JoinStrategy,
+        schema: &Rc<RowSchema>
+    ) -> Result<Vec<Row>, QueryExceptionError> {
+        let mut state = JoinState::new(right.to_vec(), kind, condition, strategy)?;
+        let mut rows = Vec::new();
+        for row in left {
+            rows.extend(state.probe(row, condition, schema)?);
+        }
+        rows.extend(state.finish(schema));
+        Ok(rows)
+    }
+
+    /// 与顺序无关地比较连接结果
+    fn sorted(rows: Vec<Row>) -> Vec<String> {
+        let mut rows: Vec<String> = rows.iter().map(|row| format!("{:?}", row.values())).collect();
+        rows.sort();
+        rows
+    }
+
+    #[test]
+    fn test_join_condition() {
+        let left = schema("a", &["x"]);
+        let right = schema("b", &["y", "z"]);
+        let on = binary(
+            binary(
+                binary(col("b", "y"), BinaryOp::Eq, col("a", "x")),
+                BinaryOp::And,
+                binary(col("b", "z"), BinaryOp::Gt, Expr::Literal(Literal::Int(1)))
+            ),
+            BinaryOp::And,
+            binary(col("a", "x"), BinaryOp::Eq, Expr::Literal(Literal::Int(1)))
+        );
+
+        let condition = JoinCondition::new(Some(&on), &left, &right);
+        assert_eq!(condition.left_keys, vec![col("a", "x")]);
+        assert_eq!(condition.right_keys, vec![col("b", "y")]);
+        assert_eq!(condition.to_string(), "a.x = b.y AND b.z > 1 AND a.x = 1");
+
+        let condition = JoinCondition::new(None, &left, &right);
+        assert!(condition.left_keys.is_empty());
+        assert_eq!(condition.to_string(), "true");
+    }
+
+    #[test]
+    fn test_join_strategies() {
+        let left_schema = schema("a", &["x"]);
+        let right_schema = schema("b", &["y"]);
+        let joined: Rc<RowSchema> = Rc::new(left_schema.iter().chain(right_schema.iter()).cloned().collect());
+        let on = binary(col("a", "x"), BinaryOp::Eq, col("b", "y"));
+
+        let equi = JoinCondition::new(Some(&on), &left_schema, &right_schema);
+        let nested = JoinCondition { left_keys: Vec::new(), right_keys: Vec::new(), residual: Some(on) };
+
+        let left_values = [Some(3), Some(1), Some(2), None, Some(2)];
+        let right_values = [Some(2), Some(4), None, Some(3), Some(2)];
+        let mut left_sorted = left_values;
+        let mut right_sorted = right_values;
+        // Option 的 None 排在最前，而连接键的 NULL 视为最大值
+        left_sorted.sort_by_key(|v| (v.is_none(), *v));
+        right_sorted.sort_by_key(|v| (v.is_none(), *v));
+
+        for kind in [JoinKind::Inner, JoinKind::Left, JoinKind::Right, JoinKind::Full] {
+            let left = rows(&left_schema, left_values);
+            let right = rows(&right_schema, right_values);
+            let expected = join_rows(&left, &right, kind, &nested, JoinStrategy::NestedLoop, &joined).unwrap();
+            let hashed = join_rows(&left, &right, kind, &equi, JoinStrategy::Hash, &joined).unwrap();
+            assert_eq!(sorted(hashed), sorted(expected.clone()), "{kind:?}");
+
+            let left = rows(&left_schema, left_sorted);
+            let right = rows(&right_schema, right_sorted);
+            let merged = join_rows(&left, &right, kind, &equi, JoinStrategy::SortMerge, &joined).unwrap();
+            assert_eq!(sorted(merged), sorted(expected), "{kind:?}");
+        }
+
+        // 只有两侧都按连接键有序时才选择归并连接
+        assert_eq!(nested.strategy(&[], &[]), JoinStrategy::NestedLoop);
+        assert_eq!(equi.strategy(&[col("a", "x")], &[]), JoinStrategy::Hash);
+        assert_eq!(equi.strategy(&[col("a", "x")], &[col("b", "z")]), JoinStrategy::Hash);
+        assert_eq!(equi.strategy(&[col("a", "x")], &[col("b", "y"), col("b", "z")]), JoinStrategy::SortMerge);
+
+        assert_eq!(JoinStrategy::Hash.to_string(), "Hash Join");
+        assert_eq!(JoinStrategy::SortMerge.to_string(), "Merge Join");
+    }
+
+    #[test]
+    fn test_mismatched_key_types() {
+        let left_schema = schema("a", &["x"]);
+        let right_schema = schema("b", &["y"]);
+        let joined: Rc<RowSchema> = Rc::new(left_schema.iter().chain(right_schema.iter()).cloned().collect());
+        let on = binary(col("a", "x"), BinaryOp::Eq, col("b", "y"));
+        let condition = JoinCondition::new(Some(&on), &left_schema, &right_schema);
+
+        let left = rows(&left_schema, [None, Some(1)]);
+        let right: Vec<Row> = ["1", "2"]
+            .into_iter()
+            .map(|s| Row::new(right_schema.clone(), vec![Value::Text(s.into())]))
+            .collect();
+        // 哈希连接不会因为找不到相同的键而返回空结果
+        for strategy in [JoinStrategy::Hash, JoinStrategy::SortMerge] {
+            let error = join_rows(&left, &right, JoinKind::Inner, &condition, strategy, &joined).unwrap_err();
+            assert_eq!(error.to_string(), "Type mismatch: cannot compare 1 with 1", "{strategy:?}");
+        }
+        // 只有 NULL 的键不需要比较
+        for strategy in [JoinStrategy::Hash, JoinStrategy::SortMerge] {
+            let result = join_rows(&left[..1], &right, JoinKind::Left, &condition, strategy, &joined).unwrap();
+            assert_eq!(result.len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_large_equi_join() {
+        const N: i64 = 100_000;
+        let left_schema = schema("a", &["x"]);
+        let right_schema = schema("b", &["y"]);
+        let joined: Rc<RowSchema> = Rc::new(left_schema.iter().chain(right_schema.iter()).cloned().collect());
+        let on = binary(col("a", "x"), BinaryOp::Eq, col("b", "y"));
+        let condition = JoinCondition::new(Some(&on), &left_schema, &right_schema);
+
+        let left = rows(&left_schema, (0..N).map(|i| Some(i * 7 % N)));
+        let right = rows(&right_schema, (0..N).map(Some));
+        let result = join_rows(&left, &right, JoinKind::Inner, &condition, JoinStrategy::Hash, &joined).unwrap();
+        assert_eq!(result.len(), N as usize);
+
+        let left = rows(&left_schema, (0..N).map(Some));
+        let result = join_rows(&left, &right, JoinKind::Inner, &condition, JoinStrategy::SortMerge, &joined).unwrap();
+        assert_eq!(result.len(), N as usize);
+    }
+}
@@ -1,64 +1,245 @@
 mod table;
 mod row;
 mod error;
+mod eval;
+mod explain;
+mod foreign_key;
+mod index;
+mod aggregate;
+mod binder;
+mod join;
+mod optimizer;
+mod physical;
+mod plan;
+mod sequence;
+mod sort;
+mod statistics;
+mod value;
 
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::time::Instant;
 use derive_more::Display;
+use serde::{Deserialize, Serialize};
 use sql_parser::ast::{parse_sql_query, SqlQuery};
+use sql_parser::commands::SelectStatement;
+use crate::binder::Binder;
+use crate::optimizer::Optimizer;
+use crate::plan::LogicalPlan;
 use crate::error::{QueryExceptionError, SQLError};
-use crate::row::Row;
+use crate::sequence::Sequence;
 use crate::table::Table;
 
+pub use crate::row::{OutputColumn, Row};
+pub use crate::value::Value;
+
 #[derive(Debug, Display)]
-pub enum ExecResponse<'a> {
+pub enum ExecResponse {
     #[display(fmt = "{_0:?}")] // only show the values not "Select(...)"
-    Select(Vec<Row<'a>>),
-    Insert,
-    Create
+    Select(Vec<Row>),
+    #[display(fmt = "Inserted {rows_affected} rows")]
+    Insert { rows_affected: usize },
+    #[display(fmt = "Updated {rows_affected} rows")]
+    Update { rows_affected: usize },
+    #[display(fmt = "Deleted {rows_affected} rows")]
+    Delete { rows_affected: usize },
+    Create,
+    Drop,
+    CreateIndex,
+    DropIndex,
+    CreateSequence,
+    DropSequence,
+    #[display(fmt = "Truncated {rows_affected} rows")]
+    Truncate { rows_affected: usize },
+    #[display(fmt = "Analyzed {tables} tables")]
+    Analyze { tables: usize },
+    /// 计划树，每个算子一行
+    #[display(fmt = "{_0}")]
+    Explain(String)
 }
 
-#[derive(Debug, Default)]
+/// 序列化时保存表和序列，取出过的序列值在恢复后也不会再次取出
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Execution {
-    tables: HashMap<String, Table>
+    tables: HashMap<String, Table>,
+    // CREATE SEQUENCE 建立的序列，名字与表名互不冲突；查询的投影在执行中通过 `nextval` 取值
+    sequences: RefCell<HashMap<String, Sequence>>,
+    // 规则开关只用于调试，不随表一起保存
+    #[serde(skip)]
+    optimizer: Optimizer
 }
 
 impl Execution {
     pub fn new() -> Self {
         Self {
-            tables: HashMap::new()
+            tables: HashMap::new(),
+            sequences: RefCell::new(HashMap::new()),
+            optimizer: Optimizer::new()
         }
     }
 
+    /// 开关一条优化规则，用于调试；规则名不存在时返回 `false`
+    ///
+    /// 规则有 `constant_folding`、`predicate_pushdown`、`join_reorder`、`index_selection`、`limit_pushdown` 和 `projection_pruning`
+    pub fn set_optimizer_rule(&mut self, name: &str, enabled: bool) -> bool {
+        self.optimizer.set_enabled(name, enabled)
+    }
+
     pub fn run(&mut self, query: SqlQuery) -> Result<ExecResponse, QueryExceptionError> {
         // 判别语义
         match query {
             SqlQuery::Select(select) => {
-                let table = select.table;
-                let table = self
-                    .tables
-                    .get(&table)
-                    .ok_or(QueryExceptionError::TableNotFound(table))?;
-
-                let rows = table.iter().collect();
+                let plan = self.plan_select(select)?;
+                let mut operator = physical::build(&self.tables, &self.sequences, &plan)?;
+                let rows = physical::collect(operator.as_mut())?;
                 Ok(ExecResponse::Select(rows))
             }
+            SqlQuery::Explain(explain) => {
+                let plan = self.plan_select(explain.query)?;
+                if !explain.analyze {
+                    let operator = physical::build(&self.tables, &self.sequences, &plan)?;
+                    return Ok(ExecResponse::Explain(explain::format_plan(operator.as_ref())));
+                }
+
+                let mut operator = physical::build_instrumented(&self.tables, &self.sequences, &plan)?;
+                let start = Instant::now();
+                physical::collect(operator.as_mut())?;
+                let elapsed = start.elapsed();
+                let mut text = explain::format_plan(operator.as_ref());
+                text.push_str(&format!("Execution Time: {}", explain::format_duration(elapsed)));
+                Ok(ExecResponse::Explain(text))
+            }
             SqlQuery::Insert(insert) => {
                 let Some(table) = self.tables.get_mut(&insert.table) else {
                     return Err(QueryExceptionError::TableNotFound(insert.table))
                 };
 
-                table.insert(insert.values);
-                Ok(ExecResponse::Insert)
+                let rows = table.prepare_insert(insert.columns.as_deref(), &insert.values, self.sequences.get_mut())?;
+                foreign_key::check_references(&self.tables, &insert.table, &rows)?;
+                let rows_affected = self.table_mut(&insert.table).write(rows)?;
+                Ok(ExecResponse::Insert { rows_affected })
+            }
+            SqlQuery::Update(update) => {
+                let Some(table) = self.tables.get(&update.table) else {
+                    return Err(QueryExceptionError::TableNotFound(update.table))
+                };
+
+                let rows = table.prepare_update(&update.assignments, update.where_clause.as_ref(), self.sequences.get_mut())?;
+                foreign_key::check_references(&self.tables, &update.table, &rows)?;
+                foreign_key::check_referenced(&self.tables, &update.table, &rows)?;
+                let rows_affected = self.table_mut(&update.table).write(rows)?;
+                Ok(ExecResponse::Update { rows_affected })
+            }
+            SqlQuery::Delete(delete) => {
+                let Some(table) = self.tables.get(&delete.table) else {
+                    return Err(QueryExceptionError::TableNotFound(delete.table))
+                };
+
+                // 没有被引用的表直接删除
+                if !foreign_key::is_referenced(&self.tables, &delete.table) {
+                    let rows_affected = self.table_mut(&delete.table).delete(delete.where_clause.as_ref())?;
+                    return Ok(ExecResponse::Delete { rows_affected });
+                }
+                let keys = table.matching_keys(delete.where_clause.as_ref())?;
+                let rows_affected = foreign_key::delete(&mut self.tables, &delete.table, keys)?;
+                Ok(ExecResponse::Delete { rows_affected })
             }
             SqlQuery::Create(create) => {
-                let table = Table::new(create.columns);
+                if self.tables.contains_key(&create.table) {
+                    if create.if_not_exists {
+                        return Ok(ExecResponse::Create);
+                    }
+                    return Err(QueryExceptionError::TableAlreadyExists(create.table));
+                }
 
+                let mut table = Table::new(&create.table, create.columns, &create.constraints)?;
+                sequence::check_defaults(&table, self.sequences.get_mut())?;
+                let foreign_keys = foreign_key::resolve(&self.tables, &create.table, &table, &create.constraints)?;
+                table.set_foreign_keys(foreign_keys);
                 self.tables.insert(create.table, table);
                 Ok(ExecResponse::Create)
             }
+            SqlQuery::Drop(drop) => {
+                foreign_key::check_drop_table(&self.tables, &drop.table)?;
+                if self.tables.remove(&drop.table).is_none() && !drop.if_exists {
+                    return Err(QueryExceptionError::TableNotFound(drop.table));
+                }
+                Ok(ExecResponse::Drop)
+            }
+            SqlQuery::Truncate(truncate) => {
+                foreign_key::check_drop_table(&self.tables, &truncate.table)?;
+                let Some(table) = self.tables.get_mut(&truncate.table) else {
+                    return Err(QueryExceptionError::TableNotFound(truncate.table))
+                };
+
+                let rows_affected = table.truncate();
+                Ok(ExecResponse::Truncate { rows_affected })
+            }
+            SqlQuery::CreateIndex(create) => {
+                // 索引名在整个库中唯一，DROP INDEX 时不需要指定表
+                if self.tables.values().any(|table| table.indexes().iter().any(|index| index.name == create.name)) {
+                    return Err(QueryExceptionError::IndexAlreadyExists(create.name));
+                }
+                let Some(table) = self.tables.get_mut(&create.table) else {
+                    return Err(QueryExceptionError::TableNotFound(create.table))
+                };
+
+                table.create_index(create.name, &create.columns, create.unique, create.kind)?;
+                Ok(ExecResponse::CreateIndex)
+            }
+            SqlQuery::DropIndex(drop) => {
+                foreign_key::check_drop_index(&self.tables, &drop.name)?;
+                let dropped = self.tables.values_mut().any(|table| table.drop_index(&drop.name));
+                if !dropped && !drop.if_exists {
+                    return Err(QueryExceptionError::IndexNotFound(drop.name));
+                }
+                Ok(ExecResponse::DropIndex)
+            }
+            SqlQuery::CreateSequence(create) => {
+                let sequences = self.sequences.get_mut();
+                if sequences.contains_key(&create.name) {
+                    return Err(QueryExceptionError::SequenceAlreadyExists(create.name));
+                }
+                if create.increment <= 0 {
+                    return Err(QueryExceptionError::InvalidSequenceIncrement(create.increment));
+                }
+
+                sequences.insert(create.name, Sequence::new(create.start, create.increment));
+                Ok(ExecResponse::CreateSequence)
+            }
+            SqlQuery::DropSequence(drop) => {
+                sequence::check_drop(&self.tables, &drop.name)?;
+                if self.sequences.get_mut().remove(&drop.name).is_none() && !drop.if_exists {
+                    return Err(QueryExceptionError::SequenceNotFound(drop.name));
+                }
+                Ok(ExecResponse::DropSequence)
+            }
+            SqlQuery::Analyze(analyze) => {
+                let Some(name) = analyze.table else {
+                    self.tables.values_mut().for_each(Table::analyze);
+                    return Ok(ExecResponse::Analyze { tables: self.tables.len() });
+                };
+                let Some(table) = self.tables.get_mut(&name) else {
+                    return Err(QueryExceptionError::TableNotFound(name))
+                };
+
+                table.analyze();
+                Ok(ExecResponse::Analyze { tables: 1 })
+            }
         }
     }
 
+    /// 已经确认存在的表
+    fn table_mut(&mut self, name: &str) -> &mut Table {
+        self.tables.get_mut(name).expect("table was looked up before")
+    }
+
+    /// 绑定并优化查询
+    fn plan_select(&self, select: SelectStatement) -> Result<LogicalPlan, QueryExceptionError> {
+        let plan = Binder::new(&self.tables).bind_select(select)?;
+        self.optimizer.optimize(&self.tables, plan)
+    }
+
     pub fn parse_and_run<'a>(&mut self, query: &'a str) -> Result<ExecResponse, SQLError<'a>> {
         let query = parse_sql_query(query)?;
         let res = self.run(query)?;
@@ -66,7 +247,1095 @@ impl Execution {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn select_ids(exec: &mut Execution, query: &str) -> Vec<Value> {
+        match exec.parse_and_run(query).unwrap() {
+            ExecResponse::Select(rows) => rows.iter().map(|row| row.get("id")).collect(),
+            other => panic!("expected select response, got {other:?}")
+        }
+    }
+
+    #[test]
+    fn test_select_where() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE TABLE t (id int, name string);").unwrap();
+        exec.parse_and_run("INSERT INTO t VALUES (9, 'foo');").unwrap();
+        exec.parse_and_run("INSERT INTO t VALUES (10, 'bar');").unwrap();
+        exec.parse_and_run("INSERT INTO t VALUES (11, 'foo');").unwrap();
+
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE id < 10;"), vec![Value::Int(9)]);
+        assert_eq!(
+            select_ids(&mut exec, "SELECT id FROM t WHERE name = 'foo' AND NOT (id = 9 OR id > 11);"),
+            vec![Value::Int(11)]
+        );
+        assert!(exec.parse_and_run("SELECT id FROM t WHERE missing = 1;").is_err());
+    }
+
+    #[test]
+    fn test_typed_values() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE TABLE t (id int, name string);").unwrap();
+        exec.parse_and_run("INSERT INTO t VALUES (9, 'foo');").unwrap();
+        exec.parse_and_run("INSERT INTO t VALUES (10, 'bar');").unwrap();
+
+        // 按数字而不是字符串比较
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE id > 9;"), vec![Value::Int(10)]);
+        assert!(exec.parse_and_run("INSERT INTO t VALUES ('abc', 'baz');").is_err());
+        assert!(exec.parse_and_run("SELECT id FROM t WHERE name = 1;").is_err());
+    }
+
+    #[test]
+    fn test_insert_literals() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE TABLE t (id int, name string, score float);").unwrap();
+        exec.parse_and_run("INSERT INTO t VALUES (1, 'hello world', 1.5);").unwrap();
+        exec.parse_and_run("INSERT INTO t VALUES (-2, 'it''s', 3);").unwrap();
+        exec.parse_and_run("INSERT INTO t VALUES (3, NULL, NULL);").unwrap();
+
+        let ExecResponse::Select(rows) = exec.parse_and_run("SELECT * FROM t;").unwrap() else {
+            panic!("expected select response")
+        };
+        let values: Vec<Vec<Value>> = rows
+            .iter()
+            .map(|row| row.columns().iter().map(|col| row.get(&col.name)).collect())
+            .collect();
+        assert_eq!(values, vec![
+            vec![Value::Int(1), Value::Text("hello world".into()), Value::Float(1.5)],
+            vec![Value::Int(-2), Value::Text("it's".into()), Value::Float(3.0)],
+            vec![Value::Int(3), Value::Null, Value::Null],
+        ]);
+
+        // NULL 比较的结果未知，不会匹配
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE name = NULL;"), vec![]);
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE score > 2;"), vec![Value::Int(-2)]);
+        assert!(exec.parse_and_run("INSERT INTO t VALUES (1.5, 'x', 1);").is_err());
+    }
+
+    #[test]
+    fn test_insert_columns_multi_row() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE TABLE t (id int, name string);").unwrap();
+
+        assert!(matches!(
+            exec.parse_and_run("INSERT INTO t (name, id) VALUES ('a', 1), ('b', 2);").unwrap(),
+            ExecResponse::Insert { rows_affected: 2 }
+        ));
+        exec.parse_and_run("INSERT INTO t (id) VALUES (3);").unwrap();
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE name = 'b';"), vec![Value::Int(2)]);
+
+        let ExecResponse::Select(rows) = exec.parse_and_run("SELECT name FROM t WHERE id = 3;").unwrap() else {
+            panic!("expected select response")
+        };
+        assert_eq!(rows[0].get("name"), Value::Null);
+
+        // 任意一行出错时整条语句都不会写入
+        assert!(matches!(
+            exec.parse_and_run("INSERT INTO t VALUES (4, 'd'), (5);"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::ValueCountMismatch { expected: 2, found: 1 }))
+        ));
+        assert!(matches!(
+            exec.parse_and_run("INSERT INTO t (id, id) VALUES (4, 4);"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::DuplicateColumn(_)))
+        ));
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE id > 3;"), vec![]);
+    }
+
+    #[test]
+    fn test_update() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE TABLE t (id int, score float, name string);").unwrap();
+        exec.parse_and_run("INSERT INTO t VALUES (1, 1.5, 'a'), (2, 2, 'b'), (3, NULL, 'c');").unwrap();
+
+        assert!(matches!(
+            exec.parse_and_run("UPDATE t SET id = id * 10, name = 'x' WHERE id >= 2;").unwrap(),
+            ExecResponse::Update { rows_affected: 2 }
+        ));
+        assert_eq!(
+            select_ids(&mut exec, "SELECT id FROM t WHERE name = 'x';"),
+            vec![Value::Int(20), Value::Int(30)]
+        );
+
+        // 整数结果会按列类型转换为浮点数
+        exec.parse_and_run("UPDATE t SET score = id + 2 WHERE id = 1;").unwrap();
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE score = 3.0;"), vec![Value::Int(1)]);
+
+        assert!(matches!(
+            exec.parse_and_run("UPDATE t SET id = id / 0;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::ArithmeticError(_)))
+        ));
+        assert!(exec.parse_and_run("UPDATE t SET id = 'abc';").is_err());
+        assert!(exec.parse_and_run("UPDATE t SET nope = 1;").is_err());
+        // 失败的语句不会修改任何行
+        assert_eq!(
+            select_ids(&mut exec, "SELECT id FROM t;"),
+            vec![Value::Int(1), Value::Int(20), Value::Int(30)]
+        );
+    }
+
+    #[test]
+    fn test_projection() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE TABLE t (id int, name string, age int);").unwrap();
+        exec.parse_and_run("INSERT INTO t VALUES (1, 'foo', 20);").unwrap();
+
+        let column_names = |res: ExecResponse| match res {
+            ExecResponse::Select(rows) => rows[0]
+                .columns()
+                .iter()
+                .map(|col| col.name.clone())
+                .collect::<Vec<_>>(),
+            other => panic!("expected select response, got {other:?}")
+        };
+
+        assert_eq!(column_names(exec.parse_and_run("SELECT age, id FROM t;").unwrap()), vec!["age", "id"]);
+        assert_eq!(column_names(exec.parse_and_run("SELECT * FROM t;").unwrap()), vec!["id", "name", "age"]);
+        // 过滤可以使用未投影的列
+        assert_eq!(
+            column_names(exec.parse_and_run("SELECT name FROM t WHERE age = 20;").unwrap()),
+            vec!["name"]
+        );
+        assert!(matches!(
+            exec.parse_and_run("SELECT nope FROM t;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::ColumnDoesNotExists(_)))
+        ));
+    }
+
+    #[test]
+    fn test_delete() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE TABLE t (id int);").unwrap();
+        exec.parse_and_run("INSERT INTO t VALUES (1), (2), (3);").unwrap();
+
+        assert!(matches!(
+            exec.parse_and_run("DELETE FROM t WHERE id >= 2;").unwrap(),
+            ExecResponse::Delete { rows_affected: 2 }
+        ));
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t;"), vec![Value::Int(1)]);
+
+        exec.parse_and_run("INSERT INTO t VALUES (4);").unwrap();
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t;"), vec![Value::Int(1), Value::Int(4)]);
+
+        assert!(matches!(
+            exec.parse_and_run("DELETE FROM t;").unwrap(),
+            ExecResponse::Delete { rows_affected: 2 }
+        ));
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t;"), vec![]);
+    }
+
+    #[test]
+    fn test_create_drop_truncate() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE TABLE t (id int);").unwrap();
+        exec.parse_and_run("INSERT INTO t VALUES (1), (2);").unwrap();
+
+        // 重复建表不会覆盖已有数据
+        assert!(matches!(
+            exec.parse_and_run("CREATE TABLE t (id int);"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::TableAlreadyExists(_)))
+        ));
+        exec.parse_and_run("CREATE TABLE IF NOT EXISTS t (id int);").unwrap();
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t;"), vec![Value::Int(1), Value::Int(2)]);
 
+        assert!(matches!(
+            exec.parse_and_run("TRUNCATE TABLE t;").unwrap(),
+            ExecResponse::Truncate { rows_affected: 2 }
+        ));
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t;"), vec![]);
+
+        exec.parse_and_run("DROP TABLE t;").unwrap();
+        assert!(matches!(
+            exec.parse_and_run("DROP TABLE t;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::TableNotFound(_)))
+        ));
+        exec.parse_and_run("DROP TABLE IF EXISTS t;").unwrap();
+        assert!(exec.parse_and_run("SELECT id FROM t;").is_err());
+    }
+
+    #[test]
+    fn test_order_by() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE TABLE t (id int, grp string, score int);").unwrap();
+        exec.parse_and_run(
+            "INSERT INTO t VALUES (1, 'b', 10), (2, 'a', NULL), (3, 'b', 9), (4, 'a', 30), (5, 'b', 10);"
+        ).unwrap();
+
+        // 按数字而不是字符串排序，相同键保持插入顺序
+        assert_eq!(
+            select_ids(&mut exec, "SELECT id FROM t ORDER BY score;"),
+            vec![Value::Int(3), Value::Int(1), Value::Int(5), Value::Int(4), Value::Int(2)]
+        );
+        assert_eq!(
+            select_ids(&mut exec, "SELECT id FROM t ORDER BY score DESC;"),
+            vec![Value::Int(2), Value::Int(4), Value::Int(1), Value::Int(5), Value::Int(3)]
+        );
+        assert_eq!(
+            select_ids(&mut exec, "SELECT id FROM t ORDER BY score ASC NULLS FIRST;"),
+            vec![Value::Int(2), Value::Int(3), Value::Int(1), Value::Int(5), Value::Int(4)]
+        );
+        assert_eq!(
+            select_ids(&mut exec, "SELECT id FROM t WHERE id > 1 ORDER BY grp, score DESC NULLS LAST, id DESC;"),
+            vec![Value::Int(4), Value::Int(2), Value::Int(5), Value::Int(3)]
+        );
+
+        // 不带表名的名字先按输出列的别名解析，再按输入列解析
+        assert_eq!(
+            select_ids(&mut exec, "SELECT id, score AS x FROM t WHERE score > 0 ORDER BY x DESC, id;"),
+            vec![Value::Int(4), Value::Int(1), Value::Int(5), Value::Int(3)]
+        );
+        assert_eq!(
+            select_ids(&mut exec, "SELECT id, 0 - id AS score FROM t ORDER BY score LIMIT 2;"),
+            vec![Value::Int(5), Value::Int(4)]
+        );
+        assert_eq!(
+            select_ids(&mut exec, "SELECT id, id AS x FROM t ORDER BY t.score, x DESC LIMIT 2;"),
+            vec![Value::Int(3), Value::Int(5)]
+        );
+        assert!(matches!(
+            exec.parse_and_run("SELECT id AS x, score AS x FROM t ORDER BY x;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::AmbiguousColumn(_)))
+        ));
+        assert!(exec.parse_and_run("SELECT id FROM t ORDER BY nope;").is_err());
+    }
+
+    #[test]
+    fn test_limit_offset() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE TABLE t (id int);").unwrap();
+        exec.parse_and_run("INSERT INTO t VALUES (1), (2), (3), (4), (5);").unwrap();
+
+        assert_eq!(
+            select_ids(&mut exec, "SELECT id FROM t LIMIT 2;"),
+            vec![Value::Int(1), Value::Int(2)]
+        );
+        assert_eq!(
+            select_ids(&mut exec, "SELECT id FROM t LIMIT 2 OFFSET 2;"),
+            vec![Value::Int(3), Value::Int(4)]
+        );
+        assert_eq!(
+            select_ids(&mut exec, "SELECT id FROM t ORDER BY id DESC LIMIT 2 OFFSET 1;"),
+            vec![Value::Int(4), Value::Int(3)]
+        );
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t LIMIT 10 OFFSET 4;"), vec![Value::Int(5)]);
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t LIMIT 0;"), vec![]);
+        // 达到 LIMIT 后不再求值剩余行的谓词
+        assert_eq!(
+            select_ids(&mut exec, "SELECT id FROM t WHERE 10 / (5 - id) > 0 LIMIT 2;"),
+            vec![Value::Int(1), Value::Int(2)]
+        );
+    }
+
+    fn select_values(exec: &mut Execution, query: &str) -> Vec<Vec<Value>> {
+        match exec.parse_and_run(query).unwrap() {
+            ExecResponse::Select(rows) => rows.iter().map(|row| row.values().to_vec()).collect(),
+            other => panic!("expected select response, got {other:?}")
+        }
+    }
+
+    #[test]
+    fn test_aggregates() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE TABLE t (id int, grp string, score int);").unwrap();
+
+        // 没有 GROUP BY 时空表也返回一行
+        assert_eq!(
+            select_values(&mut exec, "SELECT COUNT(*), SUM(score), AVG(score), MIN(score) FROM t;"),
+            vec![vec![Value::Int(0), Value::Null, Value::Null, Value::Null]]
+        );
+
+        exec.parse_and_run(
+            "INSERT INTO t VALUES (1, 'b', 10), (2, 'a', NULL), (3, 'b', 20), (4, 'a', 30), (5, 'c', 5);"
+        ).unwrap();
+
+        assert_eq!(
+            select_values(&mut exec, "SELECT COUNT(*), COUNT(score), SUM(score), AVG(score), MIN(score), MAX(score) FROM t;"),
+            vec![vec![
+                Value::Int(5), Value::Int(4), Value::Int(65), Value::Float(16.25), Value::Int(5), Value::Int(30)
+            ]]
+        );
+        assert_eq!(
+            select_values(&mut exec, "SELECT grp, COUNT(*), SUM(score) + 1 FROM t GROUP BY grp;"),
+            vec![
+                vec![Value::Text("b".into()), Value::Int(2), Value::Int(31)],
+                vec![Value::Text("a".into()), Value::Int(2), Value::Int(31)],
+                vec![Value::Text("c".into()), Value::Int(1), Value::Int(6)]
+            ]
+        );
+        assert_eq!(
+            select_values(
+                &mut exec,
+                "SELECT grp, MAX(score) AS top FROM t WHERE id > 1 GROUP BY grp HAVING COUNT(*) > 1 ORDER BY MAX(score) DESC;"
+            ),
+            vec![vec![Value::Text("a".into()), Value::Int(30)]]
+        );
+
+        assert_eq!(
+            select_values(&mut exec, "SELECT grp, COUNT(*) AS n, MIN(score) AS low FROM t GROUP BY grp ORDER BY n, low DESC;"),
+            vec![
+                vec![Value::Text("c".into()), Value::Int(1), Value::Int(5)],
+                vec![Value::Text("a".into()), Value::Int(2), Value::Int(30)],
+                vec![Value::Text("b".into()), Value::Int(2), Value::Int(10)]
+            ]
+        );
+
+        let ExecResponse::Select(rows) = exec.parse_and_run("SELECT grp, COUNT(*) AS n FROM t GROUP BY grp;").unwrap() else {
+            panic!("expected select response")
+        };
+        assert_eq!(
+            rows[0].columns().iter().map(|col| col.name.as_str()).collect::<Vec<_>>(),
+            vec!["grp", "n"]
+        );
+
+        // 带不带表名都引用同一列
+        assert_eq!(
+            select_values(&mut exec, "SELECT t.grp, COUNT(*) FROM t GROUP BY grp ORDER BY grp LIMIT 1;"),
+            vec![vec![Value::Text("a".into()), Value::Int(2)]]
+        );
+
+        assert!(matches!(
+            exec.parse_and_run("SELECT id, COUNT(*) FROM t GROUP BY grp;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::NotGrouped(_)))
+        ));
+        assert!(matches!(
+            exec.parse_and_run("SELECT id FROM t WHERE COUNT(*) > 1;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::MisplacedAggregate(_)))
+        ));
+        assert!(exec.parse_and_run("SELECT SUM(grp) FROM t;").is_err());
+    }
 
+    #[test]
+    fn test_joins() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE TABLE users (id int, name string);").unwrap();
+        exec.parse_and_run("CREATE TABLE orders (id int, uid int, total int);").unwrap();
+        exec.parse_and_run("INSERT INTO users VALUES (1, 'ann'), (2, 'bob'), (3, 'cat');").unwrap();
+        exec.parse_and_run("INSERT INTO orders VALUES (10, 1, 5), (11, 1, 7), (12, 2, 3), (13, 9, 1);").unwrap();
 
+        let text = |s: &str| Value::Text(s.into());
+        assert_eq!(
+            select_values(&mut exec, "SELECT u.name, o.total FROM users u JOIN orders o ON u.id = o.uid;"),
+            vec![
+                vec![text("ann"), Value::Int(5)],
+                vec![text("ann"), Value::Int(7)],
+                vec![text("bob"), Value::Int(3)]
+            ]
+        );
+        assert_eq!(
+            select_values(&mut exec, "SELECT name, o.id FROM users LEFT JOIN orders AS o ON users.id = uid WHERE users.id > 1;"),
+            vec![vec![text("bob"), Value::Int(12)], vec![text("cat"), Value::Null]]
+        );
+        assert_eq!(
+            select_values(&mut exec, "SELECT u.id, o.id FROM users u RIGHT JOIN orders o ON u.id = o.uid AND o.total > 4;"),
+            vec![
+                vec![Value::Int(1), Value::Int(10)],
+                vec![Value::Int(1), Value::Int(11)],
+                vec![Value::Null, Value::Int(12)],
+                vec![Value::Null, Value::Int(13)]
+            ]
+        );
+        assert_eq!(
+            select_values(&mut exec, "SELECT u.id, o.id FROM users u FULL OUTER JOIN orders o ON u.id = o.uid ORDER BY o.id;")
+                .into_iter()
+                .filter(|row| row.contains(&Value::Null))
+                .collect::<Vec<_>>(),
+            vec![vec![Value::Null, Value::Int(13)], vec![Value::Int(3), Value::Null]]
+        );
+        assert_eq!(
+            select_values(&mut exec, "SELECT COUNT(*) FROM users, orders;"),
+            vec![vec![Value::Int(12)]]
+        );
+        assert_eq!(
+            select_values(&mut exec, "SELECT a.id, b.id FROM users a, users b WHERE a.id + 1 = b.id;"),
+            vec![vec![Value::Int(1), Value::Int(2)], vec![Value::Int(2), Value::Int(3)]]
+        );
 
+        let ExecResponse::Select(rows) = exec.parse_and_run("SELECT o.*, u.name FROM users u JOIN orders o ON u.id = o.uid LIMIT 1;").unwrap() else {
+            panic!("expected select response")
+        };
+        assert_eq!(
+            rows[0].columns().iter().map(|col| col.name.as_str()).collect::<Vec<_>>(),
+            vec!["id", "uid", "total", "name"]
+        );
+
+        assert!(matches!(
+            exec.parse_and_run("SELECT id FROM users JOIN orders ON users.id = orders.uid;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::AmbiguousColumn(_)))
+        ));
+        assert!(matches!(
+            exec.parse_and_run("SELECT * FROM users, users;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::DuplicateTableReference(_)))
+        ));
+        assert!(matches!(
+            exec.parse_and_run("SELECT * FROM users u JOIN orders o ON users.id = o.uid;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::ColumnDoesNotExists(_)))
+        ));
+        assert_eq!(
+            exec.parse_and_run("SELECT u.id FROM users u JOIN orders o ON u.name = o.uid;").unwrap_err().to_string(),
+            "Type mismatch: cannot compare ann with 1"
+        );
+        assert!(matches!(
+            exec.parse_and_run("SELECT * FROM users JOIN nope ON users.id = nope.id;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::TableNotFound(_)))
+        ));
+    }
+
+    #[test]
+    fn test_optimizer_rules_preserve_results() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE TABLE users (id int, age int);").unwrap();
+        exec.parse_and_run("CREATE TABLE orders (id int, uid int, total int);").unwrap();
+        exec.parse_and_run("INSERT INTO users VALUES (1, 20), (2, 30), (3, NULL);").unwrap();
+        exec.parse_and_run("INSERT INTO orders VALUES (10, 1, 5), (11, 1, 7), (12, 2, 3), (13, 9, 1);").unwrap();
+
+        let queries = [
+            "SELECT u.id, o.total FROM users u, orders o WHERE 1 = 1 AND u.id = o.uid AND o.total > 3 ORDER BY o.total;",
+            "SELECT u.id, o.id FROM users u LEFT JOIN orders o ON u.id = o.uid AND o.total > 4 WHERE o.total > 6 OR u.age > 25 ORDER BY u.id, o.id;",
+            "SELECT u.id, o.id FROM users u RIGHT JOIN orders o ON u.id = o.uid WHERE u.age > 25 OR o.total < 2 ORDER BY o.id;",
+            "SELECT age, COUNT(*) FROM users WHERE 2 > 1 GROUP BY age HAVING COUNT(*) > 0 ORDER BY age LIMIT 2 OFFSET 1;",
+            "SELECT total FROM orders WHERE 1 = 0;",
+            "SELECT total FROM orders WHERE NULL;",
+            "SELECT id FROM users ORDER BY age DESC LIMIT 2;"
+        ];
+        for query in queries {
+            let optimized = select_values(&mut exec, query);
+            for rule in ["constant_folding", "predicate_pushdown", "join_reorder", "index_selection", "limit_pushdown", "projection_pruning"] {
+                assert!(exec.set_optimizer_rule(rule, false));
+            }
+            assert_eq!(select_values(&mut exec, query), optimized, "{query}");
+            for rule in ["constant_folding", "predicate_pushdown", "join_reorder", "index_selection", "limit_pushdown", "projection_pruning"] {
+                exec.set_optimizer_rule(rule, true);
+            }
+        }
+        assert!(!exec.set_optimizer_rule("nope", false));
+    }
+
+    #[test]
+    fn test_analyze() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE TABLE a (id int, v int);").unwrap();
+        exec.parse_and_run("CREATE TABLE b (id int, aid int);").unwrap();
+        exec.parse_and_run("CREATE TABLE c (id int, bid int);").unwrap();
+        exec.parse_and_run("INSERT INTO a VALUES (1, 10), (2, 20);").unwrap();
+        exec.parse_and_run("INSERT INTO b VALUES (1, 1), (2, 1), (3, 2), (4, 2), (5, 2);").unwrap();
+        exec.parse_and_run("INSERT INTO c VALUES (1, 1), (2, 3), (3, 5), (4, 5), (5, 9);").unwrap();
+
+        let query = "SELECT a.v, c.id FROM c, b, a WHERE c.bid = b.id AND b.aid = a.id AND a.v > 10 ORDER BY c.id;";
+        let before = select_values(&mut exec, query);
+        assert_eq!(before, vec![vec![Value::Int(20), Value::Int(2)], vec![Value::Int(20), Value::Int(3)], vec![Value::Int(20), Value::Int(4)]]);
+
+        assert!(matches!(exec.parse_and_run("ANALYZE b;").unwrap(), ExecResponse::Analyze { tables: 1 }));
+        assert!(matches!(exec.parse_and_run("ANALYZE;").unwrap(), ExecResponse::Analyze { tables: 3 }));
+        assert_eq!(exec.tables["b"].statistics().unwrap().row_count, 5);
+        assert_eq!(exec.tables["b"].statistics().unwrap().columns[1].distinct_count, 2);
+        assert_eq!(select_values(&mut exec, query), before);
+
+        assert!(matches!(
+            exec.parse_and_run("ANALYZE nope;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::TableNotFound(_)))
+        ));
+    }
+
+    #[test]
+    fn test_explain() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE TABLE users (id int, age int);").unwrap();
+        exec.parse_and_run("CREATE TABLE orders (id int, uid int, total int);").unwrap();
+        exec.parse_and_run("INSERT INTO users VALUES (1, 20), (2, 30), (3, 40);").unwrap();
+        exec.parse_and_run("INSERT INTO orders VALUES (10, 3, 5), (11, 1, 7), (12, 2, 3), (13, 9, 1);").unwrap();
+
+        let explain = |exec: &mut Execution, sql: &str| match exec.parse_and_run(sql).unwrap() {
+            ExecResponse::Explain(text) => text,
+            other => panic!("expected explain response, got {other:?}")
+        };
+        let query = "SELECT u.age, o.total FROM users u JOIN orders o ON u.id = o.uid WHERE o.total > 2 ORDER BY o.total LIMIT 2";
+        assert_eq!(
+            explain(&mut exec, &format!("EXPLAIN {query};")),
+            "Projection: u.age, o.total\n\
+             \x20 Limit: 2 OFFSET 0\n\
+             \x20   Sort: o.total ASC NULLS LAST, fetch=2\n\
+             \x20     Hash Join (Inner): u.id = o.uid\n\
+             \x20       Seq Scan: users AS u\n\
+             \x20       Filter: o.total > 2\n\
+             \x20         Seq Scan: orders AS o (uid, total)\n"
+        );
+
+        // 执行后显示同样的计划和每个算子输出的行数
+        let text = explain(&mut exec, &format!("EXPLAIN ANALYZE {query};"));
+        let lines: Vec<&str> = text.lines().collect();
+        let rows: Vec<(&str, &str)> = lines[..lines.len() - 1]
+            .iter()
+            .map(|line| {
+                let (operator, metrics) = line.rsplit_once(" (rows=").unwrap();
+                (operator.trim(), metrics.split_once(' ').unwrap().0)
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("Projection: u.age, o.total", "2"),
+                ("Limit: 2 OFFSET 0", "2"),
+                ("Sort: o.total ASC NULLS LAST, fetch=2", "2"),
+                ("Hash Join (Inner): u.id = o.uid", "3"),
+                ("Seq Scan: users AS u", "3"),
+                ("Filter: o.total > 2", "3"),
+                ("Seq Scan: orders AS o (uid, total)", "4")
+            ]
+        );
+        assert!(lines.last().unwrap().starts_with("Execution Time: "));
+
+        // 按主键存储的表全表扫描时按主键有序，两侧都按连接键有序时使用归并连接
+        let text = explain(&mut exec, "EXPLAIN SELECT a.id FROM users a JOIN users b ON a.id = b.id;");
+        assert!(text.contains("Hash Join (Inner): a.id = b.id\n"), "{text}");
+        exec.parse_and_run("CREATE TABLE p (id int PRIMARY KEY, v int);").unwrap();
+        exec.parse_and_run("INSERT INTO p VALUES (3, 1), (1, 2), (2, 3);").unwrap();
+        let query = "SELECT a.v FROM p a JOIN p b ON a.id = b.id";
+        let text = explain(&mut exec, &format!("EXPLAIN {query};"));
+        assert!(text.contains("Merge Join (Inner): a.id = b.id\n"), "{text}");
+        let text = explain(&mut exec, &format!("EXPLAIN ANALYZE {query};"));
+        assert!(text.contains("Merge Join (Inner): a.id = b.id (rows=3"), "{text}");
+        let text = explain(&mut exec, "EXPLAIN SELECT a.v FROM p a JOIN p b ON a.v = b.id;");
+        assert!(text.contains("Hash Join (Inner): a.v = b.id\n"), "{text}");
+        let text = explain(&mut exec, "EXPLAIN SELECT a.id FROM users a, users b;");
+        assert!(text.contains("Nested Loop (Cross)\n"), "{text}");
+
+        assert!(matches!(
+            exec.parse_and_run("EXPLAIN SELECT * FROM nope;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::TableNotFound(_)))
+        ));
+    }
+
+    #[test]
+    fn test_index() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE TABLE t (id int, name string, score float);").unwrap();
+        exec.parse_and_run("INSERT INTO t VALUES (1, 'a', 1.5), (2, 'b', 2), (3, 'c', NULL), (4, 'd', 4);").unwrap();
+        exec.parse_and_run("CREATE UNIQUE INDEX tid ON t (id);").unwrap();
+        exec.parse_and_run("CREATE INDEX tscorename ON t (score, name);").unwrap();
+
+        let query = "SELECT id FROM t WHERE score >= 2 AND score < 5 ORDER BY id;";
+        let ExecResponse::Explain(text) = exec.parse_and_run(&format!("EXPLAIN {query}")).unwrap() else {
+            panic!("expected explain response")
+        };
+        assert!(text.contains("Index Scan: t (id) using tscorename: t.score >= 2 AND t.score < 5\n"), "{text}");
+        assert_eq!(select_ids(&mut exec, query), vec![Value::Int(2), Value::Int(4)]);
+
+        // 索引随增删改同步更新
+        exec.parse_and_run("UPDATE t SET score = 3 WHERE id = 3;").unwrap();
+        exec.parse_and_run("DELETE FROM t WHERE id = 4;").unwrap();
+        exec.parse_and_run("INSERT INTO t VALUES (5, 'e', 2.5);").unwrap();
+        assert_eq!(select_ids(&mut exec, query), vec![Value::Int(2), Value::Int(3), Value::Int(5)]);
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE score = 2 AND name = 'b';"), vec![Value::Int(2)]);
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE id = 4;"), vec![]);
+        exec.set_optimizer_rule("index_selection", false);
+        assert_eq!(select_ids(&mut exec, query), vec![Value::Int(2), Value::Int(3), Value::Int(5)]);
+        exec.set_optimizer_rule("index_selection", true);
+
+        // 违反唯一索引的语句不会写入任何一行
+        assert!(matches!(
+            exec.parse_and_run("INSERT INTO t VALUES (6, 'f', 1), (6, 'g', 2);"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::DuplicateKey { .. }))
+        ));
+        assert!(matches!(
+            exec.parse_and_run("UPDATE t SET id = 1 WHERE id = 2;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::DuplicateKey { .. }))
+        ));
+        // 同一条语句中交换键值不算冲突
+        exec.parse_and_run("UPDATE t SET id = 6 - id WHERE id = 1 OR id = 5;").unwrap();
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE name = 'a';"), vec![Value::Int(5)]);
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t ORDER BY id;").len(), 4);
+        assert!(matches!(
+            exec.parse_and_run("CREATE UNIQUE INDEX tscore ON t (score);").unwrap(),
+            ExecResponse::CreateIndex
+        ));
+        assert!(matches!(
+            exec.parse_and_run("CREATE UNIQUE INDEX tname ON t (id, missing);"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::ColumnDoesNotExists(_)))
+        ));
+
+        assert!(matches!(
+            exec.parse_and_run("CREATE INDEX tid ON t (name);"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::IndexAlreadyExists(_)))
+        ));
+        exec.parse_and_run("DROP INDEX tid;").unwrap();
+        exec.parse_and_run("DROP INDEX IF EXISTS tid;").unwrap();
+        assert!(matches!(
+            exec.parse_and_run("DROP INDEX tid;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::IndexNotFound(_)))
+        ));
+        exec.parse_and_run("INSERT INTO t VALUES (2, 'x', NULL);").unwrap();
+    }
+
+    #[test]
+    fn test_in_list() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE TABLE t (id int, score float);").unwrap();
+        exec.parse_and_run("INSERT INTO t VALUES (1, 1.5), (2, 2), (3, NULL), (4, 4);").unwrap();
+
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE id IN (4, 1, 9);"), vec![Value::Int(1), Value::Int(4)]);
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE score NOT IN (2, 4);"), vec![Value::Int(1)]);
+        // 列表中有 NULL 时，没有匹配到的行结果未知
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE id NOT IN (1, NULL);"), vec![]);
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE id IN (1, NULL);"), vec![Value::Int(1)]);
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE 2 IN (id, score);"), vec![Value::Int(2)]);
+        assert!(exec.parse_and_run("SELECT id FROM t WHERE id IN ('a');").is_err());
+    }
+
+    #[test]
+    fn test_hash_index() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE TABLE t (id int, score float);").unwrap();
+        exec.parse_and_run("INSERT INTO t VALUES (1, 1.5), (2, 2), (3, NULL), (4, 4), (5, 2.0);").unwrap();
+        exec.parse_and_run("CREATE INDEX tscore ON t USING HASH (score);").unwrap();
+
+        let query = "SELECT id FROM t WHERE score IN (2, 4.0, 1) ORDER BY id;";
+        let ExecResponse::Explain(text) = exec.parse_and_run(&format!("EXPLAIN {query}")).unwrap() else {
+            panic!("expected explain response")
+        };
+        assert!(text.contains("Index Scan: t (id) using tscore: t.score IN (2, 4.0, 1)\n"), "{text}");
+        // 整数值的浮点数和整数在哈希索引中是同一个键
+        assert_eq!(select_ids(&mut exec, query), vec![Value::Int(2), Value::Int(4), Value::Int(5)]);
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE score = 1.5;"), vec![Value::Int(1)]);
+
+        exec.parse_and_run("UPDATE t SET score = 4 WHERE id = 1;").unwrap();
+        exec.parse_and_run("DELETE FROM t WHERE id = 2;").unwrap();
+        assert_eq!(select_ids(&mut exec, query), vec![Value::Int(1), Value::Int(4), Value::Int(5)]);
+
+        // 范围条件不会使用哈希索引
+        let ExecResponse::Explain(text) = exec.parse_and_run("EXPLAIN SELECT id FROM t WHERE score > 1;").unwrap() else {
+            panic!("expected explain response")
+        };
+        assert!(text.contains("Seq Scan: t"), "{text}");
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE score > 3;").len(), 2);
+    }
+
+    #[test]
+    fn test_primary_key() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE TABLE t (id int PRIMARY KEY, name string);").unwrap();
+        exec.parse_and_run("INSERT INTO t VALUES (3, 'c'), (1, 'a'), (2, 'b');").unwrap();
+        // 行按主键存放，顺序扫描即按主键排序
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t;"), vec![Value::Int(1), Value::Int(2), Value::Int(3)]);
+
+        // 违反主键的语句不会写入任何一行
+        assert!(matches!(
+            exec.parse_and_run("INSERT INTO t VALUES (4, 'd'), (2, 'x');"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::DuplicatePrimaryKey { .. }))
+        ));
+        assert!(matches!(
+            exec.parse_and_run("INSERT INTO t VALUES (5, 'e'), (5, 'f');"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::DuplicatePrimaryKey { .. }))
+        ));
+        assert!(matches!(
+            exec.parse_and_run("UPDATE t SET id = 1 WHERE id = 3;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::DuplicatePrimaryKey { .. }))
+        ));
+        assert!(matches!(
+            exec.parse_and_run("INSERT INTO t (name) VALUES ('n');"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::NullPrimaryKey(_)))
+        ));
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t;").len(), 3);
+
+        // 同一条语句中交换主键不算冲突
+        exec.parse_and_run("UPDATE t SET id = 4 - id WHERE id <> 2;").unwrap();
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE name = 'a';"), vec![Value::Int(3)]);
+        exec.parse_and_run("DELETE FROM t WHERE id = 3;").unwrap();
+        exec.parse_and_run("INSERT INTO t VALUES (3, 'z');").unwrap();
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t;"), vec![Value::Int(1), Value::Int(2), Value::Int(3)]);
+
+        // 复合主键
+        exec.parse_and_run("CREATE TABLE m (id int, part int, PRIMARY KEY (part, id));").unwrap();
+        exec.parse_and_run("INSERT INTO m VALUES (1, 2), (2, 1), (1, 1);").unwrap();
+        assert!(matches!(
+            exec.parse_and_run("INSERT INTO m VALUES (2, 1);"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::DuplicatePrimaryKey { .. }))
+        ));
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM m;"), vec![Value::Int(1), Value::Int(2), Value::Int(1)]);
+
+        // 主键作为有序唯一索引用于查找
+        let explain = |exec: &mut Execution, sql: &str| match exec.parse_and_run(sql).unwrap() {
+            ExecResponse::Explain(text) => text,
+            other => panic!("expected explain response, got {other:?}")
+        };
+        let text = explain(&mut exec, "EXPLAIN SELECT name FROM t WHERE id = 2;");
+        assert!(text.contains("Index Scan: t (name) using t_pkey: t.id = 2"), "{text}");
+        assert!(!text.contains("Filter"), "{text}");
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE id >= 2;"), vec![Value::Int(2), Value::Int(3)]);
+        let text = explain(&mut exec, "EXPLAIN SELECT id FROM m WHERE part = 1 AND id = 2;");
+        assert!(text.contains("using m_pkey: m.part = 1 AND m.id = 2"), "{text}");
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM m WHERE part = 1 AND id = 2;"), vec![Value::Int(2)]);
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM m WHERE id = 1 AND part IN (2, 1);"), vec![Value::Int(1), Value::Int(1)]);
+
+        // 过滤、主键查找和唯一性检查对数值的比较一致：-0.0 等于 0.0，大整数不经过浮点数比较
+        exec.parse_and_run("CREATE TABLE f (id float PRIMARY KEY);").unwrap();
+        exec.parse_and_run("INSERT INTO f VALUES (-0.0);").unwrap();
+        assert!(matches!(
+            exec.parse_and_run("INSERT INTO f VALUES (0.0);"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::DuplicatePrimaryKey { .. }))
+        ));
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM f WHERE id = 0;"), vec![Value::Float(0.0)]);
+        exec.parse_and_run("INSERT INTO t VALUES (9007199254740993, 'big');").unwrap();
+        for rule in [true, false] {
+            exec.set_optimizer_rule("index_selection", rule);
+            assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE id = 9007199254740992.0;"), vec![]);
+            assert_eq!(
+                select_ids(&mut exec, "SELECT id FROM t WHERE id > 9007199254740992.0;"),
+                vec![Value::Int(9007199254740993)]
+            );
+        }
+
+        assert!(matches!(
+            exec.parse_and_run("CREATE TABLE x (id int PRIMARY KEY, PRIMARY KEY (id));"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::MultiplePrimaryKeys))
+        ));
+        assert!(matches!(
+            exec.parse_and_run("CREATE TABLE x (id int, PRIMARY KEY (missing));"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::ColumnDoesNotExists(_)))
+        ));
+    }
+
+    #[test]
+    fn test_join_key_types() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE TABLE a (id int PRIMARY KEY);").unwrap();
+        exec.parse_and_run("CREATE TABLE b (id string PRIMARY KEY);").unwrap();
+        exec.parse_and_run("INSERT INTO a VALUES (1), (2);").unwrap();
+        exec.parse_and_run("INSERT INTO b VALUES ('1'), ('3');").unwrap();
+
+        // 键的类型不能比较时，归并连接、哈希连接和逐对比较都报同样的错
+        for (sql, strategy) in [
+            ("SELECT a.id FROM a JOIN b ON a.id = b.id;", "Merge Join"),
+            ("SELECT a.id FROM a JOIN b ON a.id + 0 = b.id;", "Hash Join"),
+            ("SELECT a.id FROM a JOIN b ON a.id = b.id OR a.id < 0;", "Nested Loop")
+        ] {
+            let ExecResponse::Explain(plan) = exec.parse_and_run(&format!("EXPLAIN {sql}")).unwrap() else {
+                panic!("expected explain response")
+            };
+            assert!(plan.contains(strategy), "{plan}");
+            let error = exec.parse_and_run(sql).unwrap_err();
+            assert_eq!(error.to_string(), "Type mismatch: cannot compare 1 with 1", "{sql}");
+        }
+    }
+
+    #[test]
+    fn test_column_constraints() {
+        let mut exec = Execution::new();
+        exec.parse_and_run(
+            "CREATE TABLE t (id int NOT NULL, qty int DEFAULT 2 * 5 CHECK (qty >= 0), name string DEFAULT 'x');"
+        ).unwrap();
+        exec.parse_and_run("INSERT INTO t (id) VALUES (1);").unwrap();
+        exec.parse_and_run("INSERT INTO t (id, name) VALUES (2, NULL);").unwrap();
+        exec.parse_and_run("INSERT INTO t (name, id, qty) VALUES ('c', 3, NULL);").unwrap();
+        let ExecResponse::Select(rows) = exec.parse_and_run("SELECT qty, name FROM t;").unwrap() else {
+            panic!("expected select response")
+        };
+        let values: Vec<_> = rows.iter().map(|row| (row.get("qty"), row.get("name"))).collect();
+        assert_eq!(values, vec![
+            (Value::Int(10), Value::Text("x".into())),
+            (Value::Int(10), Value::Null),
+            // CHECK 的结果为 NULL 时视为满足
+            (Value::Null, Value::Text("c".into()))
+        ]);
+
+        assert!(matches!(
+            exec.parse_and_run("INSERT INTO t (qty) VALUES (1);"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::NotNullViolation(_)))
+        ));
+        assert!(matches!(
+            exec.parse_and_run("UPDATE t SET id = NULL WHERE id = 1;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::NotNullViolation(_)))
+        ));
+        assert!(matches!(
+            exec.parse_and_run("INSERT INTO t VALUES (4, 1, 'a'), (5, -1, 'b');"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::CheckViolation(_)))
+        ));
+        assert!(matches!(
+            exec.parse_and_run("UPDATE t SET qty = qty - 20;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::CheckViolation(_)))
+        ));
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE qty = 10;"), vec![Value::Int(1), Value::Int(2)]);
+
+        assert!(matches!(
+            exec.parse_and_run("CREATE TABLE x (a int DEFAULT b, b int);"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::ColumnDoesNotExists(_)))
+        ));
+        assert!(matches!(
+            exec.parse_and_run("CREATE TABLE x (a int CHECK (c > 0));"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::ColumnDoesNotExists(_)))
+        ));
+        assert!(matches!(
+            exec.parse_and_run("CREATE TABLE x (a int CHECK (COUNT(a) > 0));"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::MisplacedAggregate(_)))
+        ));
+        exec.parse_and_run("CREATE TABLE x (a int, b int CHECK (b > a));").unwrap();
+        exec.parse_and_run("INSERT INTO x VALUES (1, 2);").unwrap();
+        assert!(exec.parse_and_run("INSERT INTO x VALUES (2, 1);").is_err());
+    }
+
+    #[test]
+    fn test_foreign_key() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE TABLE users (id int PRIMARY KEY, name string);").unwrap();
+        exec.parse_and_run(
+            "CREATE TABLE orders (id int PRIMARY KEY, uid int REFERENCES users (id) ON DELETE CASCADE);"
+        ).unwrap();
+        exec.parse_and_run(
+            "CREATE TABLE items (id int, oid int, FOREIGN KEY (oid) REFERENCES orders (id));"
+        ).unwrap();
+        exec.parse_and_run(
+            "CREATE TABLE notes (id int, uid int REFERENCES users (id) ON DELETE SET NULL);"
+        ).unwrap();
+        exec.parse_and_run("INSERT INTO users VALUES (1, 'a'), (2, 'b'), (3, 'c');").unwrap();
+        exec.parse_and_run("INSERT INTO orders VALUES (10, 1), (20, 2), (30, NULL);").unwrap();
+        exec.parse_and_run("INSERT INTO items VALUES (100, 10);").unwrap();
+        exec.parse_and_run("INSERT INTO notes VALUES (1, 1), (2, 2);").unwrap();
+
+        // 写入时引用的行必须存在
+        assert!(matches!(
+            exec.parse_and_run("INSERT INTO orders VALUES (40, 4);"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::ForeignKeyViolation { .. }))
+        ));
+        assert!(matches!(
+            exec.parse_and_run("UPDATE orders SET uid = 5 WHERE id = 10;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::ForeignKeyViolation { .. }))
+        ));
+        // 被引用的值不能在还有行引用时改掉
+        assert!(matches!(
+            exec.parse_and_run("UPDATE users SET id = 9 WHERE id = 2;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::ForeignKeyRestrict { .. }))
+        ));
+        exec.parse_and_run("UPDATE users SET id = 9 WHERE id = 3;").unwrap();
+
+        // CASCADE 删除的订单仍被 items 引用（RESTRICT），整条语句不生效
+        assert!(matches!(
+            exec.parse_and_run("DELETE FROM users WHERE id = 1;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::ForeignKeyRestrict { .. }))
+        ));
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM users;").len(), 3);
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM notes WHERE uid = 1;"), vec![Value::Int(1)]);
+
+        let ExecResponse::Delete { rows_affected } = exec.parse_and_run("DELETE FROM users WHERE id = 2;").unwrap() else {
+            panic!("expected delete response")
+        };
+        assert_eq!(rows_affected, 1);
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM orders;"), vec![Value::Int(10), Value::Int(30)]);
+        let ExecResponse::Select(rows) = exec.parse_and_run("SELECT uid FROM notes;").unwrap() else {
+            panic!("expected select response")
+        };
+        assert_eq!(rows.iter().map(|row| row.get("uid")).collect::<Vec<_>>(), vec![Value::Int(1), Value::Null]);
+
+        exec.parse_and_run("DELETE FROM items;").unwrap();
+        exec.parse_and_run("DELETE FROM users WHERE id = 1;").unwrap();
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM orders;"), vec![Value::Int(30)]);
+
+        assert!(matches!(
+            exec.parse_and_run("DROP TABLE users;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::TableReferenced { .. }))
+        ));
+        assert!(matches!(
+            exec.parse_and_run("TRUNCATE TABLE orders;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::TableReferenced { .. }))
+        ));
+        assert!(matches!(
+            exec.parse_and_run("CREATE TABLE x (a int REFERENCES users (name));"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::InvalidForeignKey(_)))
+        ));
+        assert!(matches!(
+            exec.parse_and_run("CREATE TABLE x (a string REFERENCES users (id));"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::InvalidForeignKey(_)))
+        ));
+        assert!(matches!(
+            exec.parse_and_run("CREATE TABLE x (a int REFERENCES nope (id));"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::TableNotFound(_)))
+        ));
+
+        // 唯一索引也可以被引用，但不能在被依赖时删除
+        exec.parse_and_run("CREATE UNIQUE INDEX uname ON users (name);").unwrap();
+        exec.parse_and_run("CREATE TABLE x (name string REFERENCES users (name));").unwrap();
+        assert!(matches!(
+            exec.parse_and_run("DROP INDEX uname;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::IndexInUse { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_self_referencing_foreign_key() {
+        let mut exec = Execution::new();
+        exec.parse_and_run(
+            "CREATE TABLE t (id int PRIMARY KEY, parent int REFERENCES t (id) ON DELETE CASCADE);"
+        ).unwrap();
+        // 同一条语句中插入的行可以互相引用
+        exec.parse_and_run("INSERT INTO t VALUES (2, 1), (1, NULL), (3, 2), (4, 1);").unwrap();
+        assert!(exec.parse_and_run("INSERT INTO t VALUES (5, 6);").is_err());
+        // 同一条语句中交换主键后，被引用的值仍然存在
+        exec.parse_and_run("UPDATE t SET id = 5 - id WHERE id = 1 OR id = 4;").unwrap();
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE parent = 1;"), vec![Value::Int(1), Value::Int(2)]);
+        assert!(matches!(
+            exec.parse_and_run("UPDATE t SET id = 6 WHERE id = 2;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::ForeignKeyRestrict { .. }))
+        ));
+
+        // 级联删除会沿着引用链继续删除
+        exec.parse_and_run("DELETE FROM t WHERE id = 1;").unwrap();
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t;"), vec![Value::Int(4)]);
+    }
+
+    #[test]
+    fn test_unique_constraint() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE TABLE t (id int UNIQUE, a int, b string, UNIQUE (a, b));").unwrap();
+        exec.parse_and_run("INSERT INTO t VALUES (1, 1, 'x'), (2, 1, 'y'), (NULL, 2, 'x'), (NULL, 2, NULL), (5, 2, NULL);").unwrap();
+
+        let error = exec.parse_and_run("INSERT INTO t VALUES (3, 1, 'x');").unwrap_err();
+        assert_eq!(error.to_string(), "Duplicate key (a, b)=(1, x) violates unique constraint t_a_b_key");
+        let error = exec.parse_and_run("UPDATE t SET id = 2 WHERE id = 1;").unwrap_err();
+        assert_eq!(error.to_string(), "Duplicate key (id)=(2) violates unique constraint t_id_key");
+        // 同一条语句中交换值不算冲突
+        exec.parse_and_run("UPDATE t SET id = 3 - id WHERE id = 1 OR id = 2;").unwrap();
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE b = 'x' AND a = 1;"), vec![Value::Int(2)]);
+
+        // 约束的索引也会用于查询
+        let ExecResponse::Explain(text) = exec.parse_and_run("EXPLAIN SELECT id FROM t WHERE id = 5;").unwrap() else {
+            panic!("expected explain response")
+        };
+        assert!(text.contains("using t_id_key"), "{text}");
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE id = 5;"), vec![Value::Int(5)]);
+
+        // UNIQUE 列可以被外键引用
+        exec.parse_and_run("CREATE TABLE c (tid int REFERENCES t (id));").unwrap();
+        exec.parse_and_run("INSERT INTO c VALUES (5);").unwrap();
+        assert!(exec.parse_and_run("INSERT INTO c VALUES (4);").is_err());
+
+        assert!(matches!(
+            exec.parse_and_run("CREATE TABLE x (a int, UNIQUE (a, b));"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::ColumnDoesNotExists(_)))
+        ));
+    }
+
+    #[test]
+    fn test_auto_increment() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE TABLE t (id INTEGER PRIMARY KEY AUTOINCREMENT, name string);").unwrap();
+        exec.parse_and_run("INSERT INTO t (name) VALUES ('a'), ('b'), ('c');").unwrap();
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t;"), vec![Value::Int(1), Value::Int(2), Value::Int(3)]);
+
+        // 删除之后也不会复用已经取出的值
+        exec.parse_and_run("DELETE FROM t WHERE id >= 2;").unwrap();
+        exec.parse_and_run("INSERT INTO t VALUES (NULL, 'd');").unwrap();
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t;"), vec![Value::Int(1), Value::Int(4)]);
+
+        // 显式写入的值越过序列
+        exec.parse_and_run("INSERT INTO t VALUES (10, 'e');").unwrap();
+        exec.parse_and_run("UPDATE t SET id = 20 WHERE id = 1;").unwrap();
+        exec.parse_and_run("INSERT INTO t (name) VALUES ('f');").unwrap();
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM t WHERE name = 'f';"), vec![Value::Int(21)]);
+
+        assert!(matches!(
+            exec.parse_and_run("CREATE TABLE x (id string AUTOINCREMENT);"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::InvalidAutoIncrement(_)))
+        ));
+        assert!(matches!(
+            exec.parse_and_run("CREATE TABLE x (id int AUTOINCREMENT DEFAULT 1);"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::InvalidAutoIncrement(_)))
+        ));
+    }
+
+    #[test]
+    fn test_sequence_persistence() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE SEQUENCE s START WITH 100 INCREMENT BY 10;").unwrap();
+        exec.parse_and_run("CREATE TABLE t (id INTEGER PRIMARY KEY AUTOINCREMENT, n int DEFAULT nextval('s'));").unwrap();
+        exec.parse_and_run("INSERT INTO t (id) VALUES (NULL), (NULL);").unwrap();
+        exec.parse_and_run("DELETE FROM t WHERE id = 2;").unwrap();
+
+        // 恢复后从上次取出的值继续，而不是回到起始值
+        let saved = bincode::serialize(&exec).unwrap();
+        let mut exec: Execution = bincode::deserialize(&saved).unwrap();
+        exec.parse_and_run("INSERT INTO t (id) VALUES (NULL);").unwrap();
+        let ExecResponse::Select(rows) = exec.parse_and_run("SELECT id, n FROM t;").unwrap() else {
+            panic!("expected select response")
+        };
+        assert_eq!(
+            rows.iter().map(|row| (row.get("id"), row.get("n"))).collect::<Vec<_>>(),
+            vec![(Value::Int(1), Value::Int(100)), (Value::Int(3), Value::Int(120))]
+        );
+        assert!(exec.set_optimizer_rule("index_selection", false));
+    }
+
+    #[test]
+    fn test_sequence() {
+        let mut exec = Execution::new();
+        exec.parse_and_run("CREATE SEQUENCE s START WITH 100 INCREMENT BY 10;").unwrap();
+        exec.parse_and_run("CREATE TABLE a (id int DEFAULT nextval('s'), name string);").unwrap();
+        exec.parse_and_run("CREATE TABLE b (id int DEFAULT nextval('s') + 1, name string);").unwrap();
+
+        // 两张表共用一个序列
+        exec.parse_and_run("INSERT INTO a (name) VALUES ('x'), ('y');").unwrap();
+        exec.parse_and_run("INSERT INTO b (name) VALUES ('z');").unwrap();
+        exec.parse_and_run("INSERT INTO a VALUES (5, 'w');").unwrap();
+        exec.parse_and_run("INSERT INTO a (name) VALUES ('v');").unwrap();
+        assert_eq!(
+            select_ids(&mut exec, "SELECT id FROM a;"),
+            vec![Value::Int(100), Value::Int(110), Value::Int(5), Value::Int(130)]
+        );
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM b;"), vec![Value::Int(121)]);
+
+        assert!(matches!(
+            exec.parse_and_run("CREATE SEQUENCE s;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::SequenceAlreadyExists(_)))
+        ));
+        assert!(matches!(
+            exec.parse_and_run("CREATE SEQUENCE z INCREMENT BY 0;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::InvalidSequenceIncrement(0)))
+        ));
+
+        // 输出列、INSERT 的值和 UPDATE 的赋值中每行取一个值
+        exec.parse_and_run("CREATE SEQUENCE q;").unwrap();
+        let ExecResponse::Select(rows) = exec.parse_and_run("SELECT nextval('q') AS n, id FROM a WHERE id < 120;").unwrap() else {
+            panic!("expected select response")
+        };
+        assert_eq!(rows.iter().map(|row| row.get("n")).collect::<Vec<_>>(), vec![Value::Int(1), Value::Int(2), Value::Int(3)]);
+        exec.parse_and_run("INSERT INTO b VALUES (nextval('q') * 100, 'p'), (nextval('q') * 100, 'q');").unwrap();
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM b WHERE name <> 'z';"), vec![Value::Int(400), Value::Int(500)]);
+        exec.parse_and_run("UPDATE b SET id = nextval('q') WHERE name <> 'z';").unwrap();
+        assert_eq!(select_ids(&mut exec, "SELECT id FROM b WHERE name <> 'z';"), vec![Value::Int(6), Value::Int(7)]);
+
+        // 其它位置不能使用，即使表中没有行
+        for sql in [
+            "SELECT id FROM a WHERE id = nextval('s');",
+            "SELECT id FROM a ORDER BY nextval('s');",
+            "SELECT count(id) FROM a GROUP BY nextval('s');"
+        ] {
+            assert!(matches!(
+                exec.parse_and_run(sql),
+                Err(SQLError::QueryExceptionError(QueryExceptionError::MisplacedSequence(_)))
+            ), "{sql}");
+        }
+        let error = exec.parse_and_run("SELECT id FROM a WHERE id = nextval('s');").unwrap_err();
+        assert_eq!(error.to_string(), "Sequence function nextval is not allowed here: id = nextval('s')");
+        assert!(matches!(
+            exec.parse_and_run("CREATE TABLE c (id int CHECK (id < nextval('s')));"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::MisplacedSequence(_)))
+        ));
+
+        // 默认值引用的序列必须存在，被引用时不能删除
+        assert!(matches!(
+            exec.parse_and_run("CREATE TABLE c (id int DEFAULT nextval('nope'));"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::SequenceNotFound(_)))
+        ));
+        let error = exec.parse_and_run("DROP SEQUENCE s;").unwrap_err();
+        assert!(error.to_string().starts_with("Sequence s is used by a column default on table"));
+        exec.parse_and_run("DROP TABLE a;").unwrap();
+        assert!(matches!(
+            exec.parse_and_run("DROP SEQUENCE IF EXISTS s;"),
+            Err(SQLError::QueryExceptionError(QueryExceptionError::SequenceInUse { table, .. })) if table == "b"
+        ));
+        exec.parse_and_run("DROP TABLE b;").unwrap();
+        exec.parse_and_run("DROP SEQUENCE s;").unwrap();
+        exec.parse_and_run("DROP SEQUENCE IF EXISTS s;").unwrap();
+
+        // 没有被默认值引用的序列可以直接删除
+        exec.parse_and_run("DROP SEQUENCE q;").unwrap();
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+use sql_parser::expr::{BinaryOp, Expr, Literal};
+use crate::error::QueryExceptionError;
+use crate::eval;
+use crate::plan::LogicalPlan;
+use crate::row::Row;
+use crate::table::Table;
+use super::OptimizerRule;
+
+/// 常量折叠：计算不引用列的子表达式，并化简和布尔常量的 AND / OR
+///
+/// 恒为 true 的过滤条件被去掉；恒为 false 或 NULL 时改为 `LIMIT 0`，不再扫描输入。
+/// 计算出错的表达式（如 `1 / 0`）保持原样，留到执行时报错
+#[derive(Debug)]
+pub(crate) struct ConstantFolding;
+
+impl OptimizerRule for ConstantFolding {
+    fn name(&self) -> &'static str {
+        "constant_folding"
+    }
+
+    fn optimize(&self, _tables: &HashMap<String, Table>, plan: LogicalPlan) -> Result<LogicalPlan, QueryExceptionError> {
+        fold_plan(plan)
+    }
+}
+
+/// 自底向上折叠计划中的表达式
+fn fold_plan(plan: LogicalPlan) -> Result<LogicalPlan, QueryExceptionError> {
+    let plan = plan.map_children(fold_plan)?;
+    Ok(match plan {
+        LogicalPlan::Filter { input, predicate } => match fold(predicate) {
+            Expr::Literal(Literal::Bool(true)) => *input,
+            Expr::Literal(Literal::Bool(false) | Literal::Null) => LogicalPlan::Limit {
+                input,
+                offset: 0,
+                limit: Some(0)
+            },
+            predicate => LogicalPlan::Filter { input, predicate }
+        },
+        LogicalPlan::Project { input, exprs } => LogicalPlan::Project {
+            input,
+            exprs: exprs.into_iter().map(|(col, expr)| (col, fold(expr))).collect()
+        },
+        LogicalPlan::Join { left, right, kind, on } => LogicalPlan::Join {
+            left,
+            right,
+            kind,
+            on: on.map(fold).filter(|on| on != &Expr::Literal(Literal::Bool(true)))
+        },
+        LogicalPlan::Sort { input, mut order_by, fetch } => {
+            for item in order_by.iter_mut() {
+                item.expr = fold(item.expr.clone());
+            }
+            LogicalPlan::Sort { input, order_by, fetch }
+        }
+        plan => plan
+    })
+}
+
+/// 自底向上折叠表达式
+pub(crate) fn fold(expr: Expr) -> Expr {
+    const TRUE: Expr = Expr::Literal(Literal::Bool(true));
+    const FALSE: Expr = Expr::Literal(Literal::Bool(false));
+
+    match expr {
+        Expr::Column(_) | Expr::Literal(_) | Expr::NextVal(_) | Expr::Aggregate { .. } => expr,
+        Expr::Unary { op, expr } => evaluate_constant(Expr::Unary { op, expr: Box::new(fold(*expr)) }),
+        Expr::Binary { left, op, right } => {
+            let (left, right) = (fold(*left), fold(*right));
+            match op {
+                BinaryOp::And if left == FALSE || right == FALSE => FALSE,
+                BinaryOp::And if left == TRUE => right,
+                BinaryOp::And if right == TRUE => left,
+                BinaryOp::Or if left == TRUE || right == TRUE => TRUE,
+                BinaryOp::Or if left == FALSE => right,
+                BinaryOp::Or if right == FALSE => left,
+                _ => evaluate_constant(Expr::Binary { left: Box::new(left), op, right: Box::new(right) })
+            }
+        }
+        Expr::InList { expr, list, negated } => evaluate_constant(Expr::InList {
+            expr: Box::new(fold(*expr)),
+            list: list.into_iter().map(fold).collect(),
+            negated
+        })
+    }
+}
+
+/// 子表达式都已是常量时直接计算
+fn evaluate_constant(expr: Expr) -> Expr {
+    let constant = match &expr {
+        Expr::Unary { expr, .. } => matches!(expr.as_ref(), Expr::Literal(_)),
+        Expr::Binary { left, right, .. } => {
+            matches!((left.as_ref(), right.as_ref()), (Expr::Literal(_), Expr::Literal(_)))
+        }
+        Expr::InList { expr, list, .. } => {
+            matches!(expr.as_ref(), Expr::Literal(_)) && list.iter().all(|item| matches!(item, Expr::Literal(_)))
+        }
+        _ => false
+    };
+    if !constant {
+        return expr;
+    }
+    match eval::evaluate(&expr, &Row::new(Rc::new(Vec::new()), Vec::new())) {
+        Ok(value) => Expr::Literal(value.into()),
+        Err(_) => expr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sql_parser::parse::Parse;
+    use super::super::test_util::{plan, tables};
+
+    fn folded(sql: &str) -> String {
+        ConstantFolding.optimize(&tables(), plan(sql)).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_fold_expressions() {
+        let fold_str = |sql: &str| fold(Expr::parse_from_raw(sql).unwrap().1).to_string();
+        assert_eq!(fold_str("1 + 2 * 3"), "7");
+        assert_eq!(fold_str("a > 1 + 1"), "a > 2");
+        assert_eq!(fold_str("1 = 1 AND a = 2"), "a = 2");
+        assert_eq!(fold_str("a = 2 OR NOT 1 = 1"), "a = 2");
+        assert_eq!(fold_str("a = 2 OR 2 > 1"), "TRUE");
+        assert_eq!(fold_str("a = 2 AND 1 > 2"), "FALSE");
+        assert_eq!(fold_str("NULL = 1"), "NULL");
+        // 出错的表达式留到执行时报错
+        assert_eq!(fold_str("a / (1 / 0)"), "a / (1 / 0)");
+    }
+
+    #[test]
+    fn test_fold_filters() {
+        assert_eq!(folded("SELECT id FROM users WHERE 1 = 1"), "Project: users.id\n  Scan: users\n");
+        assert_eq!(
+            folded("SELECT id FROM users WHERE 1 = 2 AND age > 1"),
+            "Project: users.id\n  Limit: 0 OFFSET 0\n    Scan: users\n"
+        );
+        assert_eq!(
+            folded("SELECT id + (2 - 1) AS next FROM users WHERE age > 10 * 2"),
+            "Project: users.id + 1 AS next\n  Filter: users.age > 20\n    Scan: users\n"
+        );
+    }
+}
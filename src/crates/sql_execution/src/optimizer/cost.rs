@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use sql_parser::commands::JoinKind;
+use sql_parser::expr::{BinaryOp, ColumnRef, Expr, Literal, UnaryOp};
+use crate::plan::LogicalPlan;
+use crate::statistics::ColumnStatistics;
+use crate::table::Table;
+use crate::value::Value;
+
+/// 没有统计信息时等值条件的选择率
+const DEFAULT_EQ_SELECTIVITY: f64 = 0.1;
+/// 没有统计信息时其它条件的选择率
+const DEFAULT_SELECTIVITY: f64 = 1.0 / 3.0;
+
+/// 根据 ANALYZE 收集的统计信息估计计划输出的行数
+///
+/// 假设各个条件之间相互独立
+pub(crate) struct Estimator<'c> {
+    tables: &'c HashMap<String, Table>,
+    /// 计划中扫描的每一列（带表名）的统计信息
+    columns: HashMap<ColumnRef, &'c ColumnStatistics>
+}
+
+impl<'c> Estimator<'c> {
+    /// `plans` 中所有扫描到的列都可以在之后的估计中使用
+    pub fn new<'p>(tables: &'c HashMap<String, Table>, plans: impl IntoIterator<Item = &'p LogicalPlan>) -> Self {
+        let mut estimator = Self { tables, columns: HashMap::new() };
+        for plan in plans {
+            estimator.register(plan);
+        }
+        estimator
+    }
+
+    fn register(&mut self, plan: &LogicalPlan) {
+        if let LogicalPlan::Scan { table, qualifier, schema, projection, .. } = plan {
+            let Some(statistics) = self.tables.get(table).and_then(Table::statistics) else {
+                return;
+            };
+            for (idx, col) in schema.iter().enumerate() {
+                let idx = projection.as_ref().map_or(idx, |projection| projection[idx]);
+                let column = ColumnRef::qualified(qualifier.to_owned(), col.name.to_owned());
+                self.columns.insert(column, &statistics.columns[idx]);
+            }
+        }
+        for child in plan.children() {
+            self.register(child);
+        }
+    }
+
+    /// 估计 `plan` 输出的行数，有表没有统计信息时返回 `None`
+    pub fn rows(&self, plan: &LogicalPlan) -> Option<f64> {
+        Some(match plan {
+            LogicalPlan::Scan { table, index, .. } => {
+                let rows = self.tables.get(table)?.statistics()?.row_count as f64;
+                index.as_ref().map_or(rows, |index| rows * self.selectivity(&index.predicate))
+            }
+            LogicalPlan::Filter { input, predicate } => self.rows(input)? * self.selectivity(predicate),
+            LogicalPlan::Project { input, .. } => self.rows(input)?,
+            LogicalPlan::Sort { input, fetch, .. } => {
+                let rows = self.rows(input)?;
+                fetch.map_or(rows, |fetch| rows.min(fetch as f64))
+            }
+            LogicalPlan::Limit { input, offset, limit } => {
+                let rows = (self.rows(input)? - *offset as f64).max(0.0);
+                limit.map_or(rows, |limit| rows.min(limit as f64))
+            }
+            LogicalPlan::Join { left, right, kind, on } => {
+                let (left, right) = (self.rows(left)?, self.rows(right)?);
+                let matched = left * right * on.as_ref().map_or(1.0, |on| self.selectivity(on));
+                // 外连接保留一侧所有的行
+                match kind {
+                    JoinKind::Inner | JoinKind::Cross => matched,
+                    JoinKind::Left => matched.max(left),
+                    JoinKind::Right => matched.max(right),
+                    JoinKind::Full => matched.max(left).max(right)
+                }
+            }
+            LogicalPlan::Aggregate { input, aggregation } => {
+                let rows = self.rows(input)?;
+                if aggregation.group_by.is_empty() {
+                    return Some(1.0);
+                }
+                let groups: f64 = aggregation.group_by
+                    .iter()
+                    .map(|expr| match expr {
+                        Expr::Column(column) => match self.columns.get(column) {
+                            // NULL 单独成为一组
+                            Some(stats) => stats.distinct_count as f64 + if stats.null_fraction > 0.0 { 1.0 } else { 0.0 },
+                            None => rows * DEFAULT_EQ_SELECTIVITY
+                        },
+                        _ => rows * DEFAULT_EQ_SELECTIVITY
+                    })
+                    .product();
+                groups.min(rows)
+            }
+        })
+    }
+
+    /// 估计满足 `predicate` 的行所占的比例
+    pub fn selectivity(&self, predicate: &Expr) -> f64 {
+        match predicate {
+            Expr::Literal(Literal::Bool(true)) => 1.0,
+            Expr::Literal(_) => 0.0,
+            Expr::Unary { op: UnaryOp::Not, expr } => 1.0 - self.selectivity(expr),
+            Expr::Binary { left, op: BinaryOp::And, right } => self.selectivity(left) * self.selectivity(right),
+            Expr::Binary { left, op: BinaryOp::Or, right } => {
+                let (left, right) = (self.selectivity(left), self.selectivity(right));
+                left + right - left * right
+            }
+            Expr::Binary { left, op, right } => match (left.as_ref(), right.as_ref()) {
+                (Expr::Column(column), Expr::Literal(literal)) => self.compare_constant(column, *op, &literal.into()),
+                (Expr::Literal(literal), Expr::Column(column)) => {
+                    self.compare_constant(column, flip(*op), &literal.into())
+                }
+                (Expr::Column(left), Expr::Column(right)) if *op == BinaryOp::Eq => {
+                    match (self.columns.get(left), self.columns.get(right)) {
+                        (Some(left), Some(right)) => {
+                            let distinct = left.distinct_count.max(right.distinct_count);
+                            if distinct == 0 { 0.0 } else { 1.0 / distinct as f64 }
+                        }
+                        _ => DEFAULT_EQ_SELECTIVITY
+                    }
+                }
+                _ => default_selectivity(*op)
+            },
+            Expr::InList { expr, list, negated } => {
+                // 各项的等值条件互斥，选择率相加
+                let matched: f64 = list
+                    .iter()
+                    .map(|item| match (expr.as_ref(), item) {
+                        (Expr::Column(column), Expr::Literal(literal)) => {
+                            self.compare_constant(column, BinaryOp::Eq, &literal.into())
+                        }
+                        _ => DEFAULT_EQ_SELECTIVITY
+                    })
+                    .sum();
+                let matched = matched.min(1.0);
+                if *negated { 1.0 - matched } else { matched }
+            }
+            _ => DEFAULT_SELECTIVITY
+        }
+    }
+
+    /// `column op value`
+    fn compare_constant(&self, column: &ColumnRef, op: BinaryOp, value: &Value) -> f64 {
+        let Some(stats) = self.columns.get(column) else {
+            return default_selectivity(op);
+        };
+        match op {
+            BinaryOp::Eq => stats.eq_selectivity(value),
+            BinaryOp::NotEq if value.is_null() => 0.0,
+            BinaryOp::NotEq => (1.0 - stats.null_fraction - stats.eq_selectivity(value)).max(0.0),
+            BinaryOp::Lt => stats.lt_selectivity(value),
+            BinaryOp::LtEq => stats.lt_selectivity(value) + stats.eq_selectivity(value),
+            BinaryOp::Gt => stats.gt_selectivity(value),
+            BinaryOp::GtEq => stats.gt_selectivity(value) + stats.eq_selectivity(value),
+            _ => DEFAULT_SELECTIVITY
+        }
+    }
+}
+
+fn default_selectivity(op: BinaryOp) -> f64 {
+    match op {
+        BinaryOp::Eq => DEFAULT_EQ_SELECTIVITY,
+        BinaryOp::NotEq => 1.0 - DEFAULT_EQ_SELECTIVITY,
+        _ => DEFAULT_SELECTIVITY
+    }
+}
+
+/// 交换比较两边后对应的运算符，如 `1 < a` 等价于 `a > 1`
+pub(super) fn flip(op: BinaryOp) -> BinaryOp {
+    match op {
+        BinaryOp::Lt => BinaryOp::Gt,
+        BinaryOp::LtEq => BinaryOp::GtEq,
+        BinaryOp::Gt => BinaryOp::Lt,
+        BinaryOp::GtEq => BinaryOp::LtEq,
+        op => op
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_util::{bind, insert, tables};
+
+    #[test]
+    fn test_estimate_rows() {
+        let mut tables = tables();
+        insert(tables.get_mut("users").unwrap(), (0..100).map(|i| vec![i, i % 10]));
+
+        let estimate = |sql: &str| {
+            let plan = bind(&tables, sql);
+            Estimator::new(&tables, [&plan]).rows(&plan)
+        };
+        assert_eq!(estimate("SELECT id FROM users"), None);
+
+        tables.get_mut("users").unwrap().analyze();
+        let estimate = |sql: &str| {
+            let plan = bind(&tables, sql);
+            Estimator::new(&tables, [&plan]).rows(&plan).unwrap().round()
+        };
+        assert_eq!(estimate("SELECT id FROM users"), 100.0);
+        assert_eq!(estimate("SELECT id FROM users WHERE age = 3"), 10.0);
+        assert_eq!(estimate("SELECT id FROM users WHERE 3 = age AND id < 50"), 5.0);
+        assert_eq!(estimate("SELECT id FROM users WHERE age = 3 OR age = 4"), 19.0);
+        assert_eq!(estimate("SELECT id FROM users WHERE age = 30"), 0.0);
+        assert_eq!(estimate("SELECT id FROM users WHERE age IN (3, 4, 30)"), 20.0);
+        assert_eq!(estimate("SELECT id FROM users WHERE age NOT IN (3)"), 90.0);
+        assert_eq!(estimate("SELECT id FROM users WHERE id >= 90"), 10.0);
+        assert_eq!(estimate("SELECT age FROM users GROUP BY age"), 10.0);
+        assert_eq!(estimate("SELECT COUNT(*) FROM users"), 1.0);
+        assert_eq!(estimate("SELECT a.id FROM users a JOIN users b ON a.id = b.id"), 100.0);
+        assert_eq!(estimate("SELECT a.id FROM users a LEFT JOIN users b ON a.age = b.id AND b.id > 200"), 100.0);
+        assert_eq!(estimate("SELECT id FROM users LIMIT 5"), 5.0);
+    }
+}
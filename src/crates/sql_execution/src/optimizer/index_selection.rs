@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+use sql_parser::commands::IndexKind;
+use sql_parser::expr::{BinaryOp, ColumnRef, Expr};
+use crate::error::QueryExceptionError;
+use crate::index::{self, IndexRange};
+use crate::plan::{IndexLookup, LogicalPlan};
+use crate::table::Table;
+use crate::value::Value;
+use super::cost::{flip, Estimator};
+use super::{conjunction, split_conjunction, OptimizerRule};
+
+/// 有统计信息时，估计选出的行超过这个比例就不用索引：按 id 逐行读取比顺序扫描慢
+const MAX_INDEX_SELECTIVITY: f64 = 0.3;
+
+/// 为直接作用在表上的过滤条件选择索引
+///
+/// 主键看作一个有序唯一索引。有序索引前几列取等值或 `IN`、紧接着的一列取范围；哈希索引需要所有列都取等值或 `IN`。
+/// 用到的条件由索引求值，其余的仍留在过滤中。有多个索引可用时选择估计选出行最少的一个，
+/// 相同时优先哈希索引；有统计信息且选出的行太多时保持全表扫描
+#[derive(Debug)]
+pub(crate) struct IndexSelection;
+
+impl OptimizerRule for IndexSelection {
+    fn name(&self) -> &'static str {
+        "index_selection"
+    }
+
+    fn optimize(&self, tables: &HashMap<String, Table>, plan: LogicalPlan) -> Result<LogicalPlan, QueryExceptionError> {
+        match plan {
+            LogicalPlan::Filter { input, predicate } if matches!(*input, LogicalPlan::Scan { index: None, .. }) => {
+                Ok(select(tables, *input, predicate))
+            }
+            plan => plan.map_children(|child| self.optimize(tables, child))
+        }
+    }
+}
+
+/// 可以由索引求值的条件，`column` 为列在表中的下标
+///
+/// `column IN (values)` 的 `op` 为 `Eq`，其它比较只有一个值
+struct Sarg {
+    column: usize,
+    op: BinaryOp,
+    values: Vec<Value>
+}
+
+/// 可以选择的索引，主键也作为一个有序索引
+struct Candidate<'t> {
+    name: &'t str,
+    /// 索引列在表中的下标
+    columns: &'t [usize],
+    kind: IndexKind
+}
+
+fn select(tables: &HashMap<String, Table>, scan: LogicalPlan, predicate: Expr) -> LogicalPlan {
+    let LogicalPlan::Scan { table, .. } = &scan else {
+        unreachable!("only called with a scan")
+    };
+    let Some(table) = tables.get(table) else {
+        return LogicalPlan::Filter { input: Box::new(scan), predicate };
+    };
+    let primary_key = table
+        .primary_key_index()
+        .map(|(name, columns)| Candidate { name, columns, kind: IndexKind::BTree });
+    let indexes = table
+        .indexes()
+        .iter()
+        .map(|index| Candidate { name: &index.name, columns: &index.columns, kind: index.kind() });
+    let candidates: Vec<Candidate> = primary_key.into_iter().chain(indexes).collect();
+    if candidates.is_empty() {
+        return LogicalPlan::Filter { input: Box::new(scan), predicate };
+    }
+
+    let mut conjuncts = Vec::new();
+    split_conjunction(predicate, &mut conjuncts);
+    let sargs: Vec<Option<Sarg>> = conjuncts.iter().map(|conjunct| sarg(table, &scan, conjunct)).collect();
+
+    let estimator = Estimator::new(tables, [&scan]);
+    let mut best: Option<(f64, Candidate, Vec<IndexRange>, Vec<usize>)> = None;
+    for candidate in candidates {
+        let Some((ranges, used)) = match_index(&candidate, &sargs) else {
+            continue;
+        };
+        let selectivity: f64 = used.iter().map(|&idx| estimator.selectivity(&conjuncts[idx])).product();
+        let better = match &best {
+            Some((lowest, chosen, ..)) => {
+                selectivity < *lowest
+                    || selectivity == *lowest && candidate.kind == IndexKind::Hash && chosen.kind != IndexKind::Hash
+            }
+            None => true
+        };
+        if better {
+            best = Some((selectivity, candidate, ranges, used));
+        }
+    }
+    let best = best.filter(|(selectivity, ..)| table.statistics().is_none() || *selectivity <= MAX_INDEX_SELECTIVITY);
+    let Some((_, index, ranges, used)) = best else {
+        let predicate = conjunction(conjuncts).expect("split from a predicate");
+        return LogicalPlan::Filter { input: Box::new(scan), predicate };
+    };
+
+    let (used, rest): (Vec<_>, Vec<_>) = conjuncts
+        .into_iter()
+        .enumerate()
+        .partition(|(idx, _)| used.contains(idx));
+    let LogicalPlan::Scan { table, qualifier, schema, projection, .. } = scan else {
+        unreachable!("only called with a scan")
+    };
+    let scan = LogicalPlan::Scan {
+        table,
+        qualifier,
+        schema,
+        projection,
+        index: Some(IndexLookup {
+            name: index.name.to_owned(),
+            ranges,
+            predicate: conjunction(used.into_iter().map(|(_, conjunct)| conjunct).collect())
+                .expect("an index is used for at least one condition")
+        })
+    };
+    match conjunction(rest.into_iter().map(|(_, conjunct)| conjunct).collect()) {
+        Some(predicate) => LogicalPlan::Filter { input: Box::new(scan), predicate },
+        None => scan
+    }
+}
+
+/// 条件为列和常量的比较或 `IN` 常量列表，且常量都可以和列的值在索引中比较时返回对应的 [`Sarg`]
+fn sarg(table: &Table, scan: &LogicalPlan, conjunct: &Expr) -> Option<Sarg> {
+    let (column, op, literals) = match conjunct {
+        Expr::Binary { left, op, right } => match (left.as_ref(), right.as_ref()) {
+            (Expr::Column(column), Expr::Literal(literal)) => (column, *op, vec![literal]),
+            (Expr::Literal(literal), Expr::Column(column)) => (column, flip(*op), vec![literal]),
+            _ => return None
+        },
+        Expr::InList { expr, list, negated: false } => {
+            let Expr::Column(column) = expr.as_ref() else {
+                return None;
+            };
+            let literals = list
+                .iter()
+                .map(|item| match item {
+                    Expr::Literal(literal) => Some(literal),
+                    _ => None
+                })
+                .collect::<Option<Vec<_>>>()?;
+            (column, BinaryOp::Eq, literals)
+        }
+        _ => return None
+    };
+    if !matches!(op, BinaryOp::Eq | BinaryOp::Lt | BinaryOp::LtEq | BinaryOp::Gt | BinaryOp::GtEq) {
+        return None;
+    }
+    let column = column_index(scan, column)?;
+    let type_info = &table.columns()[column].type_info;
+    let values: Vec<Value> = literals.into_iter().map(Value::from).collect();
+    values
+        .iter()
+        .all(|value| index::comparable(value, type_info))
+        .then_some(Sarg { column, op, values })
+}
+
+/// 扫描输出的列在表中的下标
+fn column_index(scan: &LogicalPlan, column: &ColumnRef) -> Option<usize> {
+    let LogicalPlan::Scan { schema, projection, .. } = scan else {
+        return None;
+    };
+    let idx = schema.iter().position(|col| col.table == column.table && col.name == column.name)?;
+    Some(projection.as_ref().map_or(idx, |projection| projection[idx]))
+}
+
+/// 索引能用上的查找范围，以及用到的条件在 `sargs` 中的下标
+fn match_index(index: &Candidate, sargs: &[Option<Sarg>]) -> Option<(Vec<IndexRange>, Vec<usize>)> {
+    let mut ranges = vec![IndexRange { prefix: Vec::new(), lower: None, upper: None }];
+    let mut used = Vec::new();
+    for &column in index.columns {
+        let find = |ops: &[BinaryOp]| {
+            sargs.iter().enumerate().find_map(|(idx, sarg)| match sarg {
+                Some(sarg) if sarg.column == column && ops.contains(&sarg.op) => Some((idx, sarg)),
+                _ => None
+            })
+        };
+        if let Some((idx, sarg)) = find(&[BinaryOp::Eq]) {
+            // `IN` 的每个值各对应一组范围
+            ranges = ranges
+                .into_iter()
+                .flat_map(|range| {
+                    sarg.values.iter().map(move |value| {
+                        let mut range = range.clone();
+                        range.prefix.push(value.clone());
+                        range
+                    })
+                })
+                .collect();
+            used.push(idx);
+            continue;
+        }
+        if index.kind == IndexKind::Hash {
+            return None;
+        }
+        let lower = find(&[BinaryOp::Gt, BinaryOp::GtEq]);
+        let upper = find(&[BinaryOp::Lt, BinaryOp::LtEq]);
+        for range in &mut ranges {
+            range.lower = lower.map(|(_, sarg)| (sarg.values[0].clone(), sarg.op == BinaryOp::GtEq));
+            range.upper = upper.map(|(_, sarg)| (sarg.values[0].clone(), sarg.op == BinaryOp::LtEq));
+        }
+        used.extend(lower.into_iter().chain(upper).map(|(idx, _)| idx));
+        break;
+    }
+    (!used.is_empty()).then_some((ranges, used))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_util::{bind, insert, tables};
+
+    fn indexed(indexes: &[(&str, &[&str], IndexKind)]) -> HashMap<String, Table> {
+        let mut tables = tables();
+        let users = tables.get_mut("users").unwrap();
+        for (name, columns, kind) in indexes {
+            let columns: Vec<String> = columns.iter().map(|column| column.to_string()).collect();
+            users.create_index(name.to_string(), &columns, false, *kind).unwrap();
+        }
+        tables
+    }
+
+    fn selected(tables: &HashMap<String, Table>, sql: &str) -> String {
+        IndexSelection.optimize(tables, bind(tables, sql)).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_select_index() {
+        let tables = indexed(&[
+            ("users_age", &["age"], IndexKind::BTree),
+            ("users_age_id", &["age", "id"], IndexKind::BTree)
+        ]);
+        assert_eq!(
+            selected(&tables, "SELECT id FROM users WHERE 21 = age AND id + 1 > 2"),
+            "Project: users.id\n  \
+               Filter: users.id + 1 > 2\n    \
+                 Scan: users using users_age: 21 = users.age\n"
+        );
+        // 复合索引能用上更多条件
+        assert_eq!(
+            selected(&tables, "SELECT id FROM users WHERE age = 21 AND id > 1 AND id <= 5 AND id > 0"),
+            "Project: users.id\n  \
+               Filter: users.id > 0\n    \
+                 Scan: users using users_age_id: users.age = 21 AND users.id > 1 AND users.id <= 5\n"
+        );
+        assert_eq!(
+            selected(&tables, "SELECT id FROM users u WHERE u.age < 30"),
+            "Project: u.id\n  Scan: users AS u using users_age: u.age < 30\n"
+        );
+
+        // 索引的第一列没有条件、常量为 NULL 或类型不能比较时不用索引
+        for sql in [
+            "SELECT id FROM users WHERE id = 1",
+            "SELECT id FROM users WHERE age = NULL",
+            "SELECT id FROM users WHERE age = 'a'",
+            "SELECT id FROM users WHERE age <> 1 OR age = 2"
+        ] {
+            assert_eq!(selected(&tables, sql), bind(&tables, sql).to_string());
+        }
+    }
+
+    #[test]
+    fn test_in_list() {
+        let tables = indexed(&[("users_age_id", &["age", "id"], IndexKind::BTree)]);
+        let plan = IndexSelection
+            .optimize(&tables, bind(&tables, "SELECT id FROM users WHERE age IN (1, 2) AND id < 3"))
+            .unwrap();
+        let LogicalPlan::Scan { index: Some(index), .. } = plan.children()[0] else {
+            panic!("expected an index scan: {plan}")
+        };
+        assert_eq!(index.predicate.to_string(), "users.age IN (1, 2) AND users.id < 3");
+        let upper = Some((Value::Int(3), false));
+        assert_eq!(index.ranges, vec![
+            IndexRange { prefix: vec![Value::Int(1)], lower: None, upper: upper.clone() },
+            IndexRange { prefix: vec![Value::Int(2)], lower: None, upper }
+        ]);
+
+        // NOT IN 和含有非常量的列表不用索引
+        for sql in ["SELECT id FROM users WHERE age NOT IN (1)", "SELECT id FROM users WHERE age IN (1, id)"] {
+            assert_eq!(selected(&tables, sql), bind(&tables, sql).to_string());
+        }
+    }
+
+    #[test]
+    fn test_hash_index() {
+        let tables = indexed(&[
+            ("users_age", &["age"], IndexKind::BTree),
+            ("users_age_hash", &["age"], IndexKind::Hash),
+            ("users_id_age", &["id", "age"], IndexKind::Hash)
+        ]);
+        // 选择率相同时优先哈希索引
+        assert_eq!(
+            selected(&tables, "SELECT id FROM users WHERE age IN (1, 2)"),
+            "Project: users.id\n  Scan: users using users_age_hash: users.age IN (1, 2)\n"
+        );
+        assert_eq!(
+            selected(&tables, "SELECT id FROM users WHERE age > 1 AND id = 2"),
+            "Project: users.id\n  Filter: users.id = 2\n    Scan: users using users_age: users.age > 1\n"
+        );
+        // 哈希索引需要所有列都取等值
+        assert_eq!(
+            selected(&tables, "SELECT id FROM users WHERE age = 1 AND id = 2"),
+            "Project: users.id\n  Scan: users using users_id_age: users.age = 1 AND users.id = 2\n"
+        );
+        let tables = indexed(&[("users_id_age", &["id", "age"], IndexKind::Hash)]);
+        let sql = "SELECT id FROM users WHERE id = 2 AND age > 1";
+        assert_eq!(selected(&tables, sql), bind(&tables, sql).to_string());
+    }
+
+    #[test]
+    fn test_skip_unselective_index() {
+        let mut tables = indexed(&[("users_age", &["age"], IndexKind::BTree)]);
+        let users = tables.get_mut("users").unwrap();
+        insert(users, (0..100).map(|i| vec![i, i]));
+        users.analyze();
+
+        let sql = "SELECT id FROM users WHERE age >= 0";
+        assert_eq!(selected(&tables, sql), bind(&tables, sql).to_string());
+        assert_eq!(
+            selected(&tables, "SELECT id FROM users WHERE age > 95"),
+            "Project: users.id\n  Scan: users using users_age: users.age > 95\n"
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+use sql_parser::commands::JoinKind;
+use sql_parser::expr::Expr;
+use crate::error::QueryExceptionError;
+use crate::plan::LogicalPlan;
+use crate::row::RowSchema;
+use crate::table::Table;
+use super::cost::Estimator;
+use super::{conjunction, referenced, split_conjunction, OptimizerRule};
+
+/// 超过这个数量的表不做穷举，保持原有顺序
+const MAX_REORDER_RELATIONS: usize = 10;
+
+/// 基于代价的连接重排：把相邻的内连接和交叉连接看作一组表，选择中间结果总行数最少的左深连接顺序
+///
+/// 行数由 ANALYZE 收集的统计信息估计，有表没有统计信息时保持查询中的顺序。
+/// 外连接不参与重排，但其两侧各自可以重排
+#[derive(Debug)]
+pub(crate) struct JoinReorder;
+
+impl OptimizerRule for JoinReorder {
+    fn name(&self) -> &'static str {
+        "join_reorder"
+    }
+
+    fn optimize(&self, tables: &HashMap<String, Table>, plan: LogicalPlan) -> Result<LogicalPlan, QueryExceptionError> {
+        match plan {
+            LogicalPlan::Join { kind: JoinKind::Inner | JoinKind::Cross, .. } => {
+                let mut relations = Vec::new();
+                let mut conjuncts = Vec::new();
+                flatten(plan, &mut relations, &mut conjuncts);
+                let relations = relations
+                    .into_iter()
+                    .map(|relation| self.optimize(tables, relation))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let order = best_order(tables, &relations, &conjuncts)
+                    .unwrap_or_else(|| (0..relations.len()).collect());
+                Ok(build(relations, conjuncts, &order))
+            }
+            plan => plan.map_children(|child| self.optimize(tables, child))
+        }
+    }
+}
+
+/// 把一组内连接拆成参与连接的子计划和所有连接条件
+fn flatten(plan: LogicalPlan, relations: &mut Vec<LogicalPlan>, conjuncts: &mut Vec<Expr>) {
+    match plan {
+        LogicalPlan::Join { left, right, kind: JoinKind::Inner | JoinKind::Cross, on } => {
+            flatten(*left, relations, conjuncts);
+            flatten(*right, relations, conjuncts);
+            if let Some(on) = on {
+                split_conjunction(on, conjuncts);
+            }
+        }
+        plan => relations.push(plan)
+    }
+}
+
+/// 按动态规划求出代价最小的连接顺序，代价为每次连接输出的行数之和
+///
+/// 估计不出行数时返回 `None`
+fn best_order(tables: &HashMap<String, Table>, relations: &[LogicalPlan], conjuncts: &[Expr]) -> Option<Vec<usize>> {
+    let count = relations.len();
+    if count > MAX_REORDER_RELATIONS {
+        return None;
+    }
+
+    let estimator = Estimator::new(tables, relations);
+    let rows = relations.iter().map(|relation| estimator.rows(relation)).collect::<Option<Vec<_>>>()?;
+    let schemas: Vec<_> = relations.iter().map(LogicalPlan::schema).collect();
+    let predicates: Vec<(usize, f64)> = conjuncts
+        .iter()
+        .map(|conjunct| (relations_mask(conjunct, &schemas), estimator.selectivity(conjunct)))
+        .collect();
+
+    // 一组表连接后的行数与连接顺序无关
+    let cardinality = |set: usize| -> f64 {
+        let rows: f64 = (0..count).filter(|idx| set & (1 << idx) != 0).map(|idx| rows[idx]).product();
+        predicates
+            .iter()
+            .filter(|(mask, _)| mask & set == *mask)
+            .map(|(_, selectivity)| selectivity)
+            .product::<f64>() * rows
+    };
+
+    // best[set] 为连接 set 中的表的最小代价，以及最后连接的表
+    let mut best: Vec<(f64, usize)> = vec![(0.0, 0); 1 << count];
+    for set in 1..(1usize << count) {
+        if set.count_ones() == 1 {
+            best[set] = (0.0, set.trailing_zeros() as usize);
+            continue;
+        }
+        let rows = cardinality(set);
+        // 从后往前找，代价相同时保持原有顺序
+        let mut choice = (f64::INFINITY, 0);
+        for last in (0..count).rev().filter(|idx| set & (1 << idx) != 0) {
+            let cost = best[set & !(1 << last)].0 + rows;
+            if cost < choice.0 {
+                choice = (cost, last);
+            }
+        }
+        best[set] = choice;
+    }
+
+    let mut order = Vec::with_capacity(count);
+    let mut set = (1 << count) - 1;
+    while set != 0 {
+        let last = best[set].1;
+        order.push(last);
+        set &= !(1 << last);
+    }
+    order.reverse();
+    Some(order)
+}
+
+/// 条件引用到的表，不引用任何列的条件视为引用了所有表
+fn relations_mask(expr: &Expr, schemas: &[Rc<RowSchema>]) -> usize {
+    let mut mask = 0;
+    for column in referenced([expr], Default::default()) {
+        for (idx, schema) in schemas.iter().enumerate() {
+            if schema.iter().any(|col| col.table == column.table && col.name == column.name) {
+                mask |= 1 << idx;
+            }
+        }
+    }
+    if mask == 0 { (1 << schemas.len()) - 1 } else { mask }
+}
+
+/// 按 `order` 构造左深连接树，每个条件放在它引用的表都已连接的最早位置
+fn build(relations: Vec<LogicalPlan>, conjuncts: Vec<Expr>, order: &[usize]) -> LogicalPlan {
+    let schemas: Vec<_> = relations.iter().map(LogicalPlan::schema).collect();
+    let mut conjuncts: Vec<(usize, Expr)> = conjuncts
+        .into_iter()
+        .map(|conjunct| (relations_mask(&conjunct, &schemas), conjunct))
+        .collect();
+    let mut relations: Vec<Option<LogicalPlan>> = relations.into_iter().map(Some).collect();
+
+    let mut joined = 1 << order[0];
+    let mut plan = relations[order[0]].take().expect("each relation is joined once");
+    for &idx in &order[1..] {
+        joined |= 1 << idx;
+        let (on, rest): (Vec<_>, Vec<_>) = conjuncts.into_iter().partition(|(mask, _)| mask & joined == *mask);
+        conjuncts = rest;
+        let on = conjunction(on.into_iter().map(|(_, conjunct)| conjunct).collect());
+        plan = LogicalPlan::Join {
+            left: Box::new(plan),
+            right: Box::new(relations[idx].take().expect("each relation is joined once")),
+            kind: if on.is_some() { JoinKind::Inner } else { JoinKind::Cross },
+            on
+        };
+    }
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::PredicatePushdown;
+    use super::super::test_util::{self, bind, table, tables};
+
+    #[test]
+    fn test_reorder_joins() {
+        let mut tables = tables();
+        tables.insert("items".to_string(), table("items", &["id", "oid"]));
+        let insert = |tables: &mut HashMap<String, Table>, name: &str, rows: Vec<Vec<i64>>| {
+            let table = tables.get_mut(name).unwrap();
+            test_util::insert(table, rows);
+            table.analyze();
+        };
+        insert(&mut tables, "users", (0..10).map(|i| vec![i, 20 + i]).collect());
+        insert(&mut tables, "orders", (0..1000).map(|i| vec![i, i % 10, i]).collect());
+        insert(&mut tables, "items", (0..5000).map(|i| vec![i, i % 1000]).collect());
+
+        let reorder = |sql: &str| {
+            let plan = PredicatePushdown.optimize(&tables, bind(&tables, sql)).unwrap();
+            JoinReorder.optimize(&tables, plan).unwrap().to_string()
+        };
+        // 先连接过滤后只剩一行的 users，且避免交叉连接
+        assert_eq!(
+            reorder(
+                "SELECT i.id FROM items i, orders o, users u \
+                 WHERE i.oid = o.id AND o.uid = u.id AND u.age = 21"
+            ),
+            "Project: i.id\n  \
+               Inner Join: i.oid = o.id\n    \
+                 Inner Join: o.uid = u.id\n      \
+                   Scan: orders AS o\n      \
+                   Filter: u.age = 21\n        \
+                     Scan: users AS u\n    \
+                 Scan: items AS i\n"
+        );
+
+        // 外连接不参与重排
+        assert_eq!(
+            reorder("SELECT i.id FROM items i LEFT JOIN users u ON i.id = u.id"),
+            "Project: i.id\n  Left Join: i.id = u.id\n    Scan: items AS i\n    Scan: users AS u\n"
+        );
+    }
+
+    #[test]
+    fn test_keep_order_without_statistics() {
+        let tables = tables();
+        let sql = "SELECT u.id FROM orders o JOIN users u ON u.id = o.uid";
+        assert_eq!(
+            JoinReorder.optimize(&tables, bind(&tables, sql)).unwrap().to_string(),
+            bind(&tables, sql).to_string()
+        );
+    }
+}
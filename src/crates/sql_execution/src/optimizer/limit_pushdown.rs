@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use crate::error::QueryExceptionError;
+use crate::plan::LogicalPlan;
+use crate::table::Table;
+use super::OptimizerRule;
+
+/// LIMIT 下推：把 LIMIT 需要的行数告诉下方的排序，相邻的 LIMIT 合并为一个
+///
+/// 排序得知只需要前 `offset + limit` 行后可以只保留这些行，不必对全部输入排序
+#[derive(Debug)]
+pub(crate) struct LimitPushdown;
+
+impl OptimizerRule for LimitPushdown {
+    fn name(&self) -> &'static str {
+        "limit_pushdown"
+    }
+
+    fn optimize(&self, _tables: &HashMap<String, Table>, plan: LogicalPlan) -> Result<LogicalPlan, QueryExceptionError> {
+        push_down(plan)
+    }
+}
+
+fn push_down(plan: LogicalPlan) -> Result<LogicalPlan, QueryExceptionError> {
+    let plan = match plan {
+        LogicalPlan::Limit { input, offset, limit } => match *input {
+            LogicalPlan::Limit { input, offset: inner_offset, limit: inner_limit } => {
+                let limit = match (inner_limit, limit) {
+                    (Some(inner), Some(outer)) => Some(inner.saturating_sub(offset).min(outer)),
+                    (Some(inner), None) => Some(inner.saturating_sub(offset)),
+                    (None, limit) => limit
+                };
+                return push_down(LogicalPlan::Limit { input, offset: inner_offset + offset, limit });
+            }
+            LogicalPlan::Sort { input: sort_input, order_by, fetch } => {
+                let fetch = match (fetch, limit) {
+                    (Some(fetch), Some(limit)) => Some(fetch.min(offset + limit)),
+                    (fetch, None) => fetch,
+                    (None, Some(limit)) => Some(offset + limit)
+                };
+                let sort = LogicalPlan::Sort { input: sort_input, order_by, fetch };
+                LogicalPlan::Limit { input: Box::new(sort), offset, limit }
+            }
+            input => LogicalPlan::Limit { input: Box::new(input), offset, limit }
+        },
+        plan => plan
+    };
+    plan.map_children(push_down)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_util::{plan, tables};
+
+    #[test]
+    fn test_push_into_sort() {
+        let pushed = |sql: &str| LimitPushdown.optimize(&tables(), plan(sql)).unwrap().to_string();
+        assert_eq!(
+            pushed("SELECT id FROM users ORDER BY age LIMIT 5 OFFSET 2"),
+            "Project: users.id\n  \
+               Limit: 5 OFFSET 2\n    \
+                 Sort: users.age ASC NULLS LAST, fetch=7\n      \
+                   Scan: users\n"
+        );
+
+        assert_eq!(
+            pushed("SELECT id FROM users LIMIT 3"),
+            "Project: users.id\n  Limit: 3 OFFSET 0\n    Scan: users\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_limits() {
+        let scan = || Box::new(plan("SELECT * FROM users").children()[0].clone());
+        let merged = |inner: (usize, Option<usize>), outer: (usize, Option<usize>)| {
+            let inner = LogicalPlan::Limit { input: scan(), offset: inner.0, limit: inner.1 };
+            let outer = LogicalPlan::Limit { input: Box::new(inner), offset: outer.0, limit: outer.1 };
+            LimitPushdown.optimize(&tables(), outer).unwrap().to_string()
+        };
+        assert_eq!(merged((2, Some(10)), (3, Some(5))), "Limit: 5 OFFSET 5\n  Scan: users\n");
+        assert_eq!(merged((2, Some(4)), (3, Some(5))), "Limit: 1 OFFSET 5\n  Scan: users\n");
+        assert_eq!(merged((2, Some(4)), (6, None)), "Limit: 0 OFFSET 8\n  Scan: users\n");
+        assert_eq!(merged((2, None), (1, Some(3))), "Limit: 3 OFFSET 3\n  Scan: users\n");
+    }
+}
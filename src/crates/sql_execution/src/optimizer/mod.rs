@@ -0,0 +1,202 @@
+mod constant_folding;
+mod cost;
+mod index_selection;
+mod join_reorder;
+mod limit_pushdown;
+mod predicate_pushdown;
+mod projection_pruning;
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use sql_parser::expr::{BinaryOp, ColumnRef, Expr};
+use crate::error::QueryExceptionError;
+use crate::plan::LogicalPlan;
+use crate::table::Table;
+
+pub(crate) use constant_folding::ConstantFolding;
+pub(crate) use index_selection::IndexSelection;
+pub(crate) use join_reorder::JoinReorder;
+pub(crate) use limit_pushdown::LimitPushdown;
+pub(crate) use predicate_pushdown::PredicatePushdown;
+pub(crate) use projection_pruning::ProjectionPruning;
+
+/// 逻辑计划的一条改写规则，改写前后的计划必须返回相同的结果
+pub(crate) trait OptimizerRule: fmt::Debug {
+    /// 规则名，用于单独开关规则
+    fn name(&self) -> &'static str;
+
+    /// `tables` 为当前的目录，用于读取表的统计信息
+    fn optimize(&self, tables: &HashMap<String, Table>, plan: LogicalPlan) -> Result<LogicalPlan, QueryExceptionError>;
+}
+
+/// 基于规则的优化器，按顺序应用所有启用的规则
+#[derive(Debug)]
+pub(crate) struct Optimizer {
+    rules: Vec<(Box<dyn OptimizerRule>, bool)>
+}
+
+impl Default for Optimizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Optimizer {
+    /// 默认启用所有规则
+    ///
+    /// 先折叠常量，使 `WHERE 1 = 1` 之类的条件在下推前就被去掉；
+    /// 连接重排需要下推后留在 `ON` 中的连接条件；选择索引需要已经下推到表上的过滤条件；
+    /// 裁剪列放在最后，此时表达式已经确定
+    pub fn new() -> Self {
+        let rules: Vec<Box<dyn OptimizerRule>> = vec![
+            Box::new(ConstantFolding),
+            Box::new(PredicatePushdown),
+            Box::new(JoinReorder),
+            Box::new(IndexSelection),
+            Box::new(LimitPushdown),
+            Box::new(ProjectionPruning)
+        ];
+        Self { rules: rules.into_iter().map(|rule| (rule, true)).collect() }
+    }
+
+    /// 开关一条规则，没有这条规则时返回 `false`
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        match self.rules.iter_mut().find(|(rule, _)| rule.name() == name) {
+            Some((_, flag)) => {
+                *flag = enabled;
+                true
+            }
+            None => false
+        }
+    }
+
+    pub fn optimize(
+        &self,
+        tables: &HashMap<String, Table>,
+        mut plan: LogicalPlan
+    ) -> Result<LogicalPlan, QueryExceptionError> {
+        for (rule, enabled) in &self.rules {
+            if *enabled {
+                plan = rule.optimize(tables, plan)?;
+            }
+        }
+        Ok(plan)
+    }
+}
+
+/// 按 AND 拆分谓词
+fn split_conjunction(expr: Expr, conjuncts: &mut Vec<Expr>) {
+    match expr {
+        Expr::Binary { left, op: BinaryOp::And, right } => {
+            split_conjunction(*left, conjuncts);
+            split_conjunction(*right, conjuncts);
+        }
+        expr => conjuncts.push(expr)
+    }
+}
+
+/// 把 `exprs` 引用的列加入 `columns`
+fn referenced<'e>(exprs: impl IntoIterator<Item = &'e Expr>, mut columns: HashSet<ColumnRef>) -> HashSet<ColumnRef> {
+    fn visit(expr: &Expr, columns: &mut HashSet<ColumnRef>) {
+        match expr {
+            Expr::Column(column) => {
+                columns.insert(column.clone());
+            }
+            Expr::Literal(_) | Expr::NextVal(_) | Expr::Aggregate { arg: None, .. } => {}
+            Expr::Aggregate { arg: Some(expr), .. } | Expr::Unary { expr, .. } => visit(expr, columns),
+            Expr::Binary { left, right, .. } => {
+                visit(left, columns);
+                visit(right, columns);
+            }
+            Expr::InList { expr, list, .. } => {
+                visit(expr, columns);
+                list.iter().for_each(|item| visit(item, columns));
+            }
+        }
+    }
+
+    for expr in exprs {
+        visit(expr, &mut columns);
+    }
+    columns
+}
+
+/// 用 AND 从左到右连接谓词，没有谓词时返回 `None`
+fn conjunction(conjuncts: Vec<Expr>) -> Option<Expr> {
+    conjuncts.into_iter().reduce(|left, right| Expr::Binary {
+        left: Box::new(left),
+        op: BinaryOp::And,
+        right: Box::new(right)
+    })
+}
+
+#[cfg(test)]
+mod test_util {
+    use std::collections::HashMap;
+    use sql_parser::ast::{parse_sql_query, SqlQuery};
+    use sql_parser::expr::{Expr, Literal};
+    use sql_parser::{Column, SqlTypeInfo};
+    use crate::binder::Binder;
+    use crate::plan::LogicalPlan;
+    use crate::table::Table;
+
+    /// 只有 `int` 列的空表
+    pub fn table(name: &str, columns: &[&str]) -> Table {
+        Table::new(name, columns.iter().map(|name| Column::new(*name, SqlTypeInfo::Int)).collect(), &[]).unwrap()
+    }
+
+    /// 按列的顺序插入整数行
+    pub fn insert(table: &mut Table, rows: impl IntoIterator<Item = Vec<i64>>) {
+        let rows: Vec<Vec<Expr>> = rows
+            .into_iter()
+            .map(|row| row.into_iter().map(|value| Expr::Literal(Literal::Int(value))).collect())
+            .collect();
+        let rows = table.prepare_insert(None, &rows, &mut HashMap::new()).unwrap();
+        table.write(rows).unwrap();
+    }
+
+    /// `users (id, age)` 和 `orders (id, uid, total)`，没有统计信息
+    pub fn tables() -> HashMap<String, Table> {
+        HashMap::from([
+            ("users".to_string(), table("users", &["id", "age"])),
+            ("orders".to_string(), table("orders", &["id", "uid", "total"]))
+        ])
+    }
+
+    pub fn bind(tables: &HashMap<String, Table>, sql: &str) -> LogicalPlan {
+        let SqlQuery::Select(select) = parse_sql_query(&format!("{sql};")).unwrap() else {
+            panic!("expected a select statement")
+        };
+        Binder::new(tables).bind_select(select).unwrap()
+    }
+
+    /// 在 [`tables`] 上绑定查询
+    pub fn plan(sql: &str) -> LogicalPlan {
+        bind(&tables(), sql)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::test_util::{plan, tables};
+
+    #[test]
+    fn test_toggle_rules() {
+        let sql = "SELECT u.id FROM users u, orders o WHERE 1 = 1 AND u.id = o.uid";
+        let mut optimizer = Optimizer::new();
+        assert_eq!(
+            optimizer.optimize(&tables(), plan(sql)).unwrap().to_string(),
+            "Project: u.id\n  \
+               Inner Join: u.id = o.uid\n    \
+                 Scan: users AS u (id)\n    \
+                 Scan: orders AS o (uid)\n"
+        );
+
+        for rule in ["constant_folding", "predicate_pushdown", "join_reorder", "index_selection", "limit_pushdown", "projection_pruning"] {
+            assert!(optimizer.set_enabled(rule, false));
+        }
+        assert!(!optimizer.set_enabled("nope", false));
+        assert_eq!(optimizer.optimize(&tables(), plan(sql)).unwrap().to_string(), plan(sql).to_string());
+    }
+}
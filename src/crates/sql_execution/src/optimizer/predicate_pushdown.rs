@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use sql_parser::commands::JoinKind;
+use sql_parser::expr::Expr;
+use crate::error::QueryExceptionError;
+use crate::eval;
+use crate::plan::LogicalPlan;
+use crate::row::RowSchema;
+use crate::table::Table;
+use super::{conjunction, split_conjunction, OptimizerRule};
+
+/// 谓词下推：把过滤条件按 AND 拆开，尽量移动到连接下方靠近扫描的位置
+///
+/// - 内连接和交叉连接：只引用一侧的条件下推到该侧，引用两侧的条件并入 `ON`，
+///   这样逗号连接也能使用哈希连接
+/// - 外连接：`WHERE` 条件只能下推到保留所有行的一侧，`ON` 条件只能下推到补 NULL 的一侧
+/// - 聚合、排序和 LIMIT 之上的条件不会穿过它们
+#[derive(Debug)]
+pub(crate) struct PredicatePushdown;
+
+impl OptimizerRule for PredicatePushdown {
+    fn name(&self) -> &'static str {
+        "predicate_pushdown"
+    }
+
+    fn optimize(&self, _tables: &HashMap<String, Table>, plan: LogicalPlan) -> Result<LogicalPlan, QueryExceptionError> {
+        push_down(plan, Vec::new())
+    }
+}
+
+/// 把 `predicates` 下推到 `plan` 中，不能继续下推的留在 `plan` 上方
+fn push_down(plan: LogicalPlan, mut predicates: Vec<Expr>) -> Result<LogicalPlan, QueryExceptionError> {
+    match plan {
+        LogicalPlan::Filter { input, predicate } => {
+            split_conjunction(predicate, &mut predicates);
+            push_down(*input, predicates)
+        }
+        LogicalPlan::Join { left, right, kind, on } => {
+            let (left_schema, right_schema) = (left.schema(), right.schema());
+            let side = |expr: &Expr| Side {
+                left: references_only(expr, &left_schema),
+                right: references_only(expr, &right_schema)
+            };
+
+            let mut to_left = Vec::new();
+            let mut to_right = Vec::new();
+            let mut on_conjuncts = Vec::new();
+            let mut remaining = Vec::new();
+            if let Some(on) = on {
+                split_conjunction(on, &mut on_conjuncts);
+            }
+
+            for predicate in predicates {
+                let side = side(&predicate);
+                match kind {
+                    JoinKind::Inner | JoinKind::Cross if side.left => to_left.push(predicate),
+                    JoinKind::Inner | JoinKind::Cross if side.right => to_right.push(predicate),
+                    JoinKind::Inner | JoinKind::Cross => on_conjuncts.push(predicate),
+                    JoinKind::Left if side.left => to_left.push(predicate),
+                    JoinKind::Right if side.right => to_right.push(predicate),
+                    _ => remaining.push(predicate)
+                }
+            }
+
+            let mut on = Vec::new();
+            for predicate in on_conjuncts {
+                let side = side(&predicate);
+                match kind {
+                    JoinKind::Inner | JoinKind::Cross | JoinKind::Right if side.left => to_left.push(predicate),
+                    JoinKind::Inner | JoinKind::Cross | JoinKind::Left if side.right => to_right.push(predicate),
+                    _ => on.push(predicate)
+                }
+            }
+
+            let kind = match kind {
+                JoinKind::Cross if !on.is_empty() => JoinKind::Inner,
+                kind => kind
+            };
+            let join = LogicalPlan::Join {
+                left: Box::new(push_down(*left, to_left)?),
+                right: Box::new(push_down(*right, to_right)?),
+                kind,
+                on: conjunction(on)
+            };
+            Ok(filter(join, remaining))
+        }
+        LogicalPlan::Scan { .. } => Ok(filter(plan, predicates)),
+        plan => Ok(filter(plan.map_children(|child| push_down(child, Vec::new()))?, predicates))
+    }
+}
+
+struct Side {
+    left: bool,
+    right: bool
+}
+
+/// 列引用在绑定后都带有表名，能在 `schema` 中解析说明只引用了这一侧的列
+fn references_only(expr: &Expr, schema: &RowSchema) -> bool {
+    eval::check_columns(expr, schema).is_ok()
+}
+
+fn filter(plan: LogicalPlan, predicates: Vec<Expr>) -> LogicalPlan {
+    match conjunction(predicates) {
+        Some(predicate) => LogicalPlan::Filter { input: Box::new(plan), predicate },
+        None => plan
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_util::{plan, tables};
+
+    fn pushed(sql: &str) -> String {
+        PredicatePushdown.optimize(&tables(), plan(sql)).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_push_into_inner_join() {
+        assert_eq!(
+            pushed("SELECT u.id FROM users u, orders o WHERE u.id = o.uid AND u.age > 1 AND o.total < 5 AND u.age < o.total"),
+            "Project: u.id\n  \
+               Inner Join: u.id = o.uid AND u.age < o.total\n    \
+                 Filter: u.age > 1\n      \
+                   Scan: users AS u\n    \
+                 Filter: o.total < 5\n      \
+                   Scan: orders AS o\n"
+        );
+        assert_eq!(
+            pushed("SELECT u.id FROM users u JOIN orders o ON u.id = o.uid AND o.total > 1"),
+            "Project: u.id\n  \
+               Inner Join: u.id = o.uid\n    \
+                 Scan: users AS u\n    \
+                 Filter: o.total > 1\n      \
+                   Scan: orders AS o\n"
+        );
+    }
+
+    #[test]
+    fn test_push_into_outer_join() {
+        // WHERE 中只引用补 NULL 一侧的条件不能下推，否则会把本应过滤掉的行补回来
+        assert_eq!(
+            pushed("SELECT u.id FROM users u LEFT JOIN orders o ON u.id = o.uid AND u.age > 1 AND o.total > 2 WHERE u.id > 3 AND o.total < 5"),
+            "Project: u.id\n  \
+               Filter: o.total < 5\n    \
+                 Left Join: u.id = o.uid AND u.age > 1\n      \
+                   Filter: u.id > 3\n        \
+                     Scan: users AS u\n      \
+                   Filter: o.total > 2\n        \
+                     Scan: orders AS o\n"
+        );
+        assert_eq!(
+            pushed("SELECT u.id FROM users u FULL JOIN orders o ON u.id = o.uid AND u.age > 1 WHERE u.id > 3"),
+            "Project: u.id\n  \
+               Filter: u.id > 3\n    \
+                 Full Join: u.id = o.uid AND u.age > 1\n      \
+                   Scan: users AS u\n      \
+                   Scan: orders AS o\n"
+        );
+    }
+
+    #[test]
+    fn test_having_stays_above_aggregate() {
+        assert_eq!(
+            pushed("SELECT age FROM users WHERE id > 1 GROUP BY age HAVING COUNT(*) > 1"),
+            "Project: users.age AS age\n  \
+               Filter: COUNT(*) > 1\n    \
+                 Aggregate: group_by=[users.age], aggregates=[COUNT(*)]\n      \
+                   Filter: users.id > 1\n        \
+                     Scan: users\n"
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use sql_parser::expr::ColumnRef;
+use crate::error::QueryExceptionError;
+use crate::plan::LogicalPlan;
+use crate::table::Table;
+use super::{referenced, OptimizerRule};
+
+/// 列裁剪：扫描时只读取上层实际引用到的列
+///
+/// 自顶向下收集每个节点引用的列，绑定后列引用都带有表名，因此连接两侧可以共用同一个集合
+#[derive(Debug)]
+pub(crate) struct ProjectionPruning;
+
+impl OptimizerRule for ProjectionPruning {
+    fn name(&self) -> &'static str {
+        "projection_pruning"
+    }
+
+    fn optimize(&self, _tables: &HashMap<String, Table>, plan: LogicalPlan) -> Result<LogicalPlan, QueryExceptionError> {
+        prune(plan, None)
+    }
+}
+
+/// `required` 为上层需要 `plan` 输出的列，`None` 表示需要所有列
+fn prune(plan: LogicalPlan, required: Option<HashSet<ColumnRef>>) -> Result<LogicalPlan, QueryExceptionError> {
+    let required = match &plan {
+        LogicalPlan::Scan { .. } => return Ok(prune_scan(plan, required)),
+        LogicalPlan::Project { exprs, .. } => Some(referenced(exprs.iter().map(|(_, expr)| expr), HashSet::new())),
+        // 聚合的输出列是聚合后的结果，输入只需要分组和聚合参数引用的列
+        LogicalPlan::Aggregate { aggregation, .. } => Some(referenced(
+            aggregation.group_by.iter().chain(aggregation.aggregates.iter()),
+            HashSet::new()
+        )),
+        LogicalPlan::Filter { predicate, .. } => required.map(|set| referenced([predicate], set)),
+        LogicalPlan::Join { on, .. } => required.map(|set| referenced(on, set)),
+        LogicalPlan::Sort { order_by, .. } => required.map(|set| referenced(order_by.iter().map(|item| &item.expr), set)),
+        LogicalPlan::Limit { .. } => required
+    };
+    plan.map_children(|child| prune(child, required.clone()))
+}
+
+fn prune_scan(plan: LogicalPlan, required: Option<HashSet<ColumnRef>>) -> LogicalPlan {
+    let (LogicalPlan::Scan { table, qualifier, schema, projection, index }, Some(required)) = (&plan, required) else {
+        return plan;
+    };
+    let kept: Vec<usize> = schema
+        .iter()
+        .enumerate()
+        .filter(|(_, col)| required.contains(&ColumnRef { table: col.table.clone(), name: col.name.to_owned() }))
+        .map(|(idx, _)| idx)
+        .collect();
+    if kept.len() == schema.len() {
+        return plan;
+    }
+    LogicalPlan::Scan {
+        table: table.to_owned(),
+        qualifier: qualifier.to_owned(),
+        schema: Rc::new(kept.iter().map(|&idx| schema[idx].clone()).collect()),
+        // 已经裁剪过的扫描，下标需要换算回表中的下标
+        projection: Some(
+            kept.iter()
+                .map(|&idx| projection.as_ref().map_or(idx, |projection| projection[idx]))
+                .collect()
+        ),
+        index: index.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_util::{plan, tables};
+
+    fn pruned(sql: &str) -> String {
+        ProjectionPruning.optimize(&tables(), plan(sql)).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_prune_scans() {
+        assert_eq!(
+            pruned("SELECT o.total FROM users u JOIN orders o ON u.id = o.uid WHERE u.age > 1 ORDER BY o.id"),
+            "Project: o.total\n  \
+               Sort: o.id ASC NULLS LAST\n    \
+                 Filter: u.age > 1\n      \
+                   Inner Join: u.id = o.uid\n        \
+                     Scan: users AS u\n        \
+                     Scan: orders AS o\n"
+        );
+        assert_eq!(
+            pruned("SELECT total FROM orders WHERE uid > 1"),
+            "Project: orders.total\n  Filter: orders.uid > 1\n    Scan: orders (uid, total)\n"
+        );
+        assert_eq!(
+            pruned("SELECT uid, SUM(total) AS sum FROM orders GROUP BY uid"),
+            "Project: orders.uid AS uid, SUM(orders.total) AS sum\n  \
+               Aggregate: group_by=[orders.uid], aggregates=[SUM(orders.total)]\n    \
+                 Scan: orders (uid, total)\n"
+        );
+        assert_eq!(pruned("SELECT COUNT(*) FROM orders"), "Project: COUNT(*)\n  Aggregate: group_by=[], aggregates=[COUNT(*)]\n    Scan: orders ()\n");
+    }
+
+    #[test]
+    fn test_prune_twice() {
+        let once = ProjectionPruning.optimize(&tables(), plan("SELECT total FROM orders")).unwrap();
+        let twice = ProjectionPruning.optimize(&tables(), once.clone()).unwrap();
+        assert_eq!(once.to_string(), twice.to_string());
+        let LogicalPlan::Scan { projection, .. } = twice.children()[0] else {
+            panic!("expected a scan")
+        };
+        assert_eq!(projection, &Some(vec![2]));
+    }
+}
@@ -0,0 +1,724 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::vec;
+use sql_parser::commands::{IndexKind, JoinKind, OrderByItem, SortDirection};
+use sql_parser::expr::{ColumnRef, Expr};
+use crate::aggregate::Aggregation;
+use crate::error::QueryExceptionError;
+use crate::eval;
+use crate::explain::{Instrumented, OperatorMetrics};
+use crate::join::{JoinCondition, JoinState, JoinStrategy};
+use crate::plan::{self, IndexLookup, LogicalPlan};
+use crate::row::{OutputColumn, Row, RowSchema};
+use crate::sequence::{self, Sequence};
+use crate::sort;
+use crate::table::{RowKey, Table, TableIter};
+
+/// 拉取式（Volcano）物理算子
+///
+/// 调用方先 `open`，再反复调用 `next` 直到返回 `None`，最后 `close`。
+/// 算子在 `next` 中按需向子算子拉取行，所以扫描、过滤、投影和 LIMIT 只占用常数内存，
+/// 连接只保留右侧的行；LIMIT 取够行后也不会再拉取上游的行
+pub(crate) trait PhysicalOperator {
+    /// 输出的行的列描述
+    fn schema(&self) -> Rc<RowSchema>;
+
+    /// 准备开始输出行，需要全部输入的算子在这里读取输入
+    fn open(&mut self) -> Result<(), QueryExceptionError>;
+
+    /// 下一行，没有更多行时返回 `None`
+    fn next(&mut self) -> Result<Option<Row>, QueryExceptionError>;
+
+    /// 释放算子持有的行，之后可以重新 `open`
+    fn close(&mut self);
+
+    /// EXPLAIN 中显示的一行描述
+    fn describe(&self) -> String;
+
+    /// 子算子，从左到右
+    fn children(&self) -> Vec<&dyn PhysicalOperator>;
+
+    /// 运行信息，只有为 EXPLAIN ANALYZE 包装过的算子才有
+    fn metrics(&self) -> Option<OperatorMetrics> {
+        None
+    }
+}
+
+pub(crate) type BoxedOperator<'a> = Box<dyn PhysicalOperator + 'a>;
+
+/// 把逻辑计划编译为物理算子树，投影中的 `nextval` 在执行时从 `sequences` 取值
+pub(crate) fn build<'a>(
+    tables: &'a HashMap<String, Table>,
+    sequences: &'a RefCell<HashMap<String, Sequence>>,
+    plan: &LogicalPlan
+) -> Result<BoxedOperator<'a>, QueryExceptionError> {
+    build_operator(tables, sequences, plan, false)
+}
+
+/// 同 [`build`]，但每个算子都会统计输出的行数和耗时，用于 EXPLAIN ANALYZE
+pub(crate) fn build_instrumented<'a>(
+    tables: &'a HashMap<String, Table>,
+    sequences: &'a RefCell<HashMap<String, Sequence>>,
+    plan: &LogicalPlan
+) -> Result<BoxedOperator<'a>, QueryExceptionError> {
+    build_operator(tables, sequences, plan, true)
+}
+
+fn build_operator<'a>(
+    tables: &'a HashMap<String, Table>,
+    sequences: &'a RefCell<HashMap<String, Sequence>>,
+    plan: &LogicalPlan,
+    instrument: bool
+) -> Result<BoxedOperator<'a>, QueryExceptionError> {
+    let build = |plan| build_operator(tables, sequences, plan, instrument);
+    let operator: BoxedOperator<'a> = match plan {
+        LogicalPlan::Scan { table: name, qualifier, schema, projection, index } => {
+            let table = tables
+                .get(name)
+                .ok_or_else(|| QueryExceptionError::TableNotFound(name.to_owned()))?;
+            let name = plan::display_scan(name, qualifier, schema, projection.is_some());
+            match index {
+                Some(index) => Box::new(IndexScan {
+                    table,
+                    name,
+                    index: index.clone(),
+                    schema: schema.clone(),
+                    projection: projection.clone(),
+                    keys: None
+                }),
+                None => Box::new(SeqScan {
+                    table,
+                    name,
+                    schema: schema.clone(),
+                    projection: projection.clone(),
+                    iter: None
+                })
+            }
+        }
+        LogicalPlan::Filter { input, predicate } => Box::new(Filter {
+            input: build(input)?,
+            predicate: predicate.clone()
+        }),
+        LogicalPlan::Project { input, exprs } => Box::new(Projection {
+            input: build(input)?,
+            schema: Rc::new(exprs.iter().map(|(col, _)| col.clone()).collect()),
+            exprs: exprs.iter().map(|(_, expr)| expr.clone()).collect(),
+            sequences
+        }),
+        LogicalPlan::Join { left: left_plan, right: right_plan, kind, on } => {
+            let left = build(left_plan)?;
+            let right = build(right_plan)?;
+            let (left_schema, right_schema) = (left.schema(), right.schema());
+            let condition = JoinCondition::new(on.as_ref(), &left_schema, &right_schema);
+            let strategy = condition.strategy(&output_order(tables, left_plan), &output_order(tables, right_plan));
+            Box::new(Join {
+                condition,
+                schema: Rc::new(left_schema.iter().chain(right_schema.iter()).cloned().collect()),
+                left,
+                right,
+                kind: *kind,
+                strategy,
+                state: None,
+                pending: Vec::new().into_iter()
+            })
+        }
+        LogicalPlan::Aggregate { input, aggregation } => Box::new(HashAggregate {
+            input: build(input)?,
+            aggregation: aggregation.clone(),
+            output: None
+        }),
+        LogicalPlan::Sort { input, order_by, fetch } => Box::new(Sort {
+            input: build(input)?,
+            order_by: order_by.clone(),
+            fetch: *fetch,
+            output: None
+        }),
+        LogicalPlan::Limit { input, offset, limit } => Box::new(Limit {
+            input: build(input)?,
+            offset: *offset,
+            limit: *limit,
+            skipped: 0,
+            produced: 0
+        })
+    };
+    Ok(if instrument { Box::new(Instrumented::new(operator)) } else { operator })
+}
+
+/// 计划输出的行已知按哪些表达式升序排列（NULL 在最后），用于选择归并连接
+///
+/// 有主键的表全表扫描时按主键列有序，只查找一个范围的有序索引扫描按索引列有序，排序的输出按排序键有序；
+/// 过滤、LIMIT 和投影保持输入的顺序，内连接和左连接保持左侧的顺序
+fn output_order(tables: &HashMap<String, Table>, plan: &LogicalPlan) -> Vec<Expr> {
+    let column = |col: &OutputColumn| Expr::Column(ColumnRef { table: col.table.clone(), name: col.name.to_owned() });
+    match plan {
+        LogicalPlan::Scan { table, schema, projection, index, .. } => {
+            let Some(table) = tables.get(table) else {
+                return Vec::new();
+            };
+            let key = match index {
+                None => table.primary_key_columns(),
+                Some(lookup) if lookup.ranges.len() == 1 => match table.primary_key_index() {
+                    Some((name, columns)) if name == lookup.name => Some(columns),
+                    _ => table
+                        .indexes()
+                        .iter()
+                        .find(|index| index.name == lookup.name && index.kind() == IndexKind::BTree)
+                        .map(|index| &index.columns[..])
+                },
+                Some(_) => None
+            };
+            let Some(key) = key else {
+                return Vec::new();
+            };
+            key.iter()
+                .map_while(|&idx| match projection {
+                    Some(projection) => projection.iter().position(|&col| col == idx),
+                    None => Some(idx)
+                })
+                .map(|position| column(&schema[position]))
+                .collect()
+        }
+        LogicalPlan::Filter { input, .. } | LogicalPlan::Limit { input, .. } => output_order(tables, input),
+        LogicalPlan::Project { input, exprs } => output_order(tables, input)
+            .iter()
+            .map_while(|expr| exprs.iter().find(|(_, projected)| projected == expr).map(|(col, _)| column(col)))
+            .collect(),
+        LogicalPlan::Join { left, kind: JoinKind::Inner | JoinKind::Left | JoinKind::Cross, .. } => output_order(tables, left),
+        LogicalPlan::Sort { order_by, .. } => order_by
+            .iter()
+            .map_while(|item| (item.direction == SortDirection::Asc && !item.nulls_first()).then(|| item.expr.clone()))
+            .collect(),
+        _ => Vec::new()
+    }
+}
+
+/// 运行算子并收集所有输出行
+pub(crate) fn collect(operator: &mut dyn PhysicalOperator) -> Result<Vec<Row>, QueryExceptionError> {
+    operator.open()?;
+    let mut rows = Vec::new();
+    let result = loop {
+        match operator.next() {
+            Ok(Some(row)) => rows.push(row),
+            Ok(None) => break Ok(rows),
+            Err(err) => break Err(err)
+        }
+    };
+    operator.close();
+    result
+}
+
+/// 按行 id 顺序扫描整张表
+struct SeqScan<'a> {
+    table: &'a Table,
+    /// EXPLAIN 中显示的表名和读取的列
+    name: String,
+    schema: Rc<RowSchema>,
+    projection: Option<Vec<usize>>,
+    iter: Option<TableIter<'a>>
+}
+
+impl PhysicalOperator for SeqScan<'_> {
+    fn schema(&self) -> Rc<RowSchema> {
+        self.schema.clone()
+    }
+
+    fn open(&mut self) -> Result<(), QueryExceptionError> {
+        self.iter = Some(match &self.projection {
+            Some(projection) => self.table.scan_columns(self.schema.clone(), projection.clone()),
+            None => self.table.scan(self.schema.clone())
+        });
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Option<Row>, QueryExceptionError> {
+        Ok(self.iter.as_mut().and_then(Iterator::next))
+    }
+
+    fn close(&mut self) {
+        self.iter = None;
+    }
+
+    fn describe(&self) -> String {
+        format!("Seq Scan: {}", self.name)
+    }
+
+    fn children(&self) -> Vec<&dyn PhysicalOperator> {
+        Vec::new()
+    }
+}
+
+/// 通过索引找到满足条件的行，再按行的键读取
+struct IndexScan<'a> {
+    table: &'a Table,
+    /// EXPLAIN 中显示的表名和读取的列
+    name: String,
+    index: IndexLookup,
+    schema: Rc<RowSchema>,
+    projection: Option<Vec<usize>>,
+    keys: Option<vec::IntoIter<RowKey>>
+}
+
+impl PhysicalOperator for IndexScan<'_> {
+    fn schema(&self) -> Rc<RowSchema> {
+        self.schema.clone()
+    }
+
+    fn open(&mut self) -> Result<(), QueryExceptionError> {
+        self.keys = Some(self.table.index_lookup(&self.index.name, &self.index.ranges)?.into_iter());
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Option<Row>, QueryExceptionError> {
+        let Some(key) = self.keys.as_mut().and_then(Iterator::next) else {
+            return Ok(None);
+        };
+        Ok(self.table.row(&key, self.schema.clone(), self.projection.as_deref()))
+    }
+
+    fn close(&mut self) {
+        self.keys = None;
+    }
+
+    fn describe(&self) -> String {
+        format!("Index Scan: {} using {}: {}", self.name, self.index.name, self.index.predicate)
+    }
+
+    fn children(&self) -> Vec<&dyn PhysicalOperator> {
+        Vec::new()
+    }
+}
+
+/// 只输出谓词为 true 的行
+struct Filter<'a> {
+    input: BoxedOperator<'a>,
+    predicate: Expr
+}
+
+impl PhysicalOperator for Filter<'_> {
+    fn schema(&self) -> Rc<RowSchema> {
+        self.input.schema()
+    }
+
+    fn open(&mut self) -> Result<(), QueryExceptionError> {
+        self.input.open()
+    }
+
+    fn next(&mut self) -> Result<Option<Row>, QueryExceptionError> {
+        while let Some(row) = self.input.next()? {
+            if eval::matches(&self.predicate, &row)? {
+                return Ok(Some(row));
+            }
+        }
+        Ok(None)
+    }
+
+    fn close(&mut self) {
+        self.input.close();
+    }
+
+    fn describe(&self) -> String {
+        format!("Filter: {}", self.predicate)
+    }
+
+    fn children(&self) -> Vec<&dyn PhysicalOperator> {
+        vec![self.input.as_ref()]
+    }
+}
+
+/// 对每行计算输出列，每行都为 `nextval` 取一个新值
+struct Projection<'a> {
+    input: BoxedOperator<'a>,
+    schema: Rc<RowSchema>,
+    exprs: Vec<Expr>,
+    sequences: &'a RefCell<HashMap<String, Sequence>>
+}
+
+impl PhysicalOperator for Projection<'_> {
+    fn schema(&self) -> Rc<RowSchema> {
+        self.schema.clone()
+    }
+
+    fn open(&mut self) -> Result<(), QueryExceptionError> {
+        self.input.open()
+    }
+
+    fn next(&mut self) -> Result<Option<Row>, QueryExceptionError> {
+        let Some(row) = self.input.next()? else {
+            return Ok(None);
+        };
+        let values = self.exprs
+            .iter()
+            .map(|expr| {
+                if !expr.contains_nextval() {
+                    return eval::evaluate(expr, &row);
+                }
+                let expr = sequence::expand(expr, &mut self.sequences.borrow_mut())?;
+                eval::evaluate(&expr, &row)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Some(Row::new(self.schema.clone(), values)))
+    }
+
+    fn close(&mut self) {
+        self.input.close();
+    }
+
+    fn describe(&self) -> String {
+        format!("Projection: {}", plan::display_projection(self.schema.iter().zip(&self.exprs)))
+    }
+
+    fn children(&self) -> Vec<&dyn PhysicalOperator> {
+        vec![self.input.as_ref()]
+    }
+}
+
+/// 连接两个子算子的输出，`open` 时读取右侧所有行，左侧在 `next` 中逐行拉取
+struct Join<'a> {
+    left: BoxedOperator<'a>,
+    right: BoxedOperator<'a>,
+    kind: JoinKind,
+    condition: JoinCondition,
+    schema: Rc<RowSchema>,
+    /// 建立算子时按两侧输入的顺序选择的连接算法
+    strategy: JoinStrategy,
+    /// 读取的右侧，左侧读完后为 `None`
+    state: Option<JoinState>,
+    /// 左侧当前行还没有输出的连接结果
+    pending: vec::IntoIter<Row>
+}
+
+impl PhysicalOperator for Join<'_> {
+    fn schema(&self) -> Rc<RowSchema> {
+        self.schema.clone()
+    }
+
+    fn open(&mut self) -> Result<(), QueryExceptionError> {
+        let right = collect(self.right.as_mut())?;
+        self.state = Some(JoinState::new(right, self.kind, &self.condition, self.strategy)?);
+        self.pending = Vec::new().into_iter();
+        self.left.open()
+    }
+
+    fn next(&mut self) -> Result<Option<Row>, QueryExceptionError> {
+        loop {
+            if let Some(row) = self.pending.next() {
+                return Ok(Some(row));
+            }
+            let Some(state) = self.state.as_mut() else {
+                return Ok(None);
+            };
+            self.pending = match self.left.next()? {
+                Some(row) => state.probe(&row, &self.condition, &self.schema)?,
+                None => self.state.take().expect("state was checked above").finish(&self.schema)
+            }
+            .into_iter();
+        }
+    }
+
+    fn close(&mut self) {
+        self.left.close();
+        self.state = None;
+        self.pending = Vec::new().into_iter();
+    }
+
+    fn describe(&self) -> String {
+        let mut text = format!("{} ({:?})", self.strategy, self.kind);
+        if !self.condition.left_keys.is_empty() || self.condition.residual.is_some() {
+            text.push_str(&format!(": {}", self.condition));
+        }
+        text
+    }
+
+    fn children(&self) -> Vec<&dyn PhysicalOperator> {
+        vec![self.left.as_ref(), self.right.as_ref()]
+    }
+}
+
+/// 哈希聚合，`open` 时读取所有输入行
+struct HashAggregate<'a> {
+    input: BoxedOperator<'a>,
+    aggregation: Aggregation,
+    output: Option<vec::IntoIter<Row>>
+}
+
+impl PhysicalOperator for HashAggregate<'_> {
+    fn schema(&self) -> Rc<RowSchema> {
+        self.aggregation.schema()
+    }
+
+    fn open(&mut self) -> Result<(), QueryExceptionError> {
+        self.input.open()?;
+        let input = &mut self.input;
+        let rows = self.aggregation.execute(std::iter::from_fn(|| input.next().transpose()));
+        self.input.close();
+        self.output = Some(rows?.into_iter());
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Option<Row>, QueryExceptionError> {
+        Ok(self.output.as_mut().and_then(Iterator::next))
+    }
+
+    fn close(&mut self) {
+        self.output = None;
+    }
+
+    fn describe(&self) -> String {
+        format!("Hash Aggregate: {}", self.aggregation)
+    }
+
+    fn children(&self) -> Vec<&dyn PhysicalOperator> {
+        vec![self.input.as_ref()]
+    }
+}
+
+/// 排序，`open` 时读取所有输入行
+///
+/// 有 `fetch` 时只保留前 `fetch` 行，缓冲区达到两倍大小时排序并截断
+struct Sort<'a> {
+    input: BoxedOperator<'a>,
+    order_by: Vec<OrderByItem>,
+    fetch: Option<usize>,
+    output: Option<vec::IntoIter<Row>>
+}
+
+impl Sort<'_> {
+    fn read_sorted(&mut self) -> Result<Vec<Row>, QueryExceptionError> {
+        let mut rows = Vec::new();
+        while let Some(row) = self.input.next()? {
+            rows.push(row);
+            if let Some(fetch) = self.fetch {
+                if rows.len() >= fetch.max(1) * 2 {
+                    rows = sort::sort_rows(rows, &self.order_by)?;
+                    rows.truncate(fetch);
+                }
+            }
+        }
+        let mut rows = sort::sort_rows(rows, &self.order_by)?;
+        if let Some(fetch) = self.fetch {
+            rows.truncate(fetch);
+        }
+        Ok(rows)
+    }
+}
+
+impl PhysicalOperator for Sort<'_> {
+    fn schema(&self) -> Rc<RowSchema> {
+        self.input.schema()
+    }
+
+    fn open(&mut self) -> Result<(), QueryExceptionError> {
+        self.input.open()?;
+        let rows = self.read_sorted();
+        self.input.close();
+        self.output = Some(rows?.into_iter());
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Option<Row>, QueryExceptionError> {
+        Ok(self.output.as_mut().and_then(Iterator::next))
+    }
+
+    fn close(&mut self) {
+        self.output = None;
+    }
+
+    fn describe(&self) -> String {
+        format!("Sort: {}", plan::display_sort(&self.order_by, self.fetch))
+    }
+
+    fn children(&self) -> Vec<&dyn PhysicalOperator> {
+        vec![self.input.as_ref()]
+    }
+}
+
+/// 跳过前 `offset` 行并最多输出 `limit` 行，取够后不再拉取上游的行
+struct Limit<'a> {
+    input: BoxedOperator<'a>,
+    offset: usize,
+    limit: Option<usize>,
+    skipped: usize,
+    produced: usize
+}
+
+impl PhysicalOperator for Limit<'_> {
+    fn schema(&self) -> Rc<RowSchema> {
+        self.input.schema()
+    }
+
+    fn open(&mut self) -> Result<(), QueryExceptionError> {
+        self.skipped = 0;
+        self.produced = 0;
+        self.input.open()
+    }
+
+    fn next(&mut self) -> Result<Option<Row>, QueryExceptionError> {
+        if Some(self.produced) == self.limit {
+            return Ok(None);
+        }
+        while self.skipped < self.offset {
+            if self.input.next()?.is_none() {
+                return Ok(None);
+            }
+            self.skipped += 1;
+        }
+        let row = self.input.next()?;
+        if row.is_some() {
+            self.produced += 1;
+        }
+        Ok(row)
+    }
+
+    fn close(&mut self) {
+        self.input.close();
+    }
+
+    fn describe(&self) -> String {
+        format!("Limit: {}", plan::display_limit(self.offset, self.limit))
+    }
+
+    fn children(&self) -> Vec<&dyn PhysicalOperator> {
+        vec![self.input.as_ref()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use sql_parser::expr::{BinaryOp, Literal};
+    use crate::value::Value;
+
+    /// 输出 `0..n`，并记录被拉取的次数
+    struct Counter<'c> {
+        schema: Rc<RowSchema>,
+        n: i64,
+        next: i64,
+        pulls: &'c Cell<usize>
+    }
+
+    impl<'c> Counter<'c> {
+        fn new(n: i64, pulls: &'c Cell<usize>) -> Self {
+            Self { schema: Rc::new(vec![OutputColumn::new("n")]), n, next: 0, pulls }
+        }
+    }
+
+    impl PhysicalOperator for Counter<'_> {
+        fn schema(&self) -> Rc<RowSchema> {
+            self.schema.clone()
+        }
+
+        fn open(&mut self) -> Result<(), QueryExceptionError> {
+            self.next = 0;
+            Ok(())
+        }
+
+        fn next(&mut self) -> Result<Option<Row>, QueryExceptionError> {
+            self.pulls.set(self.pulls.get() + 1);
+            if self.next == self.n {
+                return Ok(None);
+            }
+            self.next += 1;
+            Ok(Some(Row::new(self.schema.clone(), vec![Value::Int(self.next - 1)])))
+        }
+
+        fn close(&mut self) {}
+
+        fn describe(&self) -> String {
+            format!("Counter: {}", self.n)
+        }
+
+        fn children(&self) -> Vec<&dyn PhysicalOperator> {
+            Vec::new()
+        }
+    }
+
+    fn values(rows: Vec<Row>) -> Vec<Value> {
+        rows.into_iter().map(|row| row.values()[0].clone()).collect()
+    }
+
+    #[test]
+    fn test_limit_stops_pulling() {
+        let pulls = Cell::new(0);
+        let even = Expr::Binary {
+            left: Box::new(Expr::Binary {
+                left: Box::new(Expr::Column(ColumnRef::from("n"))),
+                op: BinaryOp::Modulo,
+                right: Box::new(Expr::Literal(Literal::Int(2)))
+            }),
+            op: BinaryOp::Eq,
+            right: Box::new(Expr::Literal(Literal::Int(0)))
+        };
+        let mut limit = Limit {
+            input: Box::new(Filter { input: Box::new(Counter::new(1_000_000, &pulls)), predicate: even }),
+            offset: 1,
+            limit: Some(2),
+            skipped: 0,
+            produced: 0
+        };
+
+        assert_eq!(values(collect(&mut limit).unwrap()), vec![Value::Int(2), Value::Int(4)]);
+        // 0..=4 各拉取一次，之后不再拉取
+        assert_eq!(pulls.get(), 5);
+
+        // close 之后可以重新执行
+        pulls.set(0);
+        assert_eq!(values(collect(&mut limit).unwrap()).len(), 2);
+        assert_eq!(pulls.get(), 5);
+    }
+
+    #[test]
+    fn test_limit_over_join_stops_pulling() {
+        let n = Expr::Column(ColumnRef::from("n"));
+        let equi = JoinCondition { left_keys: vec![n.clone()], right_keys: vec![n], residual: None };
+        let cross = JoinCondition { left_keys: Vec::new(), right_keys: Vec::new(), residual: None };
+        let cases = [
+            (equi.clone(), JoinStrategy::Hash, 2),
+            (equi, JoinStrategy::SortMerge, 2),
+            (cross, JoinStrategy::NestedLoop, 1)
+        ];
+
+        for (condition, strategy, left_pulls) in cases {
+            let (left, right) = (Cell::new(0), Cell::new(0));
+            let schema = Rc::new(vec![OutputColumn::new("n"), OutputColumn::new("n")]);
+            let join = Join {
+                left: Box::new(Counter::new(1_000_000, &left)),
+                right: Box::new(Counter::new(10, &right)),
+                kind: JoinKind::Inner,
+                condition,
+                schema,
+                strategy,
+                state: None,
+                pending: Vec::new().into_iter()
+            };
+            let mut limit = Limit { input: Box::new(join), offset: 0, limit: Some(2), skipped: 0, produced: 0 };
+
+            assert_eq!(collect(&mut limit).unwrap().len(), 2, "{strategy}");
+            // 右侧全部读入，左侧只拉取产生前两行所需的行
+            assert_eq!(right.get(), 11, "{strategy}");
+            assert_eq!(left.get(), left_pulls, "{strategy}");
+        }
+    }
+
+    #[test]
+    fn test_sort_reads_all_input() {
+        let pulls = Cell::new(0);
+        let mut sort = Sort {
+            input: Box::new(Counter::new(3, &pulls)),
+            order_by: vec![OrderByItem {
+                expr: Expr::Column(ColumnRef::from("n")),
+                direction: SortDirection::Desc,
+                nulls_first: None
+            }],
+            fetch: None,
+            output: None
+        };
+
+        sort.open().unwrap();
+        assert_eq!(pulls.get(), 4);
+        assert_eq!(sort.next().unwrap().map(|row| row.values()[0].clone()), Some(Value::Int(2)));
+        sort.close();
+        assert!(sort.next().unwrap().is_none());
+    }
+}
@@ -0,0 +1,221 @@
+use std::fmt;
+use std::rc::Rc;
+use sql_parser::commands::{JoinKind, OrderByItem, SortDirection};
+use sql_parser::expr::Expr;
+use crate::aggregate::Aggregation;
+use crate::error::QueryExceptionError;
+use crate::index::IndexRange;
+use crate::row::{OutputColumn, RowSchema};
+
+/// 逻辑查询计划
+///
+/// 由 [`Binder`](crate::binder::Binder) 从 AST 构建，表达式中的列引用都已带上所属表名
+#[derive(Debug, Clone)]
+pub(crate) enum LogicalPlan {
+    /// 扫描一张表，`qualifier` 为查询中引用这张表时用的名字
+    ///
+    /// `projection` 为需要读取的列在表中的下标，`None` 表示所有列；`schema` 只描述读取的列。
+    /// `index` 不为 `None` 时只读取索引中满足条件的行，否则为全表扫描
+    Scan {
+        table: String,
+        qualifier: String,
+        schema: Rc<RowSchema>,
+        projection: Option<Vec<usize>>,
+        index: Option<IndexLookup>
+    },
+    Filter {
+        input: Box<LogicalPlan>,
+        predicate: Expr
+    },
+    /// 计算输出列，每一项为输出列的描述和对应的表达式
+    Project {
+        input: Box<LogicalPlan>,
+        exprs: Vec<(OutputColumn, Expr)>
+    },
+    Join {
+        left: Box<LogicalPlan>,
+        right: Box<LogicalPlan>,
+        kind: JoinKind,
+        on: Option<Expr>
+    },
+    Aggregate {
+        input: Box<LogicalPlan>,
+        aggregation: Aggregation
+    },
+    /// `fetch` 为之后只需要的前几行，由 LIMIT 下推得到
+    Sort {
+        input: Box<LogicalPlan>,
+        order_by: Vec<OrderByItem>,
+        fetch: Option<usize>
+    },
+    Limit {
+        input: Box<LogicalPlan>,
+        offset: usize,
+        limit: Option<usize>
+    }
+}
+
+/// 通过索引读取的行，由索引选择规则生成
+#[derive(Debug, Clone)]
+pub(crate) struct IndexLookup {
+    pub name: String,
+    /// 满足任意一个范围的行，`IN` 对每个值各有一个范围
+    pub ranges: Vec<IndexRange>,
+    /// 由索引保证成立的条件，用于显示和估计行数
+    pub predicate: Expr
+}
+
+impl LogicalPlan {
+    /// 这个节点输出的行的列描述
+    pub fn schema(&self) -> Rc<RowSchema> {
+        match self {
+            LogicalPlan::Scan { schema, .. } => schema.clone(),
+            LogicalPlan::Filter { input, .. }
+            | LogicalPlan::Sort { input, .. }
+            | LogicalPlan::Limit { input, .. } => input.schema(),
+            LogicalPlan::Project { exprs, .. } => Rc::new(exprs.iter().map(|(col, _)| col.clone()).collect()),
+            LogicalPlan::Join { left, right, .. } => {
+                Rc::new(left.schema().iter().chain(right.schema().iter()).cloned().collect())
+            }
+            LogicalPlan::Aggregate { aggregation, .. } => aggregation.schema()
+        }
+    }
+
+    /// 直接输入的子节点，从左到右
+    pub fn children(&self) -> Vec<&LogicalPlan> {
+        match self {
+            LogicalPlan::Scan { .. } => Vec::new(),
+            LogicalPlan::Filter { input, .. }
+            | LogicalPlan::Project { input, .. }
+            | LogicalPlan::Aggregate { input, .. }
+            | LogicalPlan::Sort { input, .. }
+            | LogicalPlan::Limit { input, .. } => vec![input],
+            LogicalPlan::Join { left, right, .. } => vec![left, right]
+        }
+    }
+
+    /// 对每个子节点应用 `f`，当前节点的其它部分保持不变
+    pub fn map_children(
+        self,
+        mut f: impl FnMut(LogicalPlan) -> Result<LogicalPlan, QueryExceptionError>
+    ) -> Result<LogicalPlan, QueryExceptionError> {
+        Ok(match self {
+            LogicalPlan::Scan { .. } => self,
+            LogicalPlan::Filter { input, predicate } => LogicalPlan::Filter { input: Box::new(f(*input)?), predicate },
+            LogicalPlan::Project { input, exprs } => LogicalPlan::Project { input: Box::new(f(*input)?), exprs },
+            LogicalPlan::Join { left, right, kind, on } => LogicalPlan::Join {
+                left: Box::new(f(*left)?),
+                right: Box::new(f(*right)?),
+                kind,
+                on
+            },
+            LogicalPlan::Aggregate { input, aggregation } => {
+                LogicalPlan::Aggregate { input: Box::new(f(*input)?), aggregation }
+            }
+            LogicalPlan::Sort { input, order_by, fetch } => {
+                LogicalPlan::Sort { input: Box::new(f(*input)?), order_by, fetch }
+            }
+            LogicalPlan::Limit { input, offset, limit } => {
+                LogicalPlan::Limit { input: Box::new(f(*input)?), offset, limit }
+            }
+        })
+    }
+
+    fn fmt_tree(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        writeln!(f, "{:indent$}{}", "", Node(self), indent = depth * 2)?;
+        for child in self.children() {
+            child.fmt_tree(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// 每个节点一行，子节点依次缩进
+impl fmt::Display for LogicalPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_tree(f, 0)
+    }
+}
+
+/// 单个节点（不含子节点）的描述
+struct Node<'p>(&'p LogicalPlan);
+
+impl fmt::Display for Node<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            LogicalPlan::Scan { table, qualifier, schema, projection, index } => {
+                write!(f, "Scan: {}", display_scan(table, qualifier, schema, projection.is_some()))?;
+                match index {
+                    Some(index) => write!(f, " using {}: {}", index.name, index.predicate),
+                    None => Ok(())
+                }
+            }
+            LogicalPlan::Filter { predicate, .. } => write!(f, "Filter: {predicate}"),
+            LogicalPlan::Project { exprs, .. } => {
+                write!(f, "Project: {}", display_projection(exprs.iter().map(|(col, expr)| (col, expr))))
+            }
+            LogicalPlan::Join { kind, on, .. } => {
+                write!(f, "{kind:?} Join")?;
+                match on {
+                    Some(on) => write!(f, ": {on}"),
+                    None => Ok(())
+                }
+            }
+            LogicalPlan::Aggregate { aggregation, .. } => write!(f, "Aggregate: {aggregation}"),
+            LogicalPlan::Sort { order_by, fetch, .. } => write!(f, "Sort: {}", display_sort(order_by, *fetch)),
+            LogicalPlan::Limit { offset, limit, .. } => write!(f, "Limit: {}", display_limit(*offset, *limit))
+        }
+    }
+}
+
+/// `users AS u (id, age)`，读取所有列时不列出列名
+pub(crate) fn display_scan(table: &str, qualifier: &str, schema: &RowSchema, projected: bool) -> String {
+    let mut text = table.to_owned();
+    if table != qualifier {
+        text.push_str(&format!(" AS {qualifier}"));
+    }
+    if projected {
+        let columns: Vec<&str> = schema.iter().map(|col| col.name.as_str()).collect();
+        text.push_str(&format!(" ({})", columns.join(", ")));
+    }
+    text
+}
+
+/// 输出列名和表达式不同时写作 `expr AS name`
+pub(crate) fn display_projection<'e>(exprs: impl Iterator<Item = (&'e OutputColumn, &'e Expr)>) -> String {
+    exprs
+        .map(|(col, expr)| match expr {
+            Expr::Column(column) if column.name == col.name => expr.to_string(),
+            _ => format!("{expr} AS {}", col.name)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `x ASC NULLS LAST, y DESC NULLS FIRST[, fetch=n]`
+pub(crate) fn display_sort(order_by: &[OrderByItem], fetch: Option<usize>) -> String {
+    let mut text = order_by
+        .iter()
+        .map(|item| {
+            let direction = match item.direction {
+                SortDirection::Asc => "ASC",
+                SortDirection::Desc => "DESC"
+            };
+            let nulls = if item.nulls_first() { "FIRST" } else { "LAST" };
+            format!("{} {direction} NULLS {nulls}", item.expr)
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    if let Some(fetch) = fetch {
+        text.push_str(&format!(", fetch={fetch}"));
+    }
+    text
+}
+
+/// `n OFFSET m`，没有上限时为 `ALL OFFSET m`
+pub(crate) fn display_limit(offset: usize, limit: Option<usize>) -> String {
+    match limit {
+        Some(limit) => format!("{limit} OFFSET {offset}"),
+        None => format!("ALL OFFSET {offset}")
+    }
+}
@@ -1,34 +1,79 @@
-use std::collections::HashMap;
 use std::rc::Rc;
+use sql_parser::expr::ColumnRef;
 use crate::error::QueryExceptionError;
-use crate::table::ColumnInfo;
+use crate::value::Value;
+
+/// 查询结果中的一列
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputColumn {
+    /// 列所属的表名或表的别名，计算得到的列为 `None`
+    pub table: Option<String>,
+    pub name: String
+}
+
+impl OutputColumn {
+    /// 不属于任何表的列
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { table: None, name: name.into() }
+    }
+
+    /// 列引用是否指向这一列，不带表名的引用匹配任意表中的同名列
+    fn matches(&self, column: &ColumnRef) -> bool {
+        self.name == column.name && (column.table.is_none() || self.table == column.table)
+    }
+}
+
+// 一行中各列的描述，同一批行共享
+pub type RowSchema = Vec<OutputColumn>;
+
+/// 找到列引用对应的下标，匹配多列时报错
+pub(crate) fn resolve(schema: &RowSchema, column: &ColumnRef) -> Result<usize, QueryExceptionError> {
+    let mut found = None;
+    for (idx, col) in schema.iter().enumerate() {
+        if col.matches(column) {
+            if found.is_some() {
+                return Err(QueryExceptionError::AmbiguousColumn(column.to_string()));
+            }
+            found = Some(idx);
+        }
+    }
+    found.ok_or_else(|| QueryExceptionError::ColumnDoesNotExists(column.to_string()))
+}
 
 /// 查询响应中的一行
+///
+/// 值按 `columns` 的顺序存放
 #[derive(Debug, Clone)]
-pub struct Row<'a> {
-    id: usize,
-    columns: Rc<ColumnInfo>,
-    data: &'a HashMap<String, String>
+pub struct Row {
+    columns: Rc<RowSchema>,
+    values: Vec<Value>
 }
 
-impl<'a> Row<'a> {
-    pub fn new(columns: Rc<ColumnInfo>, id: usize, data: &'a HashMap<String, String>) -> Self {
-        Self { id, columns, data }
+impl Row {
+    pub fn new(columns: Rc<RowSchema>, values: Vec<Value>) -> Self {
+        debug_assert_eq!(columns.len(), values.len());
+        Self { columns, values }
     }
 
-    pub fn columns(&self) -> &ColumnInfo {
+    pub fn columns(&self) -> &RowSchema {
         self.columns.as_ref()
     }
 
-    pub fn get(&self, column: &String) -> String {
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+
+    pub fn get(&self, column: &str) -> Value {
         self.try_get(column).unwrap()
     }
 
     /// Get a single value from the row
-    pub fn try_get(&self, column: &String) -> Result<String, QueryExceptionError> {
-        self.data.get(column).map_or_else(
-            || Err(QueryExceptionError::ColumnDoesNotExists(column.to_owned())),
-            |val| Ok(val.to_string())
-        )
+    pub fn try_get(&self, column: &str) -> Result<Value, QueryExceptionError> {
+        self.lookup(&ColumnRef::from(column))
+    }
+
+    /// 按（可能带表名的）列引用取值
+    pub fn lookup(&self, column: &ColumnRef) -> Result<Value, QueryExceptionError> {
+        resolve(&self.columns, column).map(|idx| self.values[idx].clone())
     }
 }
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use sql_parser::expr::{Expr, Literal};
+use crate::error::QueryExceptionError;
+use crate::table::Table;
+
+/// 单调递增的整数序列，取出的值不会再次取出，也不会因为删除行而复用
+///
+/// `CREATE SEQUENCE` 建立的序列由 `nextval` 取值，`AUTOINCREMENT` 列各自有一个序列
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Sequence {
+    start: i64,
+    /// 步长，总是大于 0
+    increment: i64,
+    /// 最近一次取出或越过的值，从未取值时为 `None`
+    last: Option<i64>
+}
+
+impl Sequence {
+    pub fn new(start: i64, increment: i64) -> Self {
+        debug_assert!(increment > 0);
+        Self { start, increment, last: None }
+    }
+
+    /// 取出下一个值，超出 `i64` 的范围时返回 `None`
+    pub fn next_value(&mut self) -> Option<i64> {
+        let value = match self.last {
+            Some(last) => last.checked_add(self.increment)?,
+            None => self.start
+        };
+        self.last = Some(value);
+        Some(value)
+    }
+
+    /// 显式写入了 `value`，之后取出的值都要比它大
+    pub fn advance_past(&mut self, value: i64) {
+        if self.last.map_or(value >= self.start, |last| value > last) {
+            self.last = Some(value);
+        }
+    }
+}
+
+/// 从 `sequences` 中取值，替换表达式中的 `nextval`
+pub(crate) fn expand(expr: &Expr, sequences: &mut HashMap<String, Sequence>) -> Result<Expr, QueryExceptionError> {
+    Ok(match expr {
+        Expr::NextVal(name) => {
+            let sequence = sequences
+                .get_mut(name)
+                .ok_or_else(|| QueryExceptionError::SequenceNotFound(name.to_owned()))?;
+            let value = sequence
+                .next_value()
+                .ok_or_else(|| QueryExceptionError::SequenceExhausted(name.to_owned()))?;
+            Expr::Literal(Literal::Int(value))
+        }
+        Expr::Column(_) | Expr::Literal(_) | Expr::Aggregate { arg: None, .. } => expr.clone(),
+        Expr::Aggregate { func, arg: Some(arg) } => Expr::Aggregate {
+            func: *func,
+            arg: Some(Box::new(expand(arg, sequences)?))
+        },
+        Expr::Unary { op, expr } => Expr::Unary { op: *op, expr: Box::new(expand(expr, sequences)?) },
+        Expr::Binary { left, op, right } => Expr::Binary {
+            left: Box::new(expand(left, sequences)?),
+            op: *op,
+            right: Box::new(expand(right, sequences)?)
+        },
+        Expr::InList { expr, list, negated } => Expr::InList {
+            expr: Box::new(expand(expr, sequences)?),
+            list: list.iter().map(|item| expand(item, sequences)).collect::<Result<_, _>>()?,
+            negated: *negated
+        }
+    })
+}
+
+/// 表达式中 `nextval` 引用的序列名
+fn referenced(expr: &Expr) -> Vec<&str> {
+    match expr {
+        Expr::NextVal(name) => vec![name.as_str()],
+        Expr::Column(_) | Expr::Literal(_) | Expr::Aggregate { arg: None, .. } => Vec::new(),
+        Expr::Aggregate { arg: Some(expr), .. } | Expr::Unary { expr, .. } => referenced(expr),
+        Expr::Binary { left, right, .. } => [referenced(left), referenced(right)].concat(),
+        Expr::InList { expr, list, .. } => {
+            let mut names = referenced(expr);
+            names.extend(list.iter().flat_map(referenced));
+            names
+        }
+    }
+}
+
+/// 表的列默认值中引用的序列名
+fn default_sequences(table: &Table) -> impl Iterator<Item = &str> {
+    table.columns().iter().filter_map(|column| column.default_value()).flat_map(referenced)
+}
+
+/// 检查新建的表的列默认值引用的序列都存在
+pub(crate) fn check_defaults(table: &Table, sequences: &HashMap<String, Sequence>) -> Result<(), QueryExceptionError> {
+    match default_sequences(table).find(|name| !sequences.contains_key(*name)) {
+        Some(name) => Err(QueryExceptionError::SequenceNotFound(name.to_owned())),
+        None => Ok(())
+    }
+}
+
+/// 检查序列 `sequence` 没有被任何表的列默认值引用，可以删除
+pub(crate) fn check_drop(tables: &HashMap<String, Table>, sequence: &str) -> Result<(), QueryExceptionError> {
+    let dependent = tables.iter().find(|(_, table)| default_sequences(table).any(|name| name == sequence));
+    match dependent {
+        Some((name, _)) => Err(QueryExceptionError::SequenceInUse { sequence: sequence.to_owned(), table: name.to_owned() }),
+        None => Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_value() {
+        let mut sequence = Sequence::new(10, 5);
+        assert_eq!(sequence.next_value(), Some(10));
+        assert_eq!(sequence.next_value(), Some(15));
+
+        // 越过显式写入的值，较小的值不影响
+        sequence.advance_past(40);
+        sequence.advance_past(3);
+        assert_eq!(sequence.next_value(), Some(45));
+
+        let mut sequence = Sequence::new(i64::MAX - 1, 1);
+        assert_eq!(sequence.next_value(), Some(i64::MAX - 1));
+        assert_eq!(sequence.next_value(), Some(i64::MAX));
+        assert_eq!(sequence.next_value(), None);
+    }
+}
@@ -0,0 +1,71 @@
+use std::cmp::Ordering;
+use sql_parser::commands::{OrderByItem, SortDirection};
+use crate::error::QueryExceptionError;
+use crate::eval;
+use crate::row::Row;
+use crate::value::Value;
+
+/// 按 `ORDER BY` 对行排序
+///
+/// 排序是稳定的，所有键都相等的行保持原有顺序
+pub(crate) fn sort_rows(
+    rows: Vec<Row>,
+    order_by: &[OrderByItem]
+) -> Result<Vec<Row>, QueryExceptionError> {
+    if order_by.is_empty() {
+        return Ok(rows);
+    }
+
+    // 每行的排序键只计算一次
+    let mut keyed = rows
+        .into_iter()
+        .map(|row| {
+            let keys = order_by
+                .iter()
+                .map(|item| eval::evaluate(&item.expr, &row))
+                .collect::<Result<Vec<Value>, QueryExceptionError>>()?;
+            Ok((keys, row))
+        })
+        .collect::<Result<Vec<_>, QueryExceptionError>>()?;
+
+    // sort_by 的比较函数不能返回错误，遇到无法比较的值时先记下来
+    let mut error = None;
+    keyed.sort_by(|(left, _), (right, _)| {
+        compare_keys(left, right, order_by).unwrap_or_else(|err| {
+            error.get_or_insert(err);
+            Ordering::Equal
+        })
+    });
+
+    match error {
+        Some(err) => Err(err),
+        None => Ok(keyed.into_iter().map(|(_, row)| row).collect())
+    }
+}
+
+/// 按 `ORDER BY` 的方向和 NULL 位置比较两组排序键
+pub(crate) fn compare_keys(
+    left: &[Value],
+    right: &[Value],
+    order_by: &[OrderByItem]
+) -> Result<Ordering, QueryExceptionError> {
+    for ((l, r), item) in left.iter().zip(right).zip(order_by) {
+        let ordering = match (l.is_null(), r.is_null()) {
+            (true, true) => Ordering::Equal,
+            // NULL 的位置不受排序方向影响
+            (true, false) => return Ok(if item.nulls_first() { Ordering::Less } else { Ordering::Greater }),
+            (false, true) => return Ok(if item.nulls_first() { Ordering::Greater } else { Ordering::Less }),
+            (false, false) => {
+                let ordering = l.compare(r)?.expect("neither value is NULL");
+                match item.direction {
+                    SortDirection::Asc => ordering,
+                    SortDirection::Desc => ordering.reverse()
+                }
+            }
+        };
+        if ordering != Ordering::Equal {
+            return Ok(ordering);
+        }
+    }
+    Ok(Ordering::Equal)
+}
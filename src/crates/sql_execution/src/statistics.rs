@@ -0,0 +1,172 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use crate::table::StoreRow;
+use crate::value::Value;
+
+/// 直方图的桶数
+const HISTOGRAM_BUCKETS: usize = 10;
+
+/// 一张表的统计信息，由 `ANALYZE` 收集，之后对表的修改不会自动更新
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct TableStatistics {
+    pub row_count: usize,
+    /// 与表中的列一一对应
+    pub columns: Vec<ColumnStatistics>
+}
+
+/// 单列的统计信息，NULL 不计入 `distinct_count`、最值和直方图
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ColumnStatistics {
+    pub distinct_count: usize,
+    pub null_fraction: f64,
+    pub min: Option<Value>,
+    pub max: Option<Value>,
+    /// 等深直方图的边界，从小到大；相邻两个边界之间的值的个数大致相同
+    pub histogram: Vec<Value>
+}
+
+impl TableStatistics {
+    pub fn collect<'a>(rows: impl ExactSizeIterator<Item = &'a StoreRow>, column_count: usize) -> Self {
+        let row_count = rows.len();
+        let mut values: Vec<Vec<&Value>> = vec![Vec::with_capacity(row_count); column_count];
+        for row in rows {
+            for (column, value) in values.iter_mut().zip(row) {
+                column.push(value);
+            }
+        }
+        Self {
+            row_count,
+            columns: values.into_iter().map(|column| ColumnStatistics::collect(column, row_count)).collect()
+        }
+    }
+}
+
+impl ColumnStatistics {
+    fn collect(values: Vec<&Value>, row_count: usize) -> Self {
+        let mut values: Vec<&Value> = values.into_iter().filter(|value| !value.is_null()).collect();
+        let null_count = row_count - values.len();
+        // 同一列的值类型相同，不会出现无法比较的情况
+        values.sort_by(|l, r| l.compare(r).ok().flatten().unwrap_or(Ordering::Equal));
+
+        let distinct_count = values.iter().collect::<HashSet<_>>().len();
+        let histogram = match values.len() {
+            0 => Vec::new(),
+            len => {
+                let buckets = HISTOGRAM_BUCKETS.min(len);
+                (0..=buckets).map(|i| values[(len - 1) * i / buckets].clone()).collect()
+            }
+        };
+        Self {
+            distinct_count,
+            null_fraction: if row_count == 0 { 0.0 } else { null_count as f64 / row_count as f64 },
+            min: values.first().map(|value| (*value).clone()),
+            max: values.last().map(|value| (*value).clone()),
+            histogram
+        }
+    }
+
+    /// 等于 `value` 的行所占的比例，假设各个不同值出现的次数相同
+    pub fn eq_selectivity(&self, value: &Value) -> f64 {
+        if self.distinct_count == 0 || value.is_null() {
+            return 0.0;
+        }
+        let out_of_range = |bound: &Option<Value>, ordering| {
+            bound.as_ref().and_then(|bound| value.compare(bound).ok().flatten()) == Some(ordering)
+        };
+        if out_of_range(&self.min, Ordering::Less) || out_of_range(&self.max, Ordering::Greater) {
+            return 0.0;
+        }
+        (1.0 - self.null_fraction) / self.distinct_count as f64
+    }
+
+    /// 小于 `value` 的行所占的比例，`value` 为 NULL 时为 0
+    pub fn lt_selectivity(&self, value: &Value) -> f64 {
+        self.fraction_below(value).unwrap_or(0.0) * (1.0 - self.null_fraction)
+    }
+
+    /// 大于 `value` 的行所占的比例，`value` 为 NULL 时为 0
+    pub fn gt_selectivity(&self, value: &Value) -> f64 {
+        self.fraction_below(value).map_or(0.0, |fraction| 1.0 - fraction) * (1.0 - self.null_fraction)
+    }
+
+    /// 非 NULL 值中小于 `value` 的比例，由直方图估计：落在某个桶中时，数值按在桶内的位置插值，其它类型取桶的一半
+    fn fraction_below(&self, value: &Value) -> Option<f64> {
+        if value.is_null() {
+            return None;
+        }
+        let compare = |bound: &Value| value.compare(bound).ok().flatten();
+        let (Some(first), Some(last)) = (self.histogram.first(), self.histogram.last()) else {
+            return Some(0.0);
+        };
+        if compare(first) != Some(Ordering::Greater) {
+            return Some(0.0);
+        }
+        if compare(last) == Some(Ordering::Greater) {
+            return Some(1.0);
+        }
+
+        let buckets = (self.histogram.len() - 1) as f64;
+        let bucket = self.histogram[1..]
+            .iter()
+            .position(|bound| compare(bound) != Some(Ordering::Greater))
+            .unwrap_or(self.histogram.len() - 2);
+        let (low, high) = (&self.histogram[bucket], &self.histogram[bucket + 1]);
+        let within = match (as_f64(value), as_f64(low), as_f64(high)) {
+            (Some(value), Some(low), Some(high)) if high > low => (value - low) / (high - low),
+            _ => 0.5
+        };
+        Some((bucket as f64 + within) / buckets)
+    }
+}
+
+fn as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Int(i) => Some(*i as f64),
+        Value::Float(f) => Some(*f),
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statistics(values: Vec<Value>) -> ColumnStatistics {
+        let rows: Vec<StoreRow> = values.into_iter().map(|value| vec![value]).collect();
+        TableStatistics::collect(rows.iter(), 1).columns.remove(0)
+    }
+
+    #[test]
+    fn test_collect() {
+        let mut values: Vec<Value> = (1..=100).map(|i| Value::Int(i % 50)).collect();
+        values.extend([Value::Null, Value::Null]);
+        let stats = statistics(values);
+        assert_eq!(stats.distinct_count, 50);
+        assert!((stats.null_fraction - 2.0 / 102.0).abs() < 1e-9);
+        assert_eq!(stats.min, Some(Value::Int(0)));
+        assert_eq!(stats.max, Some(Value::Int(49)));
+        assert_eq!(stats.histogram.len(), HISTOGRAM_BUCKETS + 1);
+
+        let empty = statistics(vec![Value::Null]);
+        assert_eq!(empty.distinct_count, 0);
+        assert_eq!(empty.null_fraction, 1.0);
+        assert!(empty.histogram.is_empty());
+        assert_eq!(empty.eq_selectivity(&Value::Int(1)), 0.0);
+    }
+
+    #[test]
+    fn test_selectivity() {
+        let stats = statistics((0..1000).map(Value::Int).collect());
+        assert!((stats.eq_selectivity(&Value::Int(5)) - 0.001).abs() < 1e-9);
+        assert_eq!(stats.eq_selectivity(&Value::Int(5000)), 0.0);
+        assert_eq!(stats.eq_selectivity(&Value::Null), 0.0);
+        assert!((stats.lt_selectivity(&Value::Int(250)) - 0.25).abs() < 0.01);
+        assert!((stats.gt_selectivity(&Value::Int(900)) - 0.1).abs() < 0.01);
+        assert_eq!(stats.lt_selectivity(&Value::Int(-1)), 0.0);
+        assert_eq!(stats.gt_selectivity(&Value::Int(1000)), 0.0);
+
+        let skewed = statistics((0..1000).map(|i| Value::Int(if i < 900 { 0 } else { i })).collect());
+        assert!(skewed.lt_selectivity(&Value::Int(1)) > 0.8);
+    }
+}
@@ -1,63 +1,599 @@
-use std::collections::{BTreeMap,HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::rc::Rc;
 use serde::{Deserialize,Serialize};
-use sql_parser::Column;
-use crate::row::Row;
+use sql_parser::{Column, SqlTypeInfo};
+use sql_parser::commands::{Assignment, ColumnConstraint, IndexKind, TableConstraint};
+use sql_parser::expr::Expr;
+use crate::eval;
+use crate::error::QueryExceptionError;
+use crate::foreign_key::ForeignKey;
+use crate::index::{self, Index, IndexKey, IndexRange};
+use crate::row::{OutputColumn, Row, RowSchema};
+use crate::sequence::{self, Sequence};
+use crate::statistics::TableStatistics;
+use crate::value::Value;
 
-// A row stored in a table
-type StoreRow = HashMap<String, String>;
+// A row stored in a table, values in column order
+pub(crate) type StoreRow = Vec<Value>;
 
 // List of column info
 pub type ColumnInfo = Vec<Column>;
 
+/// 行在表中的键，表中的行按它排序：有主键时为主键列的值，否则为自动分配的行 id
+pub(crate) type RowKey = IndexKey;
+
+/// 要写入表中的一行，带有键时替换表中原有的行，没有键的是新插入的行
+pub(crate) type RowWrite = (Option<RowKey>, StoreRow);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct Table {
-    // row id to row
-    rows: BTreeMap<usize, StoreRow>,
+    // row key to row
+    rows: BTreeMap<RowKey, StoreRow>,
+    // 没有主键时下一个分配的行 id，只增不减，删除行后 id 也不会被复用
+    next_id: usize,
     // 表中所有列的列信息
-    columns: ColumnInfo
+    columns: ColumnInfo,
+    // 主键列的下标
+    primary_key: Option<Vec<usize>>,
+    // 主键在索引选择中作为有序唯一索引时的名字 `<表名>_pkey`，查找时直接读取 `rows` 的范围
+    primary_key_name: String,
+    // 最近一次 ANALYZE 收集的统计信息
+    statistics: Option<TableStatistics>,
+    // 表上的索引，随行的增删改同步维护
+    indexes: Vec<Index>,
+    // 表上的外键，由执行器在写入前检查
+    foreign_keys: Vec<ForeignKey>,
+    // AUTOINCREMENT 列的下标到它的序列
+    auto_increment: BTreeMap<usize, Sequence>
 }
 
 impl Table {
 
     /// Create a table with the given column definitions
-    pub fn new(columns: Vec<Column>) -> Self {
-        Self {
+    ///
+    /// 主键可以写在某一列后面，也可以作为表级约束，但只能有一个。
+    /// 每个 UNIQUE 约束建立一个名为 `<表名>_<列名>_key` 的唯一索引。
+    /// 默认值不能引用列，CHECK 约束只能引用本表的列，都不能包含聚合函数，只有默认值可以使用 `nextval`。
+    /// AUTOINCREMENT 列必须是 `int` 类型，且不能再有默认值
+    pub fn new(name: &str, columns: Vec<Column>, constraints: &[TableConstraint]) -> Result<Self, QueryExceptionError> {
+        let mut table = Self {
             rows: BTreeMap::new(),
-            columns
+            next_id: 0,
+            columns,
+            primary_key: None,
+            primary_key_name: format!("{name}_pkey"),
+            statistics: None,
+            indexes: Vec::new(),
+            foreign_keys: Vec::new(),
+            auto_increment: BTreeMap::new()
+        };
+
+        let column_keys = table
+            .columns
+            .iter()
+            .filter(|col| col.constraints.contains(&ColumnConstraint::PrimaryKey))
+            .map(|col| vec![col.name.to_owned()]);
+        let table_keys = constraints.iter().filter_map(|constraint| match constraint {
+            TableConstraint::PrimaryKey(columns) => Some(columns.clone()),
+            _ => None
+        });
+        let mut keys = column_keys.chain(table_keys);
+        if let Some(key) = keys.next() {
+            if keys.next().is_some() {
+                return Err(QueryExceptionError::MultiplePrimaryKeys);
+            }
+            table.primary_key = Some(table.resolve_targets(Some(&key))?);
+        }
+
+        let column_uniques = table
+            .columns
+            .iter()
+            .filter(|col| col.constraints.contains(&ColumnConstraint::Unique))
+            .map(|col| vec![col.name.to_owned()]);
+        let table_uniques = constraints.iter().filter_map(|constraint| match constraint {
+            TableConstraint::Unique(columns) => Some(columns.clone()),
+            _ => None
+        });
+        for columns in column_uniques.chain(table_uniques).collect::<Vec<_>>() {
+            let index_name = format!("{name}_{}_key", columns.join("_"));
+            // 相同的约束只建立一个索引
+            if table.indexes.iter().any(|index| index.name == index_name) {
+                continue;
+            }
+            let mut index = Index::new(index_name, table.resolve_targets(Some(&columns))?, true, IndexKind::BTree);
+            index.constraint = true;
+            table.indexes.push(index);
+        }
+
+        let (empty, schema) = (RowSchema::new(), table.schema());
+        for column in &table.columns {
+            let defaults = column.default_value().into_iter().map(|expr| (expr, &empty));
+            let checks = column.checks().map(|expr| (expr, &*schema));
+            for (expr, schema) in defaults.chain(checks) {
+                if expr.contains_aggregate() {
+                    return Err(QueryExceptionError::MisplacedAggregate(expr.to_string()));
+                }
+                eval::check_columns(expr, schema)?;
+            }
+            if let Some(check) = column.checks().find(|expr| expr.contains_nextval()) {
+                return Err(QueryExceptionError::MisplacedSequence(check.to_string()));
+            }
         }
+
+        for (idx, column) in table.columns.iter().enumerate().filter(|(_, col)| col.auto_increment()) {
+            if column.type_info != SqlTypeInfo::Int {
+                return Err(QueryExceptionError::InvalidAutoIncrement(format!("{}: column type must be int", column.name)));
+            }
+            if column.default_value().is_some() {
+                return Err(QueryExceptionError::InvalidAutoIncrement(format!("{}: column cannot have a default", column.name)));
+            }
+            table.auto_increment.insert(idx, Sequence::new(1, 1));
+        }
+        Ok(table)
     }
 
-    /// Insert values (a row) into the table
+    /// Build the rows to insert into the table, see [`Table::write`]
     ///
-    /// 假设值与传递给创建的列的顺序相同
-    pub fn insert(&mut self, values: Vec<String>) {
-        let id = self.rows
-            .last_key_value()
-            .map_or(0, |(max_id, _)| max_id + 1);
+    /// `columns` 为 `None` 时假设值与建表时列的顺序相同，未给出的列取默认值。
+    /// 值和默认值中的 `nextval` 从 `sequences` 取值，取出的值即使插入失败也不会再用
+    pub fn prepare_insert(
+        &mut self,
+        columns: Option<&[String]>,
+        rows: &[Vec<Expr>],
+        sequences: &mut HashMap<String, Sequence>
+    ) -> Result<Vec<RowWrite>, QueryExceptionError> {
+        let targets = self.resolve_targets(columns)?;
+        rows.iter()
+            .map(|values| Ok((None, self.build_row(&targets, values, sequences)?)))
+            .collect()
+    }
+
+    /// Build the updated rows matching the predicate, see [`Table::write`]
+    ///
+    /// 所有赋值都基于更新前的行计算，赋值中的 `nextval` 每行从 `sequences` 取一个值
+    pub fn prepare_update(
+        &self,
+        assignments: &[Assignment],
+        predicate: Option<&Expr>,
+        sequences: &mut HashMap<String, Sequence>
+    ) -> Result<Vec<RowWrite>, QueryExceptionError> {
+        let targets = assignments
+            .iter()
+            .map(|assignment| self.column_index(&assignment.column))
+            .collect::<Result<Vec<usize>, QueryExceptionError>>()?;
+
+        let mut updates = Vec::new();
+        for (key, row) in self.iter_with_keys() {
+            if let Some(predicate) = predicate {
+                if !eval::matches(predicate, &row)? {
+                    continue;
+                }
+            }
+            let mut values = Vec::with_capacity(assignments.len());
+            for (&idx, assignment) in targets.iter().zip(assignments) {
+                let value = sequence::expand(&assignment.value, sequences)?;
+                let value = eval::evaluate(&value, &row)?.coerce(&self.columns[idx].type_info)?;
+                values.push((idx, value));
+            }
+            updates.push((key, values));
+        }
 
-        let row: StoreRow = values
+        let updates = updates
             .into_iter()
-            .zip(self.columns.iter())
-            .map(|(value, col)| (col.name.to_owned(), value))
+            .map(|(key, values)| {
+                let mut row = self.rows[&key].clone();
+                for (idx, value) in values {
+                    row[idx] = value;
+                }
+                (Some(key), row)
+            })
             .collect();
+        Ok(updates)
+    }
+
+    /// Delete rows matching the predicate, returning how many were removed
+    pub fn delete(&mut self, predicate: Option<&Expr>) -> Result<usize, QueryExceptionError> {
+        let Some(predicate) = predicate else {
+            return Ok(self.truncate());
+        };
+
+        let keys = self.matching_keys(Some(predicate))?;
+        self.delete_keys(&keys);
+        Ok(keys.len())
+    }
+
+    /// 满足谓词的行的键，没有谓词时为所有行
+    pub fn matching_keys(&self, predicate: Option<&Expr>) -> Result<Vec<RowKey>, QueryExceptionError> {
+        let mut keys = Vec::new();
+        for (key, row) in self.iter_with_keys() {
+            if predicate.map_or(Ok(true), |predicate| eval::matches(predicate, &row))? {
+                keys.push(key);
+            }
+        }
+        Ok(keys)
+    }
+
+    /// 按键删除行，键必须来自这张表
+    pub fn delete_keys<'a>(&mut self, keys: impl IntoIterator<Item = &'a RowKey>) {
+        for key in keys {
+            self.remove_row(key);
+        }
+    }
+
+    /// 删除所有行，返回删除的行数
+    pub fn truncate(&mut self) -> usize {
+        let count = self.rows.len();
+        self.rows.clear();
+        self.indexes.iter_mut().for_each(Index::clear);
+        count
+    }
+
+    /// 在 `columns` 上建立索引并加入现有的行，唯一索引遇到重复的键时报错
+    pub fn create_index(
+        &mut self,
+        name: String,
+        columns: &[String],
+        unique: bool,
+        kind: IndexKind
+    ) -> Result<(), QueryExceptionError> {
+        let columns = columns
+            .iter()
+            .map(|column| self.column_index(column))
+            .collect::<Result<Vec<usize>, QueryExceptionError>>()?;
+        let mut index = Index::new(name, columns, unique, kind);
+        for (row_key, row) in &self.rows {
+            if let Some(key) = index.key(row).filter(|_| unique) {
+                if index.get(&key).next().is_some() {
+                    return Err(self.duplicate_key(&index, key));
+                }
+            }
+            index.insert(row, row_key.clone());
+        }
+        self.indexes.push(index);
+        Ok(())
+    }
+
+    /// 删除索引，没有这个索引时返回 `false`
+    pub fn drop_index(&mut self, name: &str) -> bool {
+        let len = self.indexes.len();
+        self.indexes.retain(|index| index.name != name);
+        self.indexes.len() != len
+    }
+
+    pub fn indexes(&self) -> &[Index] {
+        &self.indexes
+    }
+
+    pub fn columns(&self) -> &ColumnInfo {
+        &self.columns
+    }
+
+    pub fn foreign_keys(&self) -> &[ForeignKey] {
+        &self.foreign_keys
+    }
+
+    pub fn set_foreign_keys(&mut self, foreign_keys: Vec<ForeignKey>) {
+        self.foreign_keys = foreign_keys;
+    }
+
+    /// 主键列的下标
+    pub fn primary_key_columns(&self) -> Option<&[usize]> {
+        self.primary_key.as_deref()
+    }
+
+    /// 把主键看作有序唯一索引时的名字和列，没有主键时为 `None`
+    pub fn primary_key_index(&self) -> Option<(&str, &[usize])> {
+        self.primary_key.as_deref().map(|columns| (self.primary_key_name.as_str(), columns))
+    }
+
+    /// `columns` 列的值等于 `key` 的行，列恰好是主键或某个索引的列时直接查找，否则扫描整张表
+    pub fn find(&self, columns: &[usize], key: &IndexKey) -> Vec<RowKey> {
+        if self.primary_key.as_deref() == Some(columns) {
+            return self.rows.get_key_value(key).map(|(row_key, _)| row_key.clone()).into_iter().collect();
+        }
+        if let Some(index) = self.indexes.iter().find(|index| index.columns == columns) {
+            return index.get(key).cloned().collect();
+        }
+        self.rows
+            .iter()
+            .filter(|(_, row)| IndexKey(columns.iter().map(|&idx| row[idx].clone()).collect()) == *key)
+            .map(|(row_key, _)| row_key.clone())
+            .collect()
+    }
+
+    /// 按键读取一行中存储的值
+    pub fn values(&self, key: &RowKey) -> Option<&StoreRow> {
+        self.rows.get(key)
+    }
+
+    /// 通过索引找到满足任意一个 `ranges` 的行，每行只出现一次
+    ///
+    /// 名字为 [`Table::primary_key_index`] 时直接在按主键排序的行中查找
+    pub fn index_lookup(&self, name: &str, ranges: &[IndexRange]) -> Result<Vec<RowKey>, QueryExceptionError> {
+        let mut seen = HashSet::new();
+        if self.primary_key_index().is_some_and(|(primary_key, _)| primary_key == name) {
+            return Ok(ranges
+                .iter()
+                .flat_map(|range| index::range_entries(&self.rows, range))
+                .map(|(key, _)| key.clone())
+                .filter(|key| seen.insert(key.clone()))
+                .collect());
+        }
+        let index = self
+            .indexes
+            .iter()
+            .find(|index| index.name == name)
+            .ok_or_else(|| QueryExceptionError::IndexNotFound(name.to_owned()))?;
+        Ok(ranges
+            .iter()
+            .flat_map(|range| index.lookup(range))
+            .filter(|key| seen.insert(key.clone()))
+            .collect())
+    }
+
+    /// 读取一行，`schema` 和 `projection` 的含义同 [`Table::scan_columns`]
+    pub fn row(&self, key: &RowKey, schema: Rc<RowSchema>, projection: Option<&[usize]>) -> Option<Row> {
+        self.rows.get(key).map(|data| Row::new(schema, project(data, projection)))
+    }
+
+    /// 写入一组行，带有键的行替换表中原有的行，没有键的是新插入的行
+    ///
+    /// 先检查列约束、主键和唯一索引，都通过后才会写入，返回写入的行数。
+    /// 外键涉及其它表，由调用者在写入前检查
+    pub fn write(&mut self, rows: Vec<RowWrite>) -> Result<usize, QueryExceptionError> {
+        self.check_constraints(&rows)?;
+        let replaced: HashSet<&RowKey> = rows.iter().filter_map(|(key, _)| key.as_ref()).collect();
+        let mut keys = Vec::with_capacity(rows.len());
+        let mut seen = HashSet::new();
+        for (key, row) in &rows {
+            let key = match self.primary_key(row)? {
+                Some(key) => {
+                    if (self.rows.contains_key(&key) && !replaced.contains(&key)) || !seen.insert(key.clone()) {
+                        return Err(self.duplicate_primary_key(key));
+                    }
+                    Some(key)
+                }
+                None => key.clone()
+            };
+            keys.push(key);
+        }
+        self.check_unique(&rows, &replaced)?;
+
+        // 先删除所有被替换的行，这样同一条语句中交换主键也不会冲突
+        for key in rows.iter().filter_map(|(key, _)| key.as_ref()) {
+            self.remove_row(key);
+        }
+        // 显式写入的值越过 AUTOINCREMENT 序列，之后生成的值不会与它冲突
+        for (&idx, sequence) in self.auto_increment.iter_mut() {
+            for (_, row) in &rows {
+                if let Value::Int(value) = row[idx] {
+                    sequence.advance_past(value);
+                }
+            }
+        }
+        let count = rows.len();
+        for (key, (_, row)) in keys.into_iter().zip(rows) {
+            let key = key.unwrap_or_else(|| self.allocate_key());
+            for index in self.indexes.iter_mut() {
+                index.insert(&row, key.clone());
+            }
+            self.rows.insert(key, row);
+        }
+        Ok(count)
+    }
+
+    fn remove_row(&mut self, key: &RowKey) {
+        let row = self.rows.remove(key).expect("row key comes from this table");
+        for index in self.indexes.iter_mut() {
+            index.remove(&row, key);
+        }
+    }
+
+    /// 一行的主键，没有主键时为 `None`，主键列为 NULL 时报错
+    fn primary_key(&self, row: &StoreRow) -> Result<Option<RowKey>, QueryExceptionError> {
+        let Some(columns) = &self.primary_key else {
+            return Ok(None);
+        };
+        let mut values = Vec::with_capacity(columns.len());
+        for &idx in columns {
+            if row[idx].is_null() {
+                return Err(QueryExceptionError::NullPrimaryKey(self.columns[idx].name.to_owned()));
+            }
+            values.push(row[idx].clone());
+        }
+        Ok(Some(IndexKey(values)))
+    }
+
+    /// 检查每一行的主键列不为 NULL，并且满足列上的 NOT NULL 和 CHECK 约束
+    pub fn check_constraints(&self, rows: &[RowWrite]) -> Result<(), QueryExceptionError> {
+        let schema = self.schema();
+        for (_, values) in rows {
+            self.primary_key(values)?;
+            let row = Row::new(schema.clone(), values.clone());
+            for (column, value) in self.columns.iter().zip(values) {
+                if value.is_null() && column.not_null() {
+                    return Err(QueryExceptionError::NotNullViolation(column.name.to_owned()));
+                }
+                for check in column.checks() {
+                    if !eval::satisfies(check, &row)? {
+                        return Err(QueryExceptionError::CheckViolation(check.to_string()));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
 
-        self.rows.insert(id, row);
+    /// 检查写入 `rows` 后唯一索引是否仍然成立，`replaced` 中的行会被替换
+    fn check_unique(
+        &self,
+        rows: &[RowWrite],
+        replaced: &HashSet<&RowKey>
+    ) -> Result<(), QueryExceptionError> {
+        for index in self.indexes.iter().filter(|index| index.unique) {
+            let mut seen = BTreeSet::new();
+            for (_, row) in rows {
+                let Some(key) = index.key(row) else {
+                    continue;
+                };
+                if index.get(&key).any(|row_key| !replaced.contains(row_key)) || !seen.insert(key.clone()) {
+                    return Err(self.duplicate_key(index, key));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn duplicate_primary_key(&self, key: RowKey) -> QueryExceptionError {
+        let columns = self.primary_key.iter().flatten().map(|&idx| self.columns[idx].name.as_str());
+        QueryExceptionError::DuplicatePrimaryKey {
+            columns: columns.collect::<Vec<_>>().join(", "),
+            values: key.0.iter().map(Value::to_string).collect::<Vec<_>>().join(", ")
+        }
+    }
+
+    fn duplicate_key(&self, index: &Index, key: IndexKey) -> QueryExceptionError {
+        let columns: Vec<&str> = index.columns.iter().map(|&idx| self.columns[idx].name.as_str()).collect();
+        let values: Vec<String> = key.0.iter().map(Value::to_string).collect();
+        if index.constraint {
+            return QueryExceptionError::UniqueViolation {
+                constraint: index.name.to_owned(),
+                columns: columns.join(", "),
+                values: values.join(", ")
+            };
+        }
+        QueryExceptionError::DuplicateKey {
+            index: index.name.to_owned(),
+            columns: columns.join(", "),
+            values: values.join(", ")
+        }
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = Row> {
-        self.into_iter()
+    /// 重新收集统计信息
+    pub fn analyze(&mut self) {
+        self.statistics = Some(TableStatistics::collect(self.rows.values(), self.columns.len()));
+    }
+
+    /// 最近一次 ANALYZE 的结果，从未 ANALYZE 过时为 `None`
+    pub fn statistics(&self) -> Option<&TableStatistics> {
+        self.statistics.as_ref()
+    }
+
+    fn allocate_key(&mut self) -> RowKey {
+        let id = self.next_id;
+        self.next_id += 1;
+        IndexKey(vec![Value::Int(id as i64)])
+    }
+
+    /// 将 INSERT 的列名解析为列下标
+    fn resolve_targets(&self, columns: Option<&[String]>) -> Result<Vec<usize>, QueryExceptionError> {
+        let Some(names) = columns else {
+            return Ok((0..self.columns.len()).collect());
+        };
+
+        let mut targets = Vec::with_capacity(names.len());
+        for name in names {
+            let idx = self.column_index(name)?;
+            if targets.contains(&idx) {
+                return Err(QueryExceptionError::DuplicateColumn(name.to_owned()));
+            }
+            targets.push(idx);
+        }
+        Ok(targets)
+    }
+
+    /// 按目标列构造一行，每个值求值后按列类型转换，没有给出的列取默认值，
+    /// AUTOINCREMENT 列没有给出值或给出 NULL 时取它的序列的下一个值
+    fn build_row(
+        &mut self,
+        targets: &[usize],
+        values: &[Expr],
+        sequences: &mut HashMap<String, Sequence>
+    ) -> Result<StoreRow, QueryExceptionError> {
+        if values.len() != targets.len() {
+            return Err(QueryExceptionError::ValueCountMismatch {
+                expected: targets.len(),
+                found: values.len()
+            });
+        }
+
+        let empty = Row::new(Rc::new(Vec::new()), Vec::new());
+        let mut row: StoreRow = vec![Value::Null; self.columns.len()];
+        for (idx, column) in self.columns.iter().enumerate() {
+            if let Some(default) = column.default_value().filter(|_| !targets.contains(&idx)) {
+                let default = sequence::expand(default, sequences)?;
+                row[idx] = eval::evaluate(&default, &empty)?.coerce(&column.type_info)?;
+            }
+        }
+        for (&idx, value) in targets.iter().zip(values) {
+            let value = sequence::expand(value, sequences)?;
+            row[idx] = eval::evaluate(&value, &empty)?.coerce(&self.columns[idx].type_info)?;
+        }
+        for (&idx, sequence) in self.auto_increment.iter_mut() {
+            if !row[idx].is_null() {
+                continue;
+            }
+            let value = sequence
+                .next_value()
+                .ok_or_else(|| QueryExceptionError::SequenceExhausted(self.columns[idx].name.to_owned()))?;
+            row[idx] = Value::Int(value);
+        }
+        Ok(row)
+    }
+
+    pub fn column_index(&self, name: &String) -> Result<usize, QueryExceptionError> {
+        self.columns
+            .iter()
+            .position(|col| &col.name == name)
+            .ok_or_else(|| QueryExceptionError::ColumnDoesNotExists(name.to_owned()))
+    }
+
+    /// 扫描结果中各列的描述
+    pub fn schema(&self) -> Rc<RowSchema> {
+        Rc::new(
+            self.columns
+                .iter()
+                .map(|col| OutputColumn::new(col.name.to_owned()))
+                .collect()
+        )
+    }
+
+    /// 在查询中以 `qualifier` 引用这张表时扫描结果的列描述
+    pub fn qualified_schema(&self, qualifier: &str) -> Rc<RowSchema> {
+        Rc::new(
+            self.columns
+                .iter()
+                .map(|col| OutputColumn { table: Some(qualifier.to_owned()), name: col.name.to_owned() })
+                .collect()
+        )
+    }
+
+    /// 按给定的列描述扫描所有行
+    pub fn scan(&self, schema: Rc<RowSchema>) -> TableIter<'_> {
+        TableIter::new(self.rows.iter(), schema, None)
+    }
+
+    /// 只读取下标为 `projection` 的列，`schema` 描述的是投影后的列
+    pub fn scan_columns(&self, schema: Rc<RowSchema>, projection: Vec<usize>) -> TableIter<'_> {
+        TableIter::new(self.rows.iter(), schema, Some(projection))
+    }
+
+    /// 连同行的键一起遍历，用于修改表中的行
+    fn iter_with_keys(&self) -> impl Iterator<Item = (RowKey, Row)> + '_ {
+        let schema = self.schema();
+        self.rows
+            .iter()
+            .map(move |(key, values)| (key.clone(), Row::new(schema.clone(), values.clone())))
     }
 }
 
 /// 使得 table 可以转换为迭代器
 impl<'a> IntoIterator for &'a Table {
-    type Item = Row<'a>;
+    type Item = Row;
     type IntoIter = TableIter<'a>;
 
     fn into_iter(self) -> Self::IntoIter {
-        let col_info = Rc::new(self.columns.clone());
-        TableIter::new(self.rows.iter(), col_info)
+        self.scan(self.schema())
     }
 }
 
@@ -65,29 +601,71 @@ impl<'a> IntoIterator for &'a Table {
 /// 表中 [`Row`] 的迭代器
 pub(crate) struct TableIter<'a> {
     /// btree_map 的底层迭代器
-    map_iter: std::collections::btree_map::Iter<'a, usize, StoreRow>,
+    map_iter: std::collections::btree_map::Iter<'a, RowKey, StoreRow>,
     /// table 的每一列
-    columns: Rc<ColumnInfo>
+    columns: Rc<RowSchema>,
+    /// 只读取这些下标的列，`None` 表示读取所有列
+    projection: Option<Vec<usize>>
 }
 
 impl<'a> TableIter<'a> {
     pub fn new(
-        map_iter: std::collections::btree_map::Iter<'a, usize, StoreRow>,
-        columns: Rc<ColumnInfo>
+        map_iter: std::collections::btree_map::Iter<'a, RowKey, StoreRow>,
+        columns: Rc<RowSchema>,
+        projection: Option<Vec<usize>>
     ) -> Self
     {
-        Self { map_iter, columns }
+        Self { map_iter, columns, projection }
     }
 }
 
 impl<'a> Iterator for TableIter<'a> {
-    type Item = Row<'a>;
+    type Item = Row;
 
     fn next(&mut self) -> Option<Self::Item> {
         self.map_iter
             .next()
-            .map(|(id, data)| {
-                Row::new(self.columns.clone(), id.clone(), data)
+            .map(|(_, data)| {
+                Row::new(self.columns.clone(), project(data, self.projection.as_deref()))
             })
     }
 }
+
+/// 只取出下标为 `projection` 的列，`None` 表示所有列
+fn project(data: &StoreRow, projection: Option<&[usize]>) -> Vec<Value> {
+    match projection {
+        Some(projection) => projection.iter().map(|&idx| data[idx].clone()).collect(),
+        None => data.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sql_parser::expr::Literal;
+
+    #[test]
+    fn test_row_ids_are_not_reused() {
+        let mut table = Table::new("t", vec![Column::new("id", SqlTypeInfo::Int)], &[]).unwrap();
+        let insert = |table: &mut Table, values: &[i64]| {
+            let rows: Vec<_> = values.iter().map(|value| vec![Expr::Literal(Literal::Int(*value))]).collect();
+            let rows = table.prepare_insert(None, &rows, &mut HashMap::new()).unwrap();
+            table.write(rows).unwrap();
+        };
+        insert(&mut table, &[1, 2, 3]);
+
+        // 删除最后一行后，新行也不会复用它的 id
+        let predicate = Expr::Binary {
+            left: Box::new(Expr::Column("id".into())),
+            op: sql_parser::expr::BinaryOp::GtEq,
+            right: Box::new(Expr::Literal(Literal::Int(2)))
+        };
+        assert_eq!(table.delete(Some(&predicate)).unwrap(), 2);
+        insert(&mut table, &[4]);
+
+        assert_eq!(
+            table.rows.keys().cloned().collect::<Vec<_>>(),
+            vec![IndexKey(vec![Value::Int(0)]), IndexKey(vec![Value::Int(3)])]
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use serde::{Deserialize, Serialize};
+use sql_parser::expr::{BinaryOp, Literal};
+use sql_parser::SqlTypeInfo;
+use crate::error::QueryExceptionError;
+
+/// 表中存储的单个值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Value {
+    Null,
+    Int(i64),
+    Float(f64),
+    Text(String),
+    Bool(bool),
+}
+
+impl Value {
+    /// 将值转换为列的类型
+    ///
+    /// NULL 可以写入任意类型的列，整数可以写入 `float` 列
+    pub fn coerce(self, type_info: &SqlTypeInfo) -> Result<Self, QueryExceptionError> {
+        match (self, type_info) {
+            (Value::Null, _) => Ok(Value::Null),
+            (Value::Int(i), SqlTypeInfo::Int) => Ok(Value::Int(i)),
+            (Value::Int(i), SqlTypeInfo::Float) => Ok(Value::Float(i as f64)),
+            (Value::Float(f), SqlTypeInfo::Float) => Ok(Value::Float(f)),
+            (Value::Text(s), SqlTypeInfo::String) => Ok(Value::Text(s)),
+            (value, type_info) => Err(QueryExceptionError::TypeMismatch(format!(
+                "{value} is not a valid {type_info:?}"
+            ))),
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    /// SQL 比较语义：任意一边为 NULL 时结果未知，返回 `None`
+    ///
+    /// 整数和浮点数之间按精确的数值比较，`-0.0` 与 `0.0` 相等
+    pub fn compare(&self, other: &Value) -> Result<Option<Ordering>, QueryExceptionError> {
+        match (self, other) {
+            (Value::Null, _) | (_, Value::Null) => Ok(None),
+            (Value::Int(l), Value::Int(r)) => Ok(Some(l.cmp(r))),
+            (Value::Float(l), Value::Float(r)) => Ok(Some(normalize(*l).total_cmp(&normalize(*r)))),
+            (Value::Int(l), Value::Float(r)) => Ok(Some(compare_int_float(*l, *r))),
+            (Value::Float(l), Value::Int(r)) => Ok(Some(compare_int_float(*r, *l).reverse())),
+            (Value::Text(l), Value::Text(r)) => Ok(Some(l.cmp(r))),
+            (Value::Bool(l), Value::Bool(r)) => Ok(Some(l.cmp(r))),
+            (l, r) => Err(QueryExceptionError::TypeMismatch(format!(
+                "cannot compare {l} with {r}"
+            ))),
+        }
+    }
+
+    /// 算术运算，任意一边为 NULL 时结果为 NULL
+    ///
+    /// 两个整数的运算结果仍为整数，和浮点数混合时结果为浮点数
+    pub fn arithmetic(&self, op: BinaryOp, other: &Value) -> Result<Value, QueryExceptionError> {
+        match (self, other) {
+            (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+            (Value::Int(l), Value::Int(r)) => {
+                let result = match op {
+                    BinaryOp::Plus => l.checked_add(*r),
+                    BinaryOp::Minus => l.checked_sub(*r),
+                    BinaryOp::Multiply => l.checked_mul(*r),
+                    BinaryOp::Divide | BinaryOp::Modulo if *r == 0 => {
+                        return Err(QueryExceptionError::ArithmeticError("division by zero".into()))
+                    }
+                    BinaryOp::Divide => l.checked_div(*r),
+                    BinaryOp::Modulo => l.checked_rem(*r),
+                    _ => unreachable!("{op:?} is not an arithmetic operator"),
+                };
+                result
+                    .map(Value::Int)
+                    .ok_or_else(|| QueryExceptionError::ArithmeticError(format!("{l} {op:?} {r} overflows")))
+            }
+            (l, r) => {
+                let (Some(l), Some(r)) = (l.as_f64(), r.as_f64()) else {
+                    return Err(QueryExceptionError::TypeMismatch(format!(
+                        "cannot apply {op:?} to {l} and {r}"
+                    )));
+                };
+                let result = match op {
+                    BinaryOp::Plus => l + r,
+                    BinaryOp::Minus => l - r,
+                    BinaryOp::Multiply => l * r,
+                    BinaryOp::Divide | BinaryOp::Modulo if r == 0.0 => {
+                        return Err(QueryExceptionError::ArithmeticError("division by zero".into()))
+                    }
+                    BinaryOp::Divide => l / r,
+                    BinaryOp::Modulo => l % r,
+                    _ => unreachable!("{op:?} is not an arithmetic operator"),
+                };
+                Ok(Value::Float(result))
+            }
+        }
+    }
+
+    pub fn negate(&self) -> Result<Value, QueryExceptionError> {
+        match self {
+            Value::Null => Ok(Value::Null),
+            Value::Int(i) => i
+                .checked_neg()
+                .map(Value::Int)
+                .ok_or_else(|| QueryExceptionError::ArithmeticError(format!("-{i} overflows"))),
+            Value::Float(f) => Ok(Value::Float(-f)),
+            other => Err(QueryExceptionError::TypeMismatch(format!("cannot negate {other}"))),
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Int(i) => Some(*i as f64),
+            Value::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+}
+
+/// `-0.0` 视为 `0.0`，其它浮点数保持不变
+fn normalize(f: f64) -> f64 {
+    if f == 0.0 { 0.0 } else { f }
+}
+
+/// 不把整数转换为浮点数，超过 2^53 的整数也能精确比较；NaN 按 `total_cmp` 的顺序排在两端
+fn compare_int_float(i: i64, f: f64) -> Ordering {
+    // i64 的范围为 [-2^63, 2^63)
+    const BOUND: f64 = 9_223_372_036_854_775_808.0;
+    if f.is_nan() {
+        return if f.is_sign_negative() { Ordering::Greater } else { Ordering::Less };
+    }
+    if f >= BOUND {
+        return Ordering::Less;
+    }
+    if f < -BOUND {
+        return Ordering::Greater;
+    }
+    let whole = f.trunc();
+    i.cmp(&(whole as i64)).then_with(|| 0f64.total_cmp(&normalize(f - whole)))
+}
+
+/// 结构相等，浮点数按位比较以满足 `Eq` 和 `Hash`，`-0.0` 与 `0.0` 相等
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Null, Value::Null) => true,
+            (Value::Int(l), Value::Int(r)) => l == r,
+            (Value::Float(l), Value::Float(r)) => normalize(*l).total_cmp(&normalize(*r)) == Ordering::Equal,
+            (Value::Text(l), Value::Text(r)) => l == r,
+            (Value::Bool(l), Value::Bool(r)) => l == r,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Value {}
+
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Value::Null => {}
+            Value::Int(i) => i.hash(state),
+            Value::Float(f) => normalize(*f).to_bits().hash(state),
+            Value::Text(s) => s.hash(state),
+            Value::Bool(b) => b.hash(state),
+        }
+    }
+}
+
+impl From<&Literal> for Value {
+    fn from(literal: &Literal) -> Self {
+        match literal {
+            Literal::Null => Value::Null,
+            Literal::Bool(b) => Value::Bool(*b),
+            Literal::Int(i) => Value::Int(*i),
+            Literal::Float(f) => Value::Float(*f),
+            Literal::String(s) => Value::Text(s.clone()),
+        }
+    }
+}
+
+/// 常量折叠时把计算结果写回表达式
+impl From<Value> for Literal {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => Literal::Null,
+            Value::Int(i) => Literal::Int(i),
+            Value::Float(f) => Literal::Float(f),
+            Value::Text(s) => Literal::String(s),
+            Value::Bool(b) => Literal::Bool(b),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "NULL"),
+            Value::Int(i) => write!(f, "{i}"),
+            // Debug 格式会保留 `2.0` 中的小数部分
+            Value::Float(v) => write!(f, "{v:?}"),
+            Value::Text(s) => write!(f, "{s}"),
+            Value::Bool(b) => write!(f, "{b}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_numbers() {
+        let compare = |l: Value, r: Value| l.compare(&r).unwrap().unwrap();
+        assert_eq!(compare(Value::Float(-0.0), Value::Float(0.0)), Ordering::Equal);
+        assert_eq!(compare(Value::Float(-0.0), Value::Int(0)), Ordering::Equal);
+        assert_eq!(Value::Float(-0.0), Value::Float(0.0));
+
+        // 2^53 + 1 转换为浮点数后等于 2^53
+        let big = (1i64 << 53) + 1;
+        assert_eq!(compare(Value::Int(big), Value::Float((1i64 << 53) as f64)), Ordering::Greater);
+        assert_eq!(compare(Value::Float((1i64 << 53) as f64), Value::Int(big)), Ordering::Less);
+        assert_eq!(compare(Value::Int(i64::MAX), Value::Float(i64::MAX as f64)), Ordering::Less);
+        assert_eq!(compare(Value::Int(i64::MIN), Value::Float(i64::MIN as f64)), Ordering::Equal);
+        assert_eq!(compare(Value::Int(-2), Value::Float(-2.5)), Ordering::Greater);
+        assert_eq!(compare(Value::Int(2), Value::Float(2.5)), Ordering::Less);
+        assert_eq!(compare(Value::Int(3), Value::Float(3.0)), Ordering::Equal);
+        assert_eq!(compare(Value::Int(i64::MAX), Value::Float(f64::INFINITY)), Ordering::Less);
+        assert_eq!(compare(Value::Int(i64::MIN), Value::Float(f64::NEG_INFINITY)), Ordering::Greater);
+    }
+}
@@ -7,18 +7,38 @@ use nom::{
 };
 use serde::{Deserialize, Serialize};
 use crate::commands::{
+    AnalyzeStatement,
     CreateStatement,
+    CreateIndexStatement,
+    CreateSequenceStatement,
     SelectStatement,
-    InsertStatement
+    InsertStatement,
+    UpdateStatement,
+    DeleteStatement,
+    DropStatement,
+    DropIndexStatement,
+    DropSequenceStatement,
+    ExplainStatement,
+    TruncateStatement
 };
 use crate::error::FormattedError;
 use crate::parse::{Parse, ParseResult, peek_then_cut, RawSpan};
 
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum SqlQuery {
     Create(CreateStatement),
     Insert(InsertStatement),
     Select(SelectStatement),
+    Update(UpdateStatement),
+    Delete(DeleteStatement),
+    Drop(DropStatement),
+    Truncate(TruncateStatement),
+    Analyze(AnalyzeStatement),
+    Explain(ExplainStatement),
+    CreateIndex(CreateIndexStatement),
+    DropIndex(DropIndexStatement),
+    CreateSequence(CreateSequenceStatement),
+    DropSequence(DropSequenceStatement),
 }
 
 impl<'a> Parse<'a> for SqlQuery {
@@ -34,7 +54,17 @@ impl<'a> Parse<'a> for SqlQuery {
                             SqlQuery::Select(s)
                         })),
                         peek_then_cut("create", map(CreateStatement::parse, SqlQuery::Create)),
-                        peek_then_cut("insert", map(InsertStatement::parse, SqlQuery::Insert))
+                        peek_then_cut("create", map(CreateIndexStatement::parse, SqlQuery::CreateIndex)),
+                        peek_then_cut("create", map(CreateSequenceStatement::parse, SqlQuery::CreateSequence)),
+                        peek_then_cut("insert", map(InsertStatement::parse, SqlQuery::Insert)),
+                        peek_then_cut("update", map(UpdateStatement::parse, SqlQuery::Update)),
+                        peek_then_cut("delete", map(DeleteStatement::parse, SqlQuery::Delete)),
+                        peek_then_cut("drop", map(DropStatement::parse, SqlQuery::Drop)),
+                        peek_then_cut("drop", map(DropIndexStatement::parse, SqlQuery::DropIndex)),
+                        peek_then_cut("drop", map(DropSequenceStatement::parse, SqlQuery::DropSequence)),
+                        peek_then_cut("truncate", map(TruncateStatement::parse, SqlQuery::Truncate)),
+                        peek_then_cut("analyze", map(AnalyzeStatement::parse, SqlQuery::Analyze)),
+                        peek_then_cut("explain", map(ExplainStatement::parse, SqlQuery::Explain))
                     )),
                     multispace0,
                     char(';'),
@@ -65,6 +95,7 @@ pub fn parse_sql_query(input: &str) -> Result<SqlQuery, FormattedError<'_>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::commands::{FromClause, SelectItem};
 
     #[test]
     fn test_error() {
@@ -72,11 +103,22 @@ mod tests {
         assert!(query.is_err(), "expected parse to fail, got {query:?}");
     }
 
+    #[test]
+    fn test_create_and_drop() {
+        assert!(matches!(SqlQuery::parse_from_raw("CREATE TABLE t (a int);").unwrap().1, SqlQuery::Create(_)));
+        assert!(matches!(SqlQuery::parse_from_raw("CREATE INDEX i ON t (a);").unwrap().1, SqlQuery::CreateIndex(_)));
+        assert!(matches!(SqlQuery::parse_from_raw("DROP TABLE t;").unwrap().1, SqlQuery::Drop(_)));
+        assert!(matches!(SqlQuery::parse_from_raw("DROP INDEX i;").unwrap().1, SqlQuery::DropIndex(_)));
+        assert!(matches!(SqlQuery::parse_from_raw("CREATE SEQUENCE s;").unwrap().1, SqlQuery::CreateSequence(_)));
+        assert!(matches!(SqlQuery::parse_from_raw("DROP SEQUENCE s;").unwrap().1, SqlQuery::DropSequence(_)));
+    }
+
     #[test]
     fn test_select() {
         let expected = SelectStatement {
-            table: "t1".to_string(),
-            fields: vec!["foo".to_string(), "bar".to_string()]
+            from: FromClause::single("t1"),
+            fields: vec![SelectItem::column("foo"), SelectItem::column("bar")],
+            ..Default::default()
         };
         assert_eq!(
             SqlQuery::parse_from_raw("select foo, bar from t1;").unwrap().1,
@@ -0,0 +1,48 @@
+use nom::{
+    bytes::complete::tag_no_case,
+    character::complete::multispace1,
+    combinator::opt,
+    error::context,
+    sequence::{preceded, tuple}
+};
+use nom_supreme::ParserExt;
+use serde::{Serialize, Deserialize};
+use crate::parse::{identifier, Parse, ParseResult, RawSpan};
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AnalyzeStatement {
+    /// 为 `None` 时收集所有表的统计信息
+    pub table: Option<String>
+}
+
+impl<'a> Parse<'a> for AnalyzeStatement {
+    /// `ANALYZE [foo]`
+    fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
+        let (remaining_input, (_, table)) = context(
+            "Analyze Statement",
+            tuple((
+                tag_no_case("analyze"),
+                opt(preceded(multispace1, identifier.context("Table Name")))
+            ))
+        )(input)?;
+
+        Ok((remaining_input, AnalyzeStatement { table }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze() {
+        assert_eq!(
+            AnalyzeStatement::parse_from_raw("ANALYZE foo").unwrap().1,
+            AnalyzeStatement { table: Some("foo".into()) }
+        );
+        assert_eq!(
+            AnalyzeStatement::parse_from_raw("analyze").unwrap().1,
+            AnalyzeStatement { table: None }
+        );
+    }
+}
@@ -1,8 +1,16 @@
 //# 解析的 sql 语句
 //# ```sql
 //# CREATE TABLE FOO (
-//#     col1 string,
-//#     col2 int
+//#     id int PRIMARY KEY AUTOINCREMENT,
+//#     col1 string UNIQUE,
+//#     col2 int NOT NULL DEFAULT 0 CHECK (col2 >= 0) UNIQUE
+//# )
+//# CREATE TABLE BAR (a int, b int, c int, PRIMARY KEY (a, b), UNIQUE (b, c))
+//# CREATE TABLE BAZ (
+//#     id int REFERENCES FOO (col2) ON DELETE CASCADE,
+//#     a int,
+//#     b int,
+//#     FOREIGN KEY (a, b) REFERENCES BAR (a, b) ON DELETE SET NULL
 //# )
 //# INSERT INTO FOO VALUES 1,2;
 //# SELECT col1, col2 FROM foo;
@@ -10,27 +18,30 @@
 
 use nom::{
     error::context,
-    combinator::map,
+    combinator::{map, opt},
     branch::alt,
+    multi::many0,
     // sequence: `组合器`按顺序应用`解析器` 模块
-    sequence::{separated_pair, tuple, preceded},
-    character::complete::{char, multispace1}
+    sequence::{delimited, pair, separated_pair, tuple, preceded},
+    character::complete::{char, multispace0, multispace1}
 };
 // tag_no_case 的错误处理更好
 // ParserExt 主要用于在调用标识符时添加 `.context` 以说明我们想要哪种标识符
 use nom_supreme::{tag::complete::tag_no_case, ParserExt};
 use serde::{Deserialize, Serialize};
 
-use crate::parse::{comma_sep, identifier, Parse, ParseResult, RawSpan};
+use crate::expr::Expr;
+use crate::parse::{comma_sep, identifier, keyword, Parse, ParseResult, RawSpan};
 
 // A colum's type
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub enum SqlTypeInfo {
     String,
     Int,
+    Float,
 }
 
-/// parses String | Int
+/// parses String | Int | Integer | Float
 impl <'a> Parse<'a> for SqlTypeInfo {
     fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
         // `context` 参数将有助于稍后提供更好的错误消息
@@ -38,76 +49,293 @@ impl <'a> Parse<'a> for SqlTypeInfo {
             "Column Type",
             // alt 将尝试每个通过的解析器并返回成功的
             alt((
-                map(tag_no_case("string"), |_| Self::String),
-                map(tag_no_case("int"), |_| Self::Int)
+                map(keyword("string"), |_| Self::String),
+                // `integer` 是 `int` 的别名
+                map(alt((keyword("integer"), keyword("int"))), |_| Self::Int),
+                map(keyword("float"), |_| Self::Float)
+            ))
+        )(input)
+    }
+}
+
+/// 写在列定义后面的约束
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ColumnConstraint {
+    PrimaryKey,
+    Unique,
+    NotNull,
+    /// 插入时没有给出这一列的值，就取这个表达式的值
+    Default(Expr),
+    /// 写入的行不能使这个表达式为 false，为 NULL 时视为满足
+    Check(Expr),
+    /// 外键，只能引用一列
+    References(Reference),
+    /// 插入时没有给出值或给出 NULL，就取表上单调递增、不会复用的下一个值，只用于 `int` 列
+    AutoIncrement
+}
+
+/// 匹配：`primary key`
+fn primary_key(input: RawSpan<'_>) -> ParseResult<'_, ()> {
+    map(tuple((keyword("primary"), multispace1, keyword("key"))), |_| ())(input)
+}
+
+/// 删除被引用的行时，对引用它的行的处理
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ReferentialAction {
+    /// 还有行引用时不允许删除
+    #[default]
+    Restrict,
+    /// 一并删除引用它的行
+    Cascade,
+    /// 把引用它的行的外键列置为 NULL
+    SetNull
+}
+
+/// parses `RESTRICT | CASCADE | SET NULL`
+impl<'a> Parse<'a> for ReferentialAction {
+    fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
+        context(
+            "Referential Action",
+            alt((
+                map(keyword("restrict"), |_| ReferentialAction::Restrict),
+                map(keyword("cascade"), |_| ReferentialAction::Cascade),
+                map(tuple((keyword("set"), multispace1, keyword("null"))), |_| ReferentialAction::SetNull)
+            ))
+        )(input)
+    }
+}
+
+/// 外键引用的表和列
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Reference {
+    pub table: String,
+    pub columns: Vec<String>,
+    pub on_delete: ReferentialAction
+}
+
+/// parses `REFERENCES <table> (col, ...) [ON DELETE <action>]`
+impl<'a> Parse<'a> for Reference {
+    fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
+        context(
+            "Reference",
+            map(
+                tuple((
+                    preceded(pair(keyword("references"), multispace1), identifier.context("Table Name")),
+                    preceded(multispace0, column_list),
+                    opt(preceded(
+                        tuple((multispace1, keyword("on"), multispace1, keyword("delete"), multispace1)),
+                        ReferentialAction::parse
+                    ))
+                )),
+                |(table, columns, on_delete)| Reference { table, columns, on_delete: on_delete.unwrap_or_default() }
+            )
+        )(input)
+    }
+}
+
+/// parses `PRIMARY KEY | UNIQUE | NOT NULL | AUTOINCREMENT | DEFAULT <expr> | CHECK (<expr>) | REFERENCES ...`
+impl<'a> Parse<'a> for ColumnConstraint {
+    fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
+        context(
+            "Column Constraint",
+            alt((
+                map(primary_key, |_| ColumnConstraint::PrimaryKey),
+                map(keyword("unique"), |_| ColumnConstraint::Unique),
+                map(tuple((keyword("not"), multispace1, keyword("null"))), |_| ColumnConstraint::NotNull),
+                map(keyword("autoincrement"), |_| ColumnConstraint::AutoIncrement),
+                map(
+                    preceded(pair(keyword("default"), multispace1), Expr::parse.context("Default Value")),
+                    ColumnConstraint::Default
+                ),
+                map(
+                    preceded(
+                        pair(keyword("check"), multispace0),
+                        delimited(
+                            pair(char('('), multispace0),
+                            Expr::parse.context("Check Condition"),
+                            pair(multispace0, char(')'))
+                        )
+                    ),
+                    ColumnConstraint::Check
+                ),
+                map(Reference::parse, ColumnConstraint::References)
             ))
         )(input)
     }
 }
 
-/// 列名 + 类型
+/// 与列定义并列的表级约束
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum TableConstraint {
+    /// 主键列，按顺序组成主键
+    PrimaryKey(Vec<String>),
+    /// 这些列的值组合起来不能重复，含 NULL 的行除外
+    Unique(Vec<String>),
+    /// 外键列，按顺序与引用的列对应
+    ForeignKey { columns: Vec<String>, reference: Reference }
+}
+
+/// 括号中以逗号分隔的列名
+fn column_list(input: RawSpan<'_>) -> ParseResult<'_, Vec<String>> {
+    delimited(
+        pair(char('('), multispace0),
+        comma_sep(identifier.context("Column Name")),
+        pair(multispace0, char(')'))
+    )(input)
+}
+
+/// parses `PRIMARY KEY (col, ...)` | `UNIQUE (col, ...)` | `FOREIGN KEY (col, ...) REFERENCES ...`
+impl<'a> Parse<'a> for TableConstraint {
+    fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
+        context(
+            "Table Constraint",
+            alt((
+                map(preceded(pair(primary_key, multispace0), column_list), TableConstraint::PrimaryKey),
+                map(preceded(pair(keyword("unique"), multispace0), column_list), TableConstraint::Unique),
+                map(
+                    preceded(
+                        tuple((keyword("foreign"), multispace1, keyword("key"), multispace0)),
+                        separated_pair(column_list, multispace0, Reference::parse)
+                    ),
+                    |(columns, reference)| TableConstraint::ForeignKey { columns, reference }
+                )
+            ))
+        )(input)
+    }
+}
+
+/// 列名 + 类型 + 列约束
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Column {
     pub name: String,
-    pub type_info: SqlTypeInfo
+    pub type_info: SqlTypeInfo,
+    pub constraints: Vec<ColumnConstraint>
+}
+
+impl Column {
+    /// 没有约束的列
+    pub fn new(name: impl Into<String>, type_info: SqlTypeInfo) -> Self {
+        Self { name: name.into(), type_info, constraints: Vec::new() }
+    }
+
+    /// 是否带有 `NOT NULL` 约束
+    pub fn not_null(&self) -> bool {
+        self.constraints.contains(&ColumnConstraint::NotNull)
+    }
+
+    /// 是否带有 `AUTOINCREMENT` 约束
+    pub fn auto_increment(&self) -> bool {
+        self.constraints.contains(&ColumnConstraint::AutoIncrement)
+    }
+
+    /// `DEFAULT` 约束给出的默认值
+    pub fn default_value(&self) -> Option<&Expr> {
+        self.constraints.iter().find_map(|constraint| match constraint {
+            ColumnConstraint::Default(expr) => Some(expr),
+            _ => None
+        })
+    }
+
+    /// 列上的 `REFERENCES` 约束
+    pub fn references(&self) -> impl Iterator<Item = &Reference> + '_ {
+        self.constraints.iter().filter_map(|constraint| match constraint {
+            ColumnConstraint::References(reference) => Some(reference),
+            _ => None
+        })
+    }
+
+    /// 列上的 `CHECK` 约束
+    pub fn checks(&self) -> impl Iterator<Item = &Expr> + '_ {
+        self.constraints.iter().filter_map(|constraint| match constraint {
+            ColumnConstraint::Check(expr) => Some(expr),
+            _ => None
+        })
+    }
 }
 
-/// parses "<columnName> <columnType>"
-/// 解析出语句：`NAME string`
+/// parses "<columnName> <columnType> [<constraint> ...]"
+/// 解析出语句：`NAME string NOT NULL DEFAULT 'x'`
 impl<'a> Parse<'a> for Column {
     fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
         context(
             "Create Column",
             map(
-                // 分割一对值
-                // `first` 是要应用的第一个解析器。
-                // `seq` 是分隔符解析器
-                // `second` 是要应用的第二个解析器
-                separated_pair(
-                    identifier.context("Column Name"),
-                    multispace1,
-                    SqlTypeInfo::parse
-                ),
+                tuple((
+                    // 分割一对值
+                    // `first` 是要应用的第一个解析器。
+                    // `seq` 是分隔符解析器
+                    // `second` 是要应用的第二个解析器
+                    separated_pair(
+                        identifier.context("Column Name"),
+                        multispace1,
+                        SqlTypeInfo::parse
+                    ),
+                    many0(preceded(multispace1, ColumnConstraint::parse))
+                )),
                 // 把解析出来的东西，构造成一个 struct
-                |(name, type_info)| Self { name, type_info }
+                |((name, type_info), constraints)| Self { name, type_info, constraints }
             )
         )(input)
     }
 }
 
 /// 要创建的表及其列
-#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct CreateStatement {
     pub table: String,
-    pub columns: Vec<Column>
+    pub columns: Vec<Column>,
+    /// 表级约束，与列定义的先后顺序无关
+    pub constraints: Vec<TableConstraint>,
+    /// 带有 `IF NOT EXISTS` 时，表已存在不视为错误
+    pub if_not_exists: bool
 }
 
-/// 解析包含在括号中的以逗号分隔的列定义列表
+/// 括号中的一项：列定义或表级约束
+enum TableElement {
+    Column(Column),
+    Constraint(TableConstraint)
+}
+
+/// 解析包含在括号中的以逗号分隔的列定义和表级约束列表
 /// example:
 /// (
 ///      col1 string,
-///      col2 int
+///      col2 int,
+///      PRIMARY KEY (col1)
 /// )
-fn column_definitions(input: RawSpan<'_>) -> ParseResult<'_, Vec<Column>> {
+fn column_definitions(input: RawSpan<'_>) -> ParseResult<'_, (Vec<Column>, Vec<TableConstraint>)> {
     context(
         "Column Definitions",
         map(
             tuple((
-                char('('),
-                comma_sep(Column::parse),
-                char(')')
+                pair(char('('), multispace0),
+                comma_sep(alt((
+                    map(TableConstraint::parse, TableElement::Constraint),
+                    map(Column::parse, TableElement::Column)
+                ))),
+                pair(multispace0, char(')'))
             )),
-            |(_, cols, _)| cols
+            |(_, elements, _)| {
+                let mut columns = Vec::new();
+                let mut constraints = Vec::new();
+                for element in elements {
+                    match element {
+                        TableElement::Column(column) => columns.push(column),
+                        TableElement::Constraint(constraint) => constraints.push(constraint)
+                    }
+                }
+                (columns, constraints)
+            }
         )
     )(input)
 }
 
-/// parses "CREATE TABLE <table name> <column defs>
+/// parses "CREATE TABLE [IF NOT EXISTS] <table name> <column defs>
 impl<'a> Parse<'a> for CreateStatement {
     fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
         map(
             separated_pair(
-                // 匹配：`create table NAME`
+                // 匹配：`create table [if not exists] NAME`
                 preceded(
                     // 匹配：`create table`
                     tuple((
@@ -116,17 +344,40 @@ impl<'a> Parse<'a> for CreateStatement {
                         tag_no_case("table"),
                         multispace1
                     )),
-                    identifier.context("Table Name")
+                    tuple((
+                        opt(if_not_exists),
+                        identifier.context("Table Name")
+                    ))
                 ),
                 multispace1,
                 // 解析表主体
                 column_definitions
             ).context("Create Table"),
-            |(table, columns)| Self { table, columns }
+            |((if_not_exists, table), (columns, constraints))| Self {
+                table,
+                columns,
+                constraints,
+                if_not_exists: if_not_exists.is_some()
+            }
         )(input)
     }
 }
 
+/// 匹配：`if not exists `
+fn if_not_exists(input: RawSpan<'_>) -> ParseResult<'_, ()> {
+    map(
+        tuple((
+            tag_no_case("if"),
+            multispace1,
+            tag_no_case("not"),
+            multispace1,
+            tag_no_case("exists"),
+            multispace1
+        )),
+        |_| ()
+    )(input)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -136,19 +387,12 @@ mod tests {
         let expected = CreateStatement {
             table: "foo".into(),
             columns: vec![
-                Column {
-                    name: "col1".into(),
-                    type_info: SqlTypeInfo::Int
-                },
-                Column {
-                    name: "col2".into(),
-                    type_info: SqlTypeInfo::String
-                },
-                Column {
-                    name: "col3".into(),
-                    type_info: SqlTypeInfo::String
-                }
-            ]
+                Column::new("col1", SqlTypeInfo::Int),
+                Column::new("col2", SqlTypeInfo::String),
+                Column::new("col3", SqlTypeInfo::String)
+            ],
+            constraints: vec![],
+            if_not_exists: false
         };
 
         assert_eq!(
@@ -158,4 +402,108 @@ mod tests {
             expected
         );
     }
+
+    #[test]
+    fn test_create_if_not_exists() {
+        let expected = CreateStatement {
+            table: "foo".into(),
+            columns: vec![
+                Column::new("col1", SqlTypeInfo::Float)
+            ],
+            constraints: vec![],
+            if_not_exists: true
+        };
+
+        assert_eq!(
+            CreateStatement::parse_from_raw("CREATE TABLE IF NOT EXISTS foo (col1 float)")
+                .unwrap()
+                .1,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_primary_key() {
+        let (_, create) = CreateStatement::parse_from_raw("CREATE TABLE foo (id int PRIMARY KEY, name string)").unwrap();
+        assert_eq!(create.columns[0].constraints, vec![ColumnConstraint::PrimaryKey]);
+        assert!(create.columns[1].constraints.is_empty());
+        assert!(create.constraints.is_empty());
+
+        let (_, create) = CreateStatement::parse_from_raw(
+            "CREATE TABLE foo (\n    a int,\n    primary key(a, b),\n    b string\n)"
+        ).unwrap();
+        assert_eq!(create.columns, vec![Column::new("a", SqlTypeInfo::Int), Column::new("b", SqlTypeInfo::String)]);
+        assert_eq!(create.constraints, vec![TableConstraint::PrimaryKey(vec!["a".into(), "b".into()])]);
+
+        assert!(CreateStatement::parse_from_raw("CREATE TABLE foo (a int, PRIMARY KEY ())").is_err());
+    }
+
+    #[test]
+    fn test_auto_increment() {
+        let (_, create) = CreateStatement::parse_from_raw("CREATE TABLE foo (id int PRIMARY KEY AUTOINCREMENT, name string)").unwrap();
+        assert_eq!(create.columns[0].constraints, vec![ColumnConstraint::PrimaryKey, ColumnConstraint::AutoIncrement]);
+        assert!(create.columns[0].auto_increment());
+        assert!(!create.columns[1].auto_increment());
+
+        let (_, create) = CreateStatement::parse_from_raw("CREATE TABLE foo (id INTEGER PRIMARY KEY AUTOINCREMENT)").unwrap();
+        assert_eq!(create.columns[0].type_info, SqlTypeInfo::Int);
+        assert!(create.columns[0].auto_increment());
+        assert!(CreateStatement::parse_from_raw("CREATE TABLE foo (id intx)").is_err());
+    }
+
+    #[test]
+    fn test_unique() {
+        let (_, create) = CreateStatement::parse_from_raw(
+            "CREATE TABLE foo (a int UNIQUE NOT NULL, b int, unique(a, b))"
+        ).unwrap();
+        assert_eq!(create.columns[0].constraints, vec![ColumnConstraint::Unique, ColumnConstraint::NotNull]);
+        assert_eq!(create.constraints, vec![TableConstraint::Unique(vec!["a".into(), "b".into()])]);
+        assert!(CreateStatement::parse_from_raw("CREATE TABLE foo (a int, UNIQUE)").is_err());
+    }
+
+    #[test]
+    fn test_column_constraints() {
+        let (_, create) = CreateStatement::parse_from_raw(
+            "CREATE TABLE foo (a int NOT NULL DEFAULT 1 + 1 CHECK(a > 0), b string default 'x' not null, c float)"
+        ).unwrap();
+        let a = &create.columns[0];
+        assert_eq!(a.constraints.len(), 3);
+        assert!(a.not_null());
+        assert_eq!(a.default_value().unwrap().to_string(), "1 + 1");
+        assert_eq!(a.checks().map(ToString::to_string).collect::<Vec<_>>(), vec!["a > 0"]);
+        let b = &create.columns[1];
+        assert!(b.not_null());
+        assert_eq!(b.default_value().unwrap().to_string(), "'x'");
+        assert!(!create.columns[2].not_null());
+
+        assert!(CreateStatement::parse_from_raw("CREATE TABLE foo (a int CHECK ())").is_err());
+        assert!(CreateStatement::parse_from_raw("CREATE TABLE foo (a int DEFAULT)").is_err());
+    }
+
+    #[test]
+    fn test_foreign_key() {
+        let (_, create) = CreateStatement::parse_from_raw(
+            "CREATE TABLE foo (\n    a int NOT NULL REFERENCES bar(id),\n    b int,\n    \
+             FOREIGN KEY (a, b) REFERENCES baz (x, y) ON DELETE SET NULL\n)"
+        ).unwrap();
+        let reference = |table: &str, columns: &[&str], on_delete| Reference {
+            table: table.into(),
+            columns: columns.iter().map(|col| col.to_string()).collect(),
+            on_delete
+        };
+        assert_eq!(create.columns[0].references().collect::<Vec<_>>(), vec![&reference("bar", &["id"], ReferentialAction::Restrict)]);
+        assert!(create.columns[0].not_null());
+        assert_eq!(create.constraints, vec![TableConstraint::ForeignKey {
+            columns: vec!["a".into(), "b".into()],
+            reference: reference("baz", &["x", "y"], ReferentialAction::SetNull)
+        }]);
+
+        let (_, create) = CreateStatement::parse_from_raw(
+            "CREATE TABLE foo (a int REFERENCES bar (id) ON DELETE CASCADE)"
+        ).unwrap();
+        assert_eq!(create.columns[0].references().next().unwrap().on_delete, ReferentialAction::Cascade);
+
+        assert!(CreateStatement::parse_from_raw("CREATE TABLE foo (a int REFERENCES bar)").is_err());
+        assert!(CreateStatement::parse_from_raw("CREATE TABLE foo (a int REFERENCES bar (id) ON DELETE)").is_err());
+    }
 }
@@ -0,0 +1,59 @@
+use nom::{
+    bytes::complete::tag_no_case,
+    character::complete::multispace1,
+    combinator::opt,
+    error::context,
+    sequence::{preceded, tuple}
+};
+use nom_supreme::ParserExt;
+use serde::{Serialize, Deserialize};
+use crate::expr::{where_clause, Expr};
+use crate::parse::{identifier, Parse, ParseResult, RawSpan};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeleteStatement {
+    pub table: String,
+    pub where_clause: Option<Expr>
+}
+
+impl<'a> Parse<'a> for DeleteStatement {
+    /// `DELETE FROM foo [WHERE <expr>]`
+    fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
+        let (remaining_input, (_, _, table, where_clause)) = context(
+            "Delete Statement",
+            tuple((
+                tag_no_case("delete"),
+                preceded(multispace1, tag_no_case("from")),
+                preceded(multispace1, identifier.context("Table Name")),
+                opt(where_clause)
+            ))
+        )(input)?;
+
+        Ok((remaining_input, DeleteStatement { table, where_clause }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::{BinaryOp, Literal};
+
+    #[test]
+    fn test_delete() {
+        assert_eq!(
+            DeleteStatement::parse_from_raw("DELETE FROM foo").unwrap().1,
+            DeleteStatement { table: "foo".into(), where_clause: None }
+        );
+        assert_eq!(
+            DeleteStatement::parse_from_raw("delete from foo where a = 1").unwrap().1,
+            DeleteStatement {
+                table: "foo".into(),
+                where_clause: Some(Expr::Binary {
+                    left: Box::new(Expr::Column("a".into())),
+                    op: BinaryOp::Eq,
+                    right: Box::new(Expr::Literal(Literal::Int(1)))
+                })
+            }
+        );
+    }
+}
@@ -0,0 +1,56 @@
+use nom::{
+    bytes::complete::tag_no_case,
+    character::complete::multispace1,
+    combinator::opt,
+    error::context,
+    sequence::{preceded, tuple}
+};
+use nom_supreme::ParserExt;
+use serde::{Serialize, Deserialize};
+use crate::parse::{identifier, Parse, ParseResult, RawSpan};
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DropStatement {
+    pub table: String,
+    /// 带有 `IF EXISTS` 时，表不存在不视为错误
+    pub if_exists: bool
+}
+
+impl<'a> Parse<'a> for DropStatement {
+    /// `DROP TABLE [IF EXISTS] foo`
+    fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
+        let (remaining_input, (_, _, if_exists, table)) = context(
+            "Drop Statement",
+            tuple((
+                tag_no_case("drop"),
+                preceded(multispace1, tag_no_case("table")),
+                opt(tuple((
+                    multispace1,
+                    tag_no_case("if"),
+                    multispace1,
+                    tag_no_case("exists")
+                ))),
+                preceded(multispace1, identifier.context("Table Name"))
+            ))
+        )(input)?;
+
+        Ok((remaining_input, DropStatement { table, if_exists: if_exists.is_some() }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drop() {
+        assert_eq!(
+            DropStatement::parse_from_raw("DROP TABLE foo").unwrap().1,
+            DropStatement { table: "foo".into(), if_exists: false }
+        );
+        assert_eq!(
+            DropStatement::parse_from_raw("drop table if exists foo").unwrap().1,
+            DropStatement { table: "foo".into(), if_exists: true }
+        );
+    }
+}
@@ -0,0 +1,58 @@
+use nom::{
+    bytes::complete::tag_no_case,
+    character::complete::multispace1,
+    combinator::opt,
+    error::context,
+    sequence::{preceded, tuple}
+};
+use nom_supreme::ParserExt;
+use serde::{Serialize, Deserialize};
+use crate::commands::SelectStatement;
+use crate::parse::{keyword, Parse, ParseResult, RawSpan};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExplainStatement {
+    /// 带有 `ANALYZE` 时实际执行查询，并统计每个算子输出的行数和耗时
+    pub analyze: bool,
+    pub query: SelectStatement
+}
+
+impl<'a> Parse<'a> for ExplainStatement {
+    /// `EXPLAIN [ANALYZE] <select>`
+    fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
+        let (remaining_input, (_, analyze, query)) = context(
+            "Explain Statement",
+            tuple((
+                tag_no_case("explain"),
+                opt(preceded(multispace1, keyword("analyze"))),
+                preceded(multispace1, SelectStatement::parse.context("Query"))
+            ))
+        )(input)?;
+
+        Ok((remaining_input, ExplainStatement { analyze: analyze.is_some(), query }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::{FromClause, SelectItem};
+
+    #[test]
+    fn test_explain() {
+        let query = SelectStatement {
+            from: FromClause::single("t1"),
+            fields: vec![SelectItem::column("foo")],
+            ..Default::default()
+        };
+        assert_eq!(
+            ExplainStatement::parse_from_raw("EXPLAIN SELECT foo FROM t1").unwrap().1,
+            ExplainStatement { analyze: false, query: query.clone() }
+        );
+        assert_eq!(
+            ExplainStatement::parse_from_raw("explain analyze select foo from t1").unwrap().1,
+            ExplainStatement { analyze: true, query }
+        );
+        assert!(ExplainStatement::parse_from_raw("EXPLAIN DROP TABLE t1").is_err());
+    }
+}
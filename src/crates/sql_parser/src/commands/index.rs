@@ -0,0 +1,131 @@
+use nom::{
+    branch::alt,
+    bytes::complete::tag_no_case,
+    character::complete::{char, multispace0, multispace1},
+    combinator::{map, opt},
+    error::context,
+    sequence::{pair, preceded, terminated, tuple}
+};
+use nom_supreme::ParserExt;
+use serde::{Serialize, Deserialize};
+use crate::parse::{comma_sep, identifier, keyword, Parse, ParseResult, RawSpan};
+
+/// 索引的存储结构，默认为有序索引
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum IndexKind {
+    /// 有序索引，支持等值和范围查找
+    #[default]
+    BTree,
+    /// 哈希索引，只支持所有索引列都取等值的查找
+    Hash
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CreateIndexStatement {
+    pub name: String,
+    pub table: String,
+    /// 索引列，按顺序组成索引键
+    pub columns: Vec<String>,
+    /// 带有 `UNIQUE` 时不允许两行的索引键相同
+    pub unique: bool,
+    pub kind: IndexKind
+}
+
+/// parses `USING BTREE` | `USING HASH`
+fn index_kind(input: RawSpan<'_>) -> ParseResult<'_, IndexKind> {
+    preceded(
+        pair(keyword("using"), multispace1),
+        alt((
+            map(keyword("btree"), |_| IndexKind::BTree),
+            map(keyword("hash"), |_| IndexKind::Hash)
+        )).context("Index Kind")
+    )(input)
+}
+
+impl<'a> Parse<'a> for CreateIndexStatement {
+    /// `CREATE [UNIQUE] INDEX name ON foo [USING BTREE | HASH] (col, ...)`
+    fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
+        let (remaining_input, (_, unique, _, name, _, table, kind, _, columns, _)) = context(
+            "Create Index",
+            tuple((
+                tag_no_case("create"),
+                opt(preceded(multispace1, keyword("unique"))),
+                preceded(multispace1, keyword("index")),
+                preceded(multispace1, identifier.context("Index Name")),
+                preceded(multispace1, keyword("on")),
+                preceded(multispace1, identifier.context("Table Name")),
+                opt(preceded(multispace1, index_kind)),
+                tuple((multispace0, char('('), multispace0)),
+                comma_sep(identifier.context("Column Name")),
+                preceded(multispace0, char(')'))
+            ))
+        )(input)?;
+
+        Ok((remaining_input, CreateIndexStatement {
+            name,
+            table,
+            columns,
+            unique: unique.is_some(),
+            kind: kind.unwrap_or_default()
+        }))
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DropIndexStatement {
+    pub name: String,
+    /// 带有 `IF EXISTS` 时，索引不存在不视为错误
+    pub if_exists: bool
+}
+
+impl<'a> Parse<'a> for DropIndexStatement {
+    /// `DROP INDEX [IF EXISTS] name`
+    fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
+        let (remaining_input, (_, _, if_exists, name)) = context(
+            "Drop Index",
+            tuple((
+                tag_no_case("drop"),
+                preceded(multispace1, keyword("index")),
+                opt(preceded(multispace1, terminated(keyword("if"), tuple((multispace1, keyword("exists")))))),
+                preceded(multispace1, identifier.context("Index Name"))
+            ))
+        )(input)?;
+
+        Ok((remaining_input, DropIndexStatement { name, if_exists: if_exists.is_some() }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_index() {
+        assert_eq!(
+            CreateIndexStatement::parse_from_raw("CREATE INDEX byage ON users (age)").unwrap().1,
+            CreateIndexStatement { name: "byage".into(), table: "users".into(), columns: vec!["age".into()], unique: false, kind: IndexKind::BTree }
+        );
+        assert_eq!(
+            CreateIndexStatement::parse_from_raw("create unique index pair on t(a, b)").unwrap().1,
+            CreateIndexStatement { name: "pair".into(), table: "t".into(), columns: vec!["a".into(), "b".into()], unique: true, kind: IndexKind::BTree }
+        );
+        assert_eq!(
+            CreateIndexStatement::parse_from_raw("CREATE INDEX byuuid ON users USING HASH (uuid)").unwrap().1,
+            CreateIndexStatement { name: "byuuid".into(), table: "users".into(), columns: vec!["uuid".into()], unique: false, kind: IndexKind::Hash }
+        );
+        assert!(CreateIndexStatement::parse_from_raw("CREATE INDEX byage ON users USING gist (age)").is_err());
+        assert!(CreateIndexStatement::parse_from_raw("CREATE INDEX byage ON users ()").is_err());
+    }
+
+    #[test]
+    fn test_drop_index() {
+        assert_eq!(
+            DropIndexStatement::parse_from_raw("DROP INDEX byage").unwrap().1,
+            DropIndexStatement { name: "byage".into(), if_exists: false }
+        );
+        assert_eq!(
+            DropIndexStatement::parse_from_raw("drop index if exists byage").unwrap().1,
+            DropIndexStatement { name: "byage".into(), if_exists: true }
+        );
+    }
+}
@@ -1,32 +1,140 @@
 use nom::{
+    branch::alt,
     bytes::complete::tag_no_case,
-    character::complete::multispace1,
+    character::complete::{char, multispace0, multispace1},
+    combinator::{map, opt},
     error::context,
-    sequence::{preceded, tuple}
+    sequence::{delimited, preceded, tuple}
 };
 use nom_supreme::ParserExt;
 use serde::{Serialize, Deserialize};
+use crate::expr::{Expr, Literal};
 use crate::parse::{comma_sep, identifier, Parse, ParseResult, RawSpan};
 
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct InsertStatement {
     pub table: String,
-    pub values: Vec<String>
+    /// 显式给出的列，`None` 表示按建表顺序的所有列
+    pub columns: Option<Vec<String>>,
+    /// 每个元素是一行，值是不引用列的表达式，如 `1 + 2` 或 `nextval('s')`
+    pub values: Vec<Vec<Expr>>
+}
+
+/// parses `(a, b)`
+fn column_list(input: RawSpan<'_>) -> ParseResult<'_, Vec<String>> {
+    delimited(
+        tuple((char('('), multispace0)),
+        comma_sep(identifier.context("Column Name")),
+        tuple((multispace0, char(')')))
+    )(input)
+}
+
+/// parses `(1, 'a', NULL)`
+fn values_tuple(input: RawSpan<'_>) -> ParseResult<'_, Vec<Expr>> {
+    delimited(
+        tuple((char('('), multispace0)),
+        comma_sep(Expr::parse),
+        tuple((multispace0, char(')')))
+    )(input)
+}
+
+/// parses `(1, 'a'), (2, 'b')`，也兼容不带括号的单行 `1, 'a'`
+///
+/// 不带括号的写法只接受字面量，否则 `(1 + 2) * 3, 4` 开头的括号会被当作一行
+fn values_list(input: RawSpan<'_>) -> ParseResult<'_, Vec<Vec<Expr>>> {
+    alt((
+        comma_sep(values_tuple),
+        map(comma_sep(Literal::parse), |row| vec![row.into_iter().map(Expr::Literal).collect()])
+    ))(input)
 }
 
 impl<'a> Parse<'a> for InsertStatement {
+    /// `INSERT INTO foo [(col1, col2)] VALUES (1, 2) [, (3, 4) ...]`
     fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
-        let (remaining_input, (_, _, table, _, values)) = context(
+        let (remaining_input, (_, _, table, columns, _, values)) = context(
             "Insert statement",
             tuple((
                 tag_no_case("insert"),
                 preceded(multispace1, tag_no_case("into")),
                 preceded(multispace1, identifier.context("Table Name")),
-                preceded(multispace1, tag_no_case("values")),
-                preceded(multispace1, comma_sep(identifier).context("Values"))
+                opt(preceded(multispace0, column_list.context("Insert Columns"))),
+                preceded(multispace0, tag_no_case("values")),
+                preceded(multispace0, values_list.context("Values"))
             ))
         )(input)?;
 
-        Ok((remaining_input, InsertStatement { table, values }))
+        Ok((remaining_input, InsertStatement { table, columns, values }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::BinaryOp;
+
+    #[test]
+    fn test_insert() {
+        let expected = InsertStatement {
+            table: "foo".into(),
+            columns: None,
+            values: vec![vec![
+                Expr::Literal(Literal::String("hello world".into())),
+                Expr::Literal(Literal::Int(-5)),
+                Expr::Literal(Literal::Float(1.5)),
+                Expr::Literal(Literal::Null)
+            ]]
+        };
+
+        assert_eq!(
+            InsertStatement::parse_from_raw("INSERT INTO foo VALUES ('hello world', -5, 1.5, NULL)")
+                .unwrap()
+                .1,
+            expected
+        );
+        assert_eq!(
+            InsertStatement::parse_from_raw("INSERT INTO foo VALUES 'hello world', -5, 1.5, null")
+                .unwrap()
+                .1,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_insert_columns_multi_row() {
+        let expected = InsertStatement {
+            table: "foo".into(),
+            columns: Some(vec!["b".into(), "a".into()]),
+            values: vec![
+                vec![Expr::Literal(Literal::Int(1)), Expr::Literal(Literal::Int(2))],
+                vec![Expr::Literal(Literal::Int(3)), Expr::Literal(Literal::Int(4))]
+            ]
+        };
+
+        assert_eq!(
+            InsertStatement::parse_from_raw("INSERT INTO foo (b, a) VALUES (1, 2), (3, 4)")
+                .unwrap()
+                .1,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_insert_expressions() {
+        let (_, insert) = InsertStatement::parse_from_raw("INSERT INTO foo VALUES (nextval('s'), 1 + 2)").unwrap();
+        assert_eq!(insert.values, vec![vec![
+            Expr::NextVal("s".into()),
+            Expr::Binary {
+                left: Box::new(Expr::Literal(Literal::Int(1))),
+                op: BinaryOp::Plus,
+                right: Box::new(Expr::Literal(Literal::Int(2)))
+            }
+        ]]);
+
+        // 不带括号的单行只能是字面量
+        for sql in ["INSERT INTO foo VALUES (1 + 2) * 3, 4", "INSERT INTO foo VALUES 1 + 2, 3"] {
+            assert!(InsertStatement::parse_format_error(sql).is_err(), "{sql}");
+        }
+        let (_, insert) = InsertStatement::parse_from_raw("INSERT INTO foo VALUES ((1 + 2) * 3, 4)").unwrap();
+        assert_eq!(insert.values[0].len(), 2);
     }
 }
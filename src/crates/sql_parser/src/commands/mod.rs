@@ -1,7 +1,23 @@
+mod analyze;
 mod create;
+mod delete;
+mod drop;
+mod explain;
+mod index;
 mod insert;
 mod select;
+mod sequence;
+mod truncate;
+mod update;
 
-pub use create::{SqlTypeInfo, CreateStatement, Column};
+pub use analyze::{AnalyzeStatement};
+pub use create::{SqlTypeInfo, CreateStatement, Column, ColumnConstraint, TableConstraint, Reference, ReferentialAction};
+pub use delete::{DeleteStatement};
+pub use drop::{DropStatement};
+pub use explain::{ExplainStatement};
+pub use index::{CreateIndexStatement, DropIndexStatement, IndexKind};
 pub use insert::{InsertStatement};
-pub use select::{SelectStatement};
+pub use select::{SelectStatement, SelectItem, OrderByItem, SortDirection, FromClause, TableRef, Join, JoinKind};
+pub use sequence::{CreateSequenceStatement, DropSequenceStatement};
+pub use truncate::{TruncateStatement};
+pub use update::{UpdateStatement, Assignment};
@@ -1,37 +1,348 @@
 use nom::{
-    sequence::{ tuple },
+    sequence::{ pair, preceded, tuple },
+    branch::alt,
     bytes::complete::tag_no_case,
-    character::complete::multispace1,
+    character::complete::{char, digit1, multispace0, multispace1},
+    combinator::{map, map_res, opt, verify},
     error::context,
+    multi::many0,
 };
 use nom_supreme::ParserExt;
-use crate::parse::{comma_sep, identifier, Parse, ParseResult, RawSpan};
+use crate::expr::{where_clause, ColumnRef, Expr};
+use crate::parse::{comma_sep, identifier, keyword, Parse, ParseResult, RawSpan};
 use serde::{Serialize, Deserialize};
 
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+/// 投影列表中的一项
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SelectItem {
+    /// `*`，按建表顺序展开所有列
+    Wildcard,
+    /// `t.*`，只展开一张表的列
+    QualifiedWildcard(String),
+    /// `<expr> [AS <alias>]`
+    Expr {
+        expr: Expr,
+        alias: Option<String>
+    }
+}
+
+impl SelectItem {
+    /// 只引用一列、没有别名的投影项
+    pub fn column(name: impl Into<String>) -> Self {
+        SelectItem::Expr { expr: Expr::Column(ColumnRef::from(name.into())), alias: None }
+    }
+}
+
+/// parses `*` | `<table>.*` | `<expr> [AS <alias>]`
+impl<'a> Parse<'a> for SelectItem {
+    fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
+        context(
+            "Select Item",
+            alt((
+                map(char('*'), |_| SelectItem::Wildcard),
+                map(
+                    tuple((identifier, char('.'), char('*'))),
+                    |(table, _, _)| SelectItem::QualifiedWildcard(table)
+                ),
+                map(
+                    tuple((
+                        Expr::parse,
+                        opt(preceded(
+                            tuple((multispace1, keyword("as"), multispace1)),
+                            identifier.context("Alias")
+                        ))
+                    )),
+                    |(expr, alias)| SelectItem::Expr { expr, alias }
+                )
+            ))
+        )(input)
+    }
+}
+
+/// 不能作为不带 `AS` 的表别名的关键字，它们可能紧跟在表名后面
+const RESERVED: &[&str] = &[
+    "where", "group", "having", "order", "limit", "offset",
+    "join", "inner", "left", "right", "full", "outer", "cross", "on"
+];
+
+/// `FROM` 中的一张表
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TableRef {
+    pub name: String,
+    pub alias: Option<String>
+}
+
+impl TableRef {
+    pub fn new(name: impl Into<String>) -> Self {
+        TableRef { name: name.into(), alias: None }
+    }
+
+    /// 查询中引用这张表的列时使用的名字：有别名时为别名，否则为表名
+    pub fn qualifier(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// parses `<table> [[AS] <alias>]`
+impl<'a> Parse<'a> for TableRef {
+    fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
+        map(
+            tuple((
+                identifier.context("Table Name"),
+                opt(preceded(
+                    multispace1,
+                    alt((
+                        preceded(pair(keyword("as"), multispace1), identifier.context("Table Alias")),
+                        verify(identifier, |alias: &String| {
+                            !RESERVED.iter().any(|kw| kw.eq_ignore_ascii_case(alias))
+                        })
+                    ))
+                ))
+            )),
+            |(name, alias)| TableRef { name, alias }
+        )(input)
+    }
+}
+
+/// 连接类型，逗号连接等价于 `CROSS JOIN`
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum JoinKind {
+    Inner,
+    Left,
+    Right,
+    Full,
+    Cross
+}
+
+/// 连接到左侧所有表上的一张表
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Join {
+    pub kind: JoinKind,
+    pub table: TableRef,
+    /// 只有 `CROSS JOIN` 没有连接条件
+    pub on: Option<Expr>
+}
+
+/// parses `[INNER] JOIN` | `LEFT [OUTER] JOIN` | `RIGHT [OUTER] JOIN` | `FULL [OUTER] JOIN`
+fn join_kind(input: RawSpan<'_>) -> ParseResult<'_, JoinKind> {
+    let outer = || opt(pair(keyword("outer"), multispace1));
+    alt((
+        map(keyword("join"), |_| JoinKind::Inner),
+        map(tuple((keyword("inner"), multispace1, keyword("join"))), |_| JoinKind::Inner),
+        map(tuple((keyword("left"), multispace1, outer(), keyword("join"))), |_| JoinKind::Left),
+        map(tuple((keyword("right"), multispace1, outer(), keyword("join"))), |_| JoinKind::Right),
+        map(tuple((keyword("full"), multispace1, outer(), keyword("join"))), |_| JoinKind::Full)
+    ))(input)
+}
+
+/// 解析一个连接，包括前面的空白或逗号
+fn join(input: RawSpan<'_>) -> ParseResult<'_, Join> {
+    alt((
+        map(
+            preceded(tuple((multispace0, char(','), multispace0)), TableRef::parse),
+            |table| Join { kind: JoinKind::Cross, table, on: None }
+        ),
+        map(
+            preceded(
+                tuple((multispace1, keyword("cross"), multispace1, keyword("join"), multispace1)),
+                TableRef::parse
+            ),
+            |table| Join { kind: JoinKind::Cross, table, on: None }
+        ),
+        map(
+            tuple((
+                preceded(multispace1, join_kind),
+                preceded(multispace1, TableRef::parse),
+                preceded(
+                    tuple((multispace1, keyword("on"), multispace1)),
+                    Expr::parse.context("Join Condition")
+                )
+            )),
+            |(kind, table, on)| Join { kind, table, on: Some(on) }
+        )
+    ))(input)
+}
+
+/// `FROM` 子句：第一张表和按顺序向左结合的连接
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FromClause {
+    pub table: TableRef,
+    pub joins: Vec<Join>
+}
+
+impl FromClause {
+    /// 只有一张表、没有别名的 `FROM`
+    pub fn single(table: impl Into<String>) -> Self {
+        FromClause { table: TableRef::new(table), joins: Vec::new() }
+    }
+
+    /// 按出现顺序列出所有表
+    pub fn tables(&self) -> impl Iterator<Item = &TableRef> {
+        std::iter::once(&self.table).chain(self.joins.iter().map(|join| &join.table))
+    }
+}
+
+/// parses `<table> [<join> ...]`
+impl<'a> Parse<'a> for FromClause {
+    fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
+        map(
+            pair(TableRef::parse, many0(join)),
+            |(table, joins)| FromClause { table, joins }
+        )(input)
+    }
+}
+
+/// 排序方向
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc
+}
+
+/// `ORDER BY` 中的一项
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OrderByItem {
+    pub expr: Expr,
+    pub direction: SortDirection,
+    /// `NULLS FIRST` 为 `Some(true)`，`NULLS LAST` 为 `Some(false)`，未指定为 `None`
+    pub nulls_first: Option<bool>
+}
+
+impl OrderByItem {
+    /// 未指定时 NULL 视为最大值：升序时排在最后，降序时排在最前
+    pub fn nulls_first(&self) -> bool {
+        self.nulls_first.unwrap_or(self.direction == SortDirection::Desc)
+    }
+}
+
+/// parses `<expr> [ASC | DESC] [NULLS FIRST | NULLS LAST]`
+impl<'a> Parse<'a> for OrderByItem {
+    fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
+        let (remaining_input, (expr, direction, nulls_first)) = context(
+            "Order By Item",
+            tuple((
+                Expr::parse,
+                opt(preceded(
+                    multispace1,
+                    alt((
+                        map(keyword("asc"), |_| SortDirection::Asc),
+                        map(keyword("desc"), |_| SortDirection::Desc)
+                    ))
+                )),
+                opt(preceded(
+                    tuple((multispace1, keyword("nulls"), multispace1)),
+                    alt((
+                        map(keyword("first"), |_| true),
+                        map(keyword("last"), |_| false)
+                    ))
+                ))
+            ))
+        )(input)?;
+
+        Ok((remaining_input, OrderByItem {
+            expr,
+            direction: direction.unwrap_or_default(),
+            nulls_first
+        }))
+    }
+}
+
+/// 解析 ` ORDER BY <item>, ...`，包括关键字前面的空白
+fn order_by_clause(input: RawSpan<'_>) -> ParseResult<'_, Vec<OrderByItem>> {
+    preceded(
+        tuple((multispace1, keyword("order"), multispace1, keyword("by"), multispace1)),
+        comma_sep(OrderByItem::parse).context("Order By")
+    )(input)
+}
+
+/// 非负整数，用于 `LIMIT` 和 `OFFSET`
+fn count(input: RawSpan<'_>) -> ParseResult<'_, usize> {
+    map_res(digit1, |s: RawSpan| s.fragment().parse::<usize>())(input)
+}
+
+/// 解析 ` LIMIT <n> [OFFSET <m>]`，包括关键字前面的空白
+fn limit_clause(input: RawSpan<'_>) -> ParseResult<'_, (usize, Option<usize>)> {
+    tuple((
+        preceded(
+            tuple((multispace1, keyword("limit"), multispace1)),
+            count.context("Limit")
+        ),
+        opt(preceded(
+            tuple((multispace1, keyword("offset"), multispace1)),
+            count.context("Offset")
+        ))
+    ))(input)
+}
+
+/// 解析 ` GROUP BY <expr>, ...`，包括关键字前面的空白
+fn group_by_clause(input: RawSpan<'_>) -> ParseResult<'_, Vec<Expr>> {
+    preceded(
+        tuple((multispace1, keyword("group"), multispace1, keyword("by"), multispace1)),
+        comma_sep(Expr::parse).context("Group By")
+    )(input)
+}
+
+/// 解析 ` HAVING <expr>`，包括关键字前面的空白
+fn having_clause(input: RawSpan<'_>) -> ParseResult<'_, Expr> {
+    preceded(
+        tuple((multispace1, keyword("having"), multispace1)),
+        Expr::parse.context("Having")
+    )(input)
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct SelectStatement {
-    pub table: String,
-    pub fields: Vec<String>
+    pub from: FromClause,
+    pub fields: Vec<SelectItem>,
+    pub where_clause: Option<Expr>,
+    pub group_by: Vec<Expr>,
+    pub having: Option<Expr>,
+    pub order_by: Vec<OrderByItem>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>
 }
 
 impl<'a> Parse<'a> for SelectStatement {
-    /// `SELECT col1, col2 FROM foo`
+    /// `SELECT <items> FROM <table> [<join> ...] [WHERE <expr>] [GROUP BY <expr>, ...] [HAVING <expr>]
+    /// [ORDER BY <expr> [ASC | DESC], ...] [LIMIT n [OFFSET m]]`
     fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
-        let (remaining_input, (_, _, fields, _, _, _, table)) = context(
+        let (
+            remaining_input,
+            (_, _, fields, _, _, _, from, where_clause, group_by, having, order_by, limit)
+        ) = context(
             "Select Statement",
             tuple((
                 tag_no_case("select"),
                 multispace1,
                 // 使用逗号分割标识符
-                comma_sep(identifier).context("Select Columns"),
+                comma_sep(SelectItem::parse).context("Select Columns"),
                 multispace1,
                 tag_no_case("from"),
                 multispace1,
-                identifier.context("From Table")
+                FromClause::parse.context("From"),
+                opt(where_clause),
+                opt(group_by_clause),
+                opt(having_clause),
+                opt(order_by_clause),
+                opt(limit_clause)
             ))
         )(input)?;
 
-        Ok((remaining_input, SelectStatement { fields, table }))
+        let (limit, offset) = match limit {
+            Some((limit, offset)) => (Some(limit), offset),
+            None => (None, None)
+        };
+        Ok((remaining_input, SelectStatement {
+            fields,
+            from,
+            where_clause,
+            group_by: group_by.unwrap_or_default(),
+            having,
+            order_by: order_by.unwrap_or_default(),
+            limit,
+            offset
+        }))
     }
 }
 
@@ -39,13 +350,15 @@ impl<'a> Parse<'a> for SelectStatement {
 mod tests {
 
     use super::*;
+    use crate::expr::{AggregateFunction, BinaryOp, ColumnRef, Literal};
 
     #[test]
     fn test_select() {
 
         let select_statement = SelectStatement {
-            table: "t1".into(),
-            fields: vec!["foo".into(), "bar".into()]
+            from: FromClause::single("t1"),
+            fields: vec![SelectItem::column("foo"), SelectItem::column("bar")],
+            ..Default::default()
         };
 
         assert_eq!(
@@ -55,4 +368,175 @@ mod tests {
 
     }
 
+    #[test]
+    fn test_select_wildcard() {
+
+        let select_statement = SelectStatement {
+            from: FromClause::single("t1"),
+            fields: vec![SelectItem::Wildcard],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            select_statement,
+            SelectStatement::parse_from_raw("SELECT * FROM t1").unwrap().1
+        );
+
+    }
+
+    #[test]
+    fn test_select_where() {
+
+        let select_statement = SelectStatement {
+            from: FromClause::single("t1"),
+            fields: vec![SelectItem::column("foo")],
+            where_clause: Some(Expr::Binary {
+                left: Box::new(Expr::Column("foo".into())),
+                op: BinaryOp::Gt,
+                right: Box::new(Expr::Literal(Literal::Int(1)))
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            select_statement,
+            SelectStatement::parse_from_raw("SELECT foo FROM t1 WHERE foo > 1").unwrap().1
+        );
+
+    }
+
+    #[test]
+    fn test_select_order_by() {
+
+        let select_statement = SelectStatement {
+            from: FromClause::single("t1"),
+            fields: vec![SelectItem::Wildcard],
+            order_by: vec![
+                OrderByItem {
+                    expr: Expr::Column("a".into()),
+                    direction: SortDirection::Desc,
+                    nulls_first: Some(false)
+                },
+                OrderByItem {
+                    expr: Expr::Column("b".into()),
+                    direction: SortDirection::Asc,
+                    nulls_first: None
+                }
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            select_statement,
+            SelectStatement::parse_from_raw("SELECT * FROM t1 ORDER BY a DESC NULLS LAST, b").unwrap().1
+        );
+
+    }
+
+    #[test]
+    fn test_select_limit() {
+
+        let select_statement = SelectStatement {
+            from: FromClause::single("t1"),
+            fields: vec![SelectItem::Wildcard],
+            limit: Some(10),
+            offset: Some(20),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            select_statement,
+            SelectStatement::parse_from_raw("SELECT * FROM t1 LIMIT 10 OFFSET 20").unwrap().1
+        );
+        assert_eq!(
+            SelectStatement::parse_from_raw("SELECT * FROM t1 ORDER BY a LIMIT 5").unwrap().1.limit,
+            Some(5)
+        );
+
+    }
+
+    #[test]
+    fn test_select_group_by() {
+
+        let count = Expr::Aggregate { func: AggregateFunction::Count, arg: None };
+        let select_statement = SelectStatement {
+            from: FromClause::single("t1"),
+            fields: vec![
+                SelectItem::column("a"),
+                SelectItem::Expr { expr: count.clone(), alias: Some("total".into()) }
+            ],
+            group_by: vec![Expr::Column("a".into())],
+            having: Some(Expr::Binary {
+                left: Box::new(count),
+                op: BinaryOp::Gt,
+                right: Box::new(Expr::Literal(Literal::Int(1)))
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            select_statement,
+            SelectStatement::parse_from_raw("SELECT a, COUNT(*) AS total FROM t1 GROUP BY a HAVING COUNT(*) > 1")
+                .unwrap()
+                .1
+        );
+
+    }
+
+    #[test]
+    fn test_select_join() {
+
+        let on = |left: ColumnRef, right: ColumnRef| Expr::Binary {
+            left: Box::new(Expr::Column(left)),
+            op: BinaryOp::Eq,
+            right: Box::new(Expr::Column(right))
+        };
+        let select_statement = SelectStatement {
+            from: FromClause {
+                table: TableRef { name: "users".into(), alias: Some("u".into()) },
+                joins: vec![
+                    Join {
+                        kind: JoinKind::Left,
+                        table: TableRef { name: "orders".into(), alias: Some("o".into()) },
+                        on: Some(on(ColumnRef::qualified("u", "id"), ColumnRef::qualified("o", "uid")))
+                    },
+                    Join {
+                        kind: JoinKind::Cross,
+                        table: TableRef::new("tags"),
+                        on: None
+                    }
+                ]
+            },
+            fields: vec![
+                SelectItem::QualifiedWildcard("u".into()),
+                SelectItem::Expr { expr: Expr::Column(ColumnRef::qualified("o", "total")), alias: None }
+            ],
+            where_clause: Some(on("id".into(), ColumnRef::qualified("tags", "uid"))),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            select_statement,
+            SelectStatement::parse_from_raw(
+                "SELECT u.*, o.total FROM users AS u LEFT OUTER JOIN orders o ON u.id = o.uid, tags WHERE id = tags.uid"
+            ).unwrap().1
+        );
+
+        let kinds = |sql| SelectStatement::parse_from_raw(sql).unwrap().1.from.joins
+            .into_iter()
+            .map(|join| join.kind)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds("SELECT * FROM a JOIN b ON a.x = b.x INNER JOIN c ON b.y = c.y RIGHT JOIN d ON d.z = c.z"),
+            vec![JoinKind::Inner, JoinKind::Inner, JoinKind::Right]
+        );
+        assert_eq!(
+            kinds("SELECT * FROM a FULL JOIN b ON a.x = b.x CROSS JOIN c"),
+            vec![JoinKind::Full, JoinKind::Cross]
+        );
+        // 内连接必须有连接条件
+        assert!(SelectStatement::parse_format_error("SELECT * FROM a JOIN b").is_err());
+
+    }
+
 }
@@ -0,0 +1,126 @@
+use nom::{
+    branch::alt,
+    bytes::complete::tag_no_case,
+    character::complete::{i64, multispace1},
+    combinator::{map, opt},
+    error::context,
+    multi::many0,
+    sequence::{preceded, terminated, tuple}
+};
+use nom_supreme::ParserExt;
+use serde::{Serialize, Deserialize};
+use crate::parse::{identifier, keyword, Parse, ParseResult, RawSpan};
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CreateSequenceStatement {
+    pub name: String,
+    /// 第一个取出的值，默认为 1
+    pub start: i64,
+    /// 相邻两个值的差，默认为 1
+    pub increment: i64
+}
+
+/// `START [WITH] n` 或 `INCREMENT [BY] n`
+enum SequenceOption {
+    Start(i64),
+    Increment(i64)
+}
+
+fn sequence_option(input: RawSpan<'_>) -> ParseResult<'_, SequenceOption> {
+    context(
+        "Sequence Option",
+        alt((
+            map(
+                preceded(
+                    tuple((keyword("start"), opt(preceded(multispace1, keyword("with"))), multispace1)),
+                    i64.context("Start Value")
+                ),
+                SequenceOption::Start
+            ),
+            map(
+                preceded(
+                    tuple((keyword("increment"), opt(preceded(multispace1, keyword("by"))), multispace1)),
+                    i64.context("Increment")
+                ),
+                SequenceOption::Increment
+            )
+        ))
+    )(input)
+}
+
+impl<'a> Parse<'a> for CreateSequenceStatement {
+    /// `CREATE SEQUENCE name [START [WITH] n] [INCREMENT [BY] n]`，选项的顺序不限
+    fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
+        let (remaining_input, (_, _, name, options)) = context(
+            "Create Sequence",
+            tuple((
+                tag_no_case("create"),
+                preceded(multispace1, keyword("sequence")),
+                preceded(multispace1, identifier.context("Sequence Name")),
+                many0(preceded(multispace1, sequence_option))
+            ))
+        )(input)?;
+
+        let mut statement = CreateSequenceStatement { name, start: 1, increment: 1 };
+        for option in options {
+            match option {
+                SequenceOption::Start(start) => statement.start = start,
+                SequenceOption::Increment(increment) => statement.increment = increment
+            }
+        }
+        Ok((remaining_input, statement))
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DropSequenceStatement {
+    pub name: String,
+    /// 带有 `IF EXISTS` 时，序列不存在不视为错误
+    pub if_exists: bool
+}
+
+impl<'a> Parse<'a> for DropSequenceStatement {
+    /// `DROP SEQUENCE [IF EXISTS] name`
+    fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
+        let (remaining_input, (_, _, if_exists, name)) = context(
+            "Drop Sequence",
+            tuple((
+                tag_no_case("drop"),
+                preceded(multispace1, keyword("sequence")),
+                opt(preceded(multispace1, terminated(keyword("if"), tuple((multispace1, keyword("exists")))))),
+                preceded(multispace1, identifier.context("Sequence Name"))
+            ))
+        )(input)?;
+
+        Ok((remaining_input, DropSequenceStatement { name, if_exists: if_exists.is_some() }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_sequence() {
+        assert_eq!(
+            CreateSequenceStatement::parse_from_raw("CREATE SEQUENCE ids").unwrap().1,
+            CreateSequenceStatement { name: "ids".into(), start: 1, increment: 1 }
+        );
+        assert_eq!(
+            CreateSequenceStatement::parse_from_raw("create sequence ids increment by 10 start with -5").unwrap().1,
+            CreateSequenceStatement { name: "ids".into(), start: -5, increment: 10 }
+        );
+        assert_eq!(
+            CreateSequenceStatement::parse_from_raw("CREATE SEQUENCE ids START 100").unwrap().1,
+            CreateSequenceStatement { name: "ids".into(), start: 100, increment: 1 }
+        );
+    }
+
+    #[test]
+    fn test_drop_sequence() {
+        assert_eq!(
+            DropSequenceStatement::parse_from_raw("DROP SEQUENCE IF EXISTS ids").unwrap().1,
+            DropSequenceStatement { name: "ids".into(), if_exists: true }
+        );
+    }
+}
@@ -0,0 +1,30 @@
+use nom::{
+    bytes::complete::tag_no_case,
+    character::complete::multispace1,
+    error::context,
+    sequence::{preceded, tuple}
+};
+use nom_supreme::ParserExt;
+use serde::{Serialize, Deserialize};
+use crate::parse::{identifier, Parse, ParseResult, RawSpan};
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TruncateStatement {
+    pub table: String
+}
+
+impl<'a> Parse<'a> for TruncateStatement {
+    /// `TRUNCATE TABLE foo`
+    fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
+        let (remaining_input, (_, _, table)) = context(
+            "Truncate Statement",
+            tuple((
+                tag_no_case("truncate"),
+                preceded(multispace1, tag_no_case("table")),
+                preceded(multispace1, identifier.context("Table Name"))
+            ))
+        )(input)?;
+
+        Ok((remaining_input, TruncateStatement { table }))
+    }
+}
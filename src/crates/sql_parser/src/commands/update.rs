@@ -0,0 +1,96 @@
+use nom::{
+    bytes::complete::tag_no_case,
+    character::complete::{char, multispace0, multispace1},
+    combinator::{map, opt},
+    error::context,
+    sequence::{preceded, separated_pair, tuple}
+};
+use nom_supreme::ParserExt;
+use serde::{Serialize, Deserialize};
+use crate::expr::{where_clause, Expr};
+use crate::parse::{comma_sep, identifier, Parse, ParseResult, RawSpan};
+
+/// `SET` 中的一项：`<column> = <expr>`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Assignment {
+    pub column: String,
+    pub value: Expr
+}
+
+impl<'a> Parse<'a> for Assignment {
+    fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
+        context(
+            "Assignment",
+            map(
+                separated_pair(
+                    identifier.context("Column Name"),
+                    tuple((multispace0, char('='), multispace0)),
+                    Expr::parse
+                ),
+                |(column, value)| Self { column, value }
+            )
+        )(input)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UpdateStatement {
+    pub table: String,
+    pub assignments: Vec<Assignment>,
+    pub where_clause: Option<Expr>
+}
+
+impl<'a> Parse<'a> for UpdateStatement {
+    /// `UPDATE foo SET col1 = <expr> [, ...] [WHERE <expr>]`
+    fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
+        let (remaining_input, (_, table, _, assignments, where_clause)) = context(
+            "Update Statement",
+            tuple((
+                tag_no_case("update"),
+                preceded(multispace1, identifier.context("Table Name")),
+                preceded(multispace1, tag_no_case("set")),
+                preceded(multispace1, comma_sep(Assignment::parse).context("Assignments")),
+                opt(where_clause)
+            ))
+        )(input)?;
+
+        Ok((remaining_input, UpdateStatement { table, assignments, where_clause }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::{BinaryOp, Literal};
+
+    #[test]
+    fn test_update() {
+        let expected = UpdateStatement {
+            table: "foo".into(),
+            assignments: vec![
+                Assignment {
+                    column: "a".into(),
+                    value: Expr::Binary {
+                        left: Box::new(Expr::Column("a".into())),
+                        op: BinaryOp::Plus,
+                        right: Box::new(Expr::Literal(Literal::Int(1)))
+                    }
+                },
+                Assignment {
+                    column: "b".into(),
+                    value: Expr::Literal(Literal::String("x".into()))
+                }
+            ],
+            where_clause: Some(Expr::Binary {
+                left: Box::new(Expr::Column("a".into())),
+                op: BinaryOp::Lt,
+                right: Box::new(Expr::Literal(Literal::Int(10)))
+            })
+        };
+
+        assert_eq!(
+            UpdateStatement::parse_from_raw("UPDATE foo SET a = a + 1, b='x' WHERE a < 10").unwrap().1,
+            expected
+        );
+    }
+}
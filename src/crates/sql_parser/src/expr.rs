@@ -0,0 +1,790 @@
+//# 表达式语法，优先级从低到高：
+//# ```text
+//# expr       := or
+//# or         := and ( OR and )*
+//# and        := not ( AND not )*
+//# not        := NOT not | comparison
+//# comparison := additive ( ( = | <> | != | < | <= | > | >= ) additive | [ NOT ] IN '(' expr ( ',' expr )* ')' )?
+//# additive   := term ( ( + | - ) term )*
+//# term       := unary ( ( * | / | % ) unary )*
+//# unary      := primary | - unary
+//# primary    := '(' expr ')' | aggregate | nextval | literal | column
+//# column     := [ table '.' ] name
+//# aggregate  := COUNT '(' * ')' | ( COUNT | SUM | AVG | MIN | MAX ) '(' expr ')'
+//# nextval    := NEXTVAL '(' 'sequence' ')'
+//# literal    := NULL | TRUE | FALSE | [+-]digits[.digits] | 'string'
+//# ```
+
+use std::fmt;
+
+use nom::{
+    branch::alt,
+    bytes::complete::{is_not, tag},
+    character::complete::{char, digit1, multispace0, multispace1, one_of},
+    combinator::{map, map_res, opt, recognize, verify},
+    error::context,
+    multi::{fold_many0, many0},
+    sequence::{delimited, pair, preceded, tuple},
+};
+use nom_supreme::ParserExt;
+use serde::{Deserialize, Serialize};
+
+use crate::parse::{comma_sep, identifier, keyword, Parse, ParseResult, RawSpan};
+
+/// 字面量
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Literal {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+/// 一元运算符
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum UnaryOp {
+    Not,
+    Neg,
+}
+
+/// 二元运算符
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum BinaryOp {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    And,
+    Or,
+    Plus,
+    Minus,
+    Multiply,
+    Divide,
+    Modulo,
+}
+
+/// 聚合函数
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+/// 列引用，`table` 为表名或表的别名
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct ColumnRef {
+    pub table: Option<String>,
+    pub name: String,
+}
+
+impl ColumnRef {
+    /// 带表名限定的列引用，如 `a.id`
+    pub fn qualified(table: impl Into<String>, name: impl Into<String>) -> Self {
+        ColumnRef { table: Some(table.into()), name: name.into() }
+    }
+}
+
+impl From<&str> for ColumnRef {
+    fn from(name: &str) -> Self {
+        ColumnRef { table: None, name: name.to_owned() }
+    }
+}
+
+impl From<String> for ColumnRef {
+    fn from(name: String) -> Self {
+        ColumnRef { table: None, name }
+    }
+}
+
+/// 表达式树，括号只影响结构，不单独保存
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Expr {
+    Column(ColumnRef),
+    Literal(Literal),
+    /// `arg` 为 `None` 表示 `COUNT(*)`
+    Aggregate {
+        func: AggregateFunction,
+        arg: Option<Box<Expr>>,
+    },
+    Unary {
+        op: UnaryOp,
+        expr: Box<Expr>,
+    },
+    Binary {
+        left: Box<Expr>,
+        op: BinaryOp,
+        right: Box<Expr>,
+    },
+    /// `expr [NOT] IN (list)`
+    InList {
+        expr: Box<Expr>,
+        list: Vec<Expr>,
+        negated: bool,
+    },
+    /// `nextval('name')`，取出序列的下一个值
+    NextVal(String),
+}
+
+impl Expr {
+    fn binary(left: Expr, op: BinaryOp, right: Expr) -> Self {
+        Expr::Binary {
+            left: Box::new(left),
+            op,
+            right: Box::new(right),
+        }
+    }
+
+    /// 表达式中是否包含聚合函数
+    pub fn contains_aggregate(&self) -> bool {
+        match self {
+            Expr::Aggregate { .. } => true,
+            Expr::Column(_) | Expr::Literal(_) | Expr::NextVal(_) => false,
+            Expr::Unary { expr, .. } => expr.contains_aggregate(),
+            Expr::Binary { left, right, .. } => left.contains_aggregate() || right.contains_aggregate(),
+            Expr::InList { expr, list, .. } => expr.contains_aggregate() || list.iter().any(Expr::contains_aggregate),
+        }
+    }
+
+    /// 表达式中是否包含 `nextval`
+    pub fn contains_nextval(&self) -> bool {
+        match self {
+            Expr::NextVal(_) => true,
+            Expr::Column(_) | Expr::Literal(_) | Expr::Aggregate { arg: None, .. } => false,
+            Expr::Aggregate { arg: Some(expr), .. } | Expr::Unary { expr, .. } => expr.contains_nextval(),
+            Expr::Binary { left, right, .. } => left.contains_nextval() || right.contains_nextval(),
+            Expr::InList { expr, list, .. } => expr.contains_nextval() || list.iter().any(Expr::contains_nextval),
+        }
+    }
+
+    /// 二元运算和 `IN` 的绑定强度，其它表达式不需要加括号
+    fn precedence(&self) -> Option<u8> {
+        match self {
+            Expr::Binary { op, .. } => Some(op.precedence()),
+            Expr::InList { .. } => Some(BinaryOp::Eq.precedence()),
+            _ => None,
+        }
+    }
+}
+
+impl BinaryOp {
+    /// 绑定强度，数字越大越先结合
+    fn precedence(&self) -> u8 {
+        match self {
+            BinaryOp::Or => 1,
+            BinaryOp::And => 2,
+            BinaryOp::Eq | BinaryOp::NotEq | BinaryOp::Lt | BinaryOp::LtEq | BinaryOp::Gt | BinaryOp::GtEq => 4,
+            BinaryOp::Plus | BinaryOp::Minus => 5,
+            BinaryOp::Multiply | BinaryOp::Divide | BinaryOp::Modulo => 6,
+        }
+    }
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Literal::Null => write!(f, "NULL"),
+            Literal::Bool(true) => write!(f, "TRUE"),
+            Literal::Bool(false) => write!(f, "FALSE"),
+            Literal::Int(i) => write!(f, "{i}"),
+            Literal::Float(v) => write!(f, "{v:?}"),
+            Literal::String(s) => write!(f, "'{}'", s.replace('\'', "''")),
+        }
+    }
+}
+
+impl fmt::Display for ColumnRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.table {
+            Some(table) => write!(f, "{table}.{}", self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+impl fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self {
+            BinaryOp::Eq => "=",
+            BinaryOp::NotEq => "<>",
+            BinaryOp::Lt => "<",
+            BinaryOp::LtEq => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::GtEq => ">=",
+            BinaryOp::And => "AND",
+            BinaryOp::Or => "OR",
+            BinaryOp::Plus => "+",
+            BinaryOp::Minus => "-",
+            BinaryOp::Multiply => "*",
+            BinaryOp::Divide => "/",
+            BinaryOp::Modulo => "%",
+        };
+        write!(f, "{op}")
+    }
+}
+
+impl fmt::Display for AggregateFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AggregateFunction::Count => "COUNT",
+            AggregateFunction::Sum => "SUM",
+            AggregateFunction::Avg => "AVG",
+            AggregateFunction::Min => "MIN",
+            AggregateFunction::Max => "MAX",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// 输出能被重新解析为同一棵树的 SQL，只在需要时加括号
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Column(column) => write!(f, "{column}"),
+            Expr::Literal(literal) => write!(f, "{literal}"),
+            Expr::Aggregate { func, arg: None } => write!(f, "{func}(*)"),
+            Expr::Aggregate { func, arg: Some(arg) } => write!(f, "{func}({arg})"),
+            Expr::NextVal(name) => write!(f, "nextval({})", Literal::String(name.clone())),
+            Expr::Unary { op: UnaryOp::Not, expr } => match expr.as_ref() {
+                Expr::Binary { op, .. } if op.precedence() <= BinaryOp::And.precedence() => {
+                    write!(f, "NOT ({expr})")
+                }
+                _ => write!(f, "NOT {expr}"),
+            },
+            Expr::Unary { op: UnaryOp::Neg, expr } => match expr.as_ref() {
+                Expr::Binary { .. } | Expr::Unary { .. } | Expr::InList { .. } => write!(f, "-({expr})"),
+                _ => write!(f, "-{expr}"),
+            },
+            Expr::Binary { left, op, right } => {
+                // 左结合：左边优先级更低、右边优先级不高于当前运算符时需要括号；
+                // 比较不能连写，左边也是比较时同样需要括号
+                let comparison = BinaryOp::Eq.precedence();
+                match left.precedence() {
+                    Some(inner) if inner < op.precedence() || inner == comparison && op.precedence() == comparison => {
+                        write!(f, "({left})")?
+                    }
+                    _ => write!(f, "{left}")?,
+                }
+                write!(f, " {op} ")?;
+                match right.precedence() {
+                    Some(inner) if inner <= op.precedence() => write!(f, "({right})"),
+                    _ => write!(f, "{right}"),
+                }
+            }
+            Expr::InList { expr, list, negated } => {
+                match expr.precedence() {
+                    Some(inner) if inner <= BinaryOp::Eq.precedence() => write!(f, "({expr})")?,
+                    _ => write!(f, "{expr}")?,
+                }
+                let list: Vec<String> = list.iter().map(Expr::to_string).collect();
+                let not = if *negated { "NOT " } else { "" };
+                write!(f, " {not}IN ({})", list.join(", "))
+            }
+        }
+    }
+}
+
+/// parses `NULL` | `-12` | `3.14` | `'it''s'`
+impl<'a> Parse<'a> for Literal {
+    fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
+        context(
+            "Literal",
+            alt((
+                map(keyword("null"), |_| Literal::Null),
+                map(keyword("true"), |_| Literal::Bool(true)),
+                map(keyword("false"), |_| Literal::Bool(false)),
+                number,
+                map(quoted_string, Literal::String),
+            )),
+        )(input)
+    }
+}
+
+/// 带符号的整数或小数，含小数点的解析为 `Float`
+fn number(input: RawSpan<'_>) -> ParseResult<'_, Literal> {
+    map_res(
+        recognize(tuple((
+            opt(one_of("+-")),
+            digit1,
+            opt(pair(char('.'), digit1)),
+        ))),
+        |s: RawSpan| {
+            let s = s.fragment();
+            if s.contains('.') {
+                s.parse::<f64>().map(Literal::Float).map_err(|e| e.to_string())
+            } else {
+                s.parse::<i64>().map(Literal::Int).map_err(|e| e.to_string())
+            }
+        },
+    )(input)
+}
+
+/// 单引号字符串，`''` 表示一个单引号
+fn quoted_string(input: RawSpan<'_>) -> ParseResult<'_, String> {
+    delimited(
+        char('\''),
+        fold_many0(
+            alt((
+                map(tag("''"), |_| "'"),
+                map(is_not("'"), |s: RawSpan<'_>| *s.fragment()),
+            )),
+            String::new,
+            |mut acc, part| {
+                acc.push_str(part);
+                acc
+            },
+        ),
+        char('\''),
+    )(input)
+}
+
+impl<'a> Parse<'a> for Expr {
+    fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
+        context("Expression", or_expr)(input)
+    }
+}
+
+/// 左结合地折叠 `operand ( op operand )*`
+fn fold_binary(first: Expr, rest: Vec<(BinaryOp, Expr)>) -> Expr {
+    rest.into_iter()
+        .fold(first, |left, (op, right)| Expr::binary(left, op, right))
+}
+
+fn or_expr(input: RawSpan<'_>) -> ParseResult<'_, Expr> {
+    map(
+        pair(
+            and_expr,
+            many0(pair(
+                map(delimited(multispace0, keyword("or"), multispace0), |_| BinaryOp::Or),
+                and_expr,
+            )),
+        ),
+        |(first, rest)| fold_binary(first, rest),
+    )(input)
+}
+
+fn and_expr(input: RawSpan<'_>) -> ParseResult<'_, Expr> {
+    map(
+        pair(
+            not_expr,
+            many0(pair(
+                map(delimited(multispace0, keyword("and"), multispace0), |_| BinaryOp::And),
+                not_expr,
+            )),
+        ),
+        |(first, rest)| fold_binary(first, rest),
+    )(input)
+}
+
+fn not_expr(input: RawSpan<'_>) -> ParseResult<'_, Expr> {
+    alt((
+        map(
+            preceded(pair(keyword("not"), multispace0), not_expr),
+            |expr| Expr::Unary { op: UnaryOp::Not, expr: Box::new(expr) },
+        ),
+        comparison,
+    ))(input)
+}
+
+fn comparison_op(input: RawSpan<'_>) -> ParseResult<'_, BinaryOp> {
+    context(
+        "Comparison Operator",
+        alt((
+            // 较长的运算符需要先匹配
+            map(tag("<="), |_| BinaryOp::LtEq),
+            map(tag(">="), |_| BinaryOp::GtEq),
+            map(tag("<>"), |_| BinaryOp::NotEq),
+            map(tag("!="), |_| BinaryOp::NotEq),
+            map(tag("="), |_| BinaryOp::Eq),
+            map(tag("<"), |_| BinaryOp::Lt),
+            map(tag(">"), |_| BinaryOp::Gt),
+        )),
+    )(input)
+}
+
+/// 比较运算符或 `IN` 之后的部分
+enum ComparisonRest {
+    Binary(BinaryOp, Expr),
+    InList(Vec<Expr>, bool),
+}
+
+/// parses `[NOT] IN (<expr>, ...)`，返回列表和是否带有 `NOT`
+fn in_list(input: RawSpan<'_>) -> ParseResult<'_, (Vec<Expr>, bool)> {
+    map(
+        tuple((
+            opt(pair(keyword("not"), multispace1)),
+            keyword("in"),
+            delimited(
+                tuple((multispace0, char('('), multispace0)),
+                comma_sep(or_expr).context("In List"),
+                tuple((multispace0, char(')'))),
+            ),
+        )),
+        |(not, _, list)| (list, not.is_some()),
+    )(input)
+}
+
+fn comparison(input: RawSpan<'_>) -> ParseResult<'_, Expr> {
+    map(
+        pair(
+            additive,
+            opt(preceded(
+                multispace0,
+                alt((
+                    map(
+                        pair(comparison_op, preceded(multispace0, additive)),
+                        |(op, right)| ComparisonRest::Binary(op, right),
+                    ),
+                    map(in_list, |(list, negated)| ComparisonRest::InList(list, negated)),
+                )),
+            )),
+        ),
+        |(left, rest)| match rest {
+            Some(ComparisonRest::Binary(op, right)) => Expr::binary(left, op, right),
+            Some(ComparisonRest::InList(list, negated)) => Expr::InList { expr: Box::new(left), list, negated },
+            None => left,
+        },
+    )(input)
+}
+
+fn additive(input: RawSpan<'_>) -> ParseResult<'_, Expr> {
+    map(
+        pair(
+            term,
+            many0(pair(
+                delimited(
+                    multispace0,
+                    alt((
+                        map(char('+'), |_| BinaryOp::Plus),
+                        map(char('-'), |_| BinaryOp::Minus),
+                    )),
+                    multispace0,
+                ),
+                term,
+            )),
+        ),
+        |(first, rest)| fold_binary(first, rest),
+    )(input)
+}
+
+fn term(input: RawSpan<'_>) -> ParseResult<'_, Expr> {
+    map(
+        pair(
+            unary,
+            many0(pair(
+                delimited(
+                    multispace0,
+                    alt((
+                        map(char('*'), |_| BinaryOp::Multiply),
+                        map(char('/'), |_| BinaryOp::Divide),
+                        map(char('%'), |_| BinaryOp::Modulo),
+                    )),
+                    multispace0,
+                ),
+                unary,
+            )),
+        ),
+        |(first, rest)| fold_binary(first, rest),
+    )(input)
+}
+
+/// 先尝试 primary，使 `-5` 解析为字面量而不是取负
+fn unary(input: RawSpan<'_>) -> ParseResult<'_, Expr> {
+    alt((
+        primary,
+        map(
+            preceded(pair(char('-'), multispace0), unary),
+            |expr| Expr::Unary { op: UnaryOp::Neg, expr: Box::new(expr) },
+        ),
+    ))(input)
+}
+
+fn primary(input: RawSpan<'_>) -> ParseResult<'_, Expr> {
+    alt((
+        delimited(
+            pair(char('('), multispace0),
+            or_expr,
+            pair(multispace0, char(')')),
+        ),
+        aggregate,
+        nextval,
+        map(Literal::parse, Expr::Literal),
+        map(ColumnRef::parse, Expr::Column),
+    ))(input)
+}
+
+/// parses `name` | `table.name`
+impl<'a> Parse<'a> for ColumnRef {
+    fn parse(input: RawSpan<'a>) -> ParseResult<'a, Self> {
+        map(
+            pair(
+                identifier.context("Column Name"),
+                opt(preceded(char('.'), identifier.context("Column Name"))),
+            ),
+            |(first, second)| match second {
+                Some(name) => ColumnRef::qualified(first, name),
+                None => ColumnRef::from(first),
+            },
+        )(input)
+    }
+}
+
+fn aggregate_function(input: RawSpan<'_>) -> ParseResult<'_, AggregateFunction> {
+    alt((
+        map(keyword("count"), |_| AggregateFunction::Count),
+        map(keyword("sum"), |_| AggregateFunction::Sum),
+        map(keyword("avg"), |_| AggregateFunction::Avg),
+        map(keyword("min"), |_| AggregateFunction::Min),
+        map(keyword("max"), |_| AggregateFunction::Max),
+    ))(input)
+}
+
+/// parses `COUNT(*)` | `SUM(<expr>)` ...
+///
+/// 函数名后面必须紧跟括号，所以 `count` 仍然可以作为列名
+fn aggregate(input: RawSpan<'_>) -> ParseResult<'_, Expr> {
+    map(
+        verify(
+            pair(
+                aggregate_function,
+                delimited(
+                    tuple((multispace0, char('('), multispace0)),
+                    alt((
+                        map(char('*'), |_| None),
+                        map(or_expr, |arg| Some(Box::new(arg))),
+                    )),
+                    tuple((multispace0, char(')'))),
+                ),
+            ),
+            // 只有 COUNT 可以使用 `*`
+            |(func, arg)| arg.is_some() || *func == AggregateFunction::Count,
+        ),
+        |(func, arg)| Expr::Aggregate { func, arg },
+    )(input)
+}
+
+/// parses `nextval('name')`，和聚合函数一样，函数名后面必须紧跟括号
+fn nextval(input: RawSpan<'_>) -> ParseResult<'_, Expr> {
+    map(
+        preceded(
+            keyword("nextval"),
+            delimited(
+                tuple((multispace0, char('('), multispace0)),
+                quoted_string.context("Sequence Name"),
+                tuple((multispace0, char(')'))),
+            ),
+        ),
+        Expr::NextVal,
+    )(input)
+}
+
+/// 解析 ` WHERE <expr>`，包括关键字前面的空白
+pub(crate) fn where_clause(input: RawSpan<'_>) -> ParseResult<'_, Expr> {
+    preceded(
+        tuple((multispace1, keyword("where"), multispace1)),
+        Expr::parse.context("Where Clause"),
+    )(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn col(name: &str) -> Expr {
+        Expr::Column(name.into())
+    }
+
+    fn int(v: i64) -> Expr {
+        Expr::Literal(Literal::Int(v))
+    }
+
+    #[test]
+    fn test_literals() {
+        assert_eq!(Literal::parse_from_raw("NULL").unwrap().1, Literal::Null);
+        assert_eq!(Literal::parse_from_raw("-5").unwrap().1, Literal::Int(-5));
+        assert_eq!(Literal::parse_from_raw("+5").unwrap().1, Literal::Int(5));
+        assert_eq!(Literal::parse_from_raw("-1.25").unwrap().1, Literal::Float(-1.25));
+        assert_eq!(
+            Literal::parse_from_raw("'hello world'").unwrap().1,
+            Literal::String("hello world".into())
+        );
+        assert_eq!(
+            Literal::parse_from_raw("'it''s'").unwrap().1,
+            Literal::String("it's".into())
+        );
+        assert_eq!(Literal::parse_from_raw("''").unwrap().1, Literal::String("".into()));
+        assert!(Literal::parse_from_raw("nullable").is_err());
+        assert_eq!(Literal::parse_from_raw("TRUE").unwrap().1, Literal::Bool(true));
+        assert_eq!(Literal::parse_from_raw("false").unwrap().1, Literal::Bool(false));
+    }
+
+    #[test]
+    fn test_comparison() {
+        assert_eq!(
+            Expr::parse_from_raw("a<=10").unwrap().1,
+            Expr::binary(col("a"), BinaryOp::LtEq, int(10))
+        );
+        assert_eq!(
+            Expr::parse_from_raw("name <> 'bob'").unwrap().1,
+            Expr::binary(col("name"), BinaryOp::NotEq, Expr::Literal(Literal::String("bob".into())))
+        );
+    }
+
+    #[test]
+    fn test_in_list() {
+        assert_eq!(
+            Expr::parse_from_raw("a IN(1, 2)").unwrap().1,
+            Expr::InList { expr: Box::new(col("a")), list: vec![int(1), int(2)], negated: false }
+        );
+        assert_eq!(
+            Expr::parse_from_raw("a + 1 not in (b) and c = 1").unwrap().1,
+            Expr::binary(
+                Expr::InList {
+                    expr: Box::new(Expr::binary(col("a"), BinaryOp::Plus, int(1))),
+                    list: vec![col("b")],
+                    negated: true,
+                },
+                BinaryOp::And,
+                Expr::binary(col("c"), BinaryOp::Eq, int(1)),
+            )
+        );
+        // 列表不能为空
+        let (rest, _) = Expr::parse_from_raw("a IN ()").unwrap();
+        assert_eq!(*rest.fragment(), " IN ()");
+    }
+
+    #[test]
+    fn test_precedence() {
+        // NOT > AND > OR
+        let expected = Expr::binary(
+            Expr::binary(col("a"), BinaryOp::Eq, int(1)),
+            BinaryOp::Or,
+            Expr::binary(
+                Expr::Unary {
+                    op: UnaryOp::Not,
+                    expr: Box::new(Expr::binary(col("b"), BinaryOp::Eq, int(2))),
+                },
+                BinaryOp::And,
+                Expr::binary(col("c"), BinaryOp::Eq, int(3)),
+            ),
+        );
+        assert_eq!(
+            Expr::parse_from_raw("a = 1 or not b = 2 and c = 3").unwrap().1,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        // a + b * 2 > -(c - 1)
+        let expected = Expr::binary(
+            Expr::binary(
+                col("a"),
+                BinaryOp::Plus,
+                Expr::binary(col("b"), BinaryOp::Multiply, int(2)),
+            ),
+            BinaryOp::Gt,
+            Expr::Unary {
+                op: UnaryOp::Neg,
+                expr: Box::new(Expr::binary(col("c"), BinaryOp::Minus, int(1))),
+            },
+        );
+        assert_eq!(
+            Expr::parse_from_raw("a + b*2 > -(c - 1)").unwrap().1,
+            expected
+        );
+        assert_eq!(
+            Expr::parse_from_raw("a-5").unwrap().1,
+            Expr::binary(col("a"), BinaryOp::Minus, int(5))
+        );
+    }
+
+    #[test]
+    fn test_aggregate() {
+        assert_eq!(
+            Expr::parse_from_raw("COUNT(*)").unwrap().1,
+            Expr::Aggregate { func: AggregateFunction::Count, arg: None }
+        );
+        assert_eq!(
+            Expr::parse_from_raw("sum( a + 1 ) > 3").unwrap().1,
+            Expr::binary(
+                Expr::Aggregate {
+                    func: AggregateFunction::Sum,
+                    arg: Some(Box::new(Expr::binary(col("a"), BinaryOp::Plus, int(1)))),
+                },
+                BinaryOp::Gt,
+                int(3),
+            )
+        );
+        // 没有括号时是普通的列名
+        assert_eq!(Expr::parse_from_raw("count").unwrap().1, col("count"));
+        // 只有 COUNT 接受 `*`，这里只会解析出列名
+        let (rest, expr) = Expr::parse_from_raw("sum(*)").unwrap();
+        assert_eq!(expr, col("sum"));
+        assert_eq!(*rest.fragment(), "(*)");
+    }
+
+    #[test]
+    fn test_nextval() {
+        assert_eq!(Expr::parse_from_raw("NEXTVAL ( 'ids' )").unwrap().1, Expr::NextVal("ids".into()));
+        // 没有括号时仍是列名
+        assert_eq!(Expr::parse_from_raw("nextval").unwrap().1, col("nextval"));
+        assert!(Expr::parse_from_raw("nextval(ids)").unwrap().0.fragment().starts_with('('));
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for sql in [
+            "a + b * 2 > -(c - 1)",
+            "(a = 1 OR b = 2) AND NOT (c = 3 OR d = 4)",
+            "a - (b - c)",
+            "COUNT(*) + SUM(x) / 2",
+            "name = 'it''s'",
+            "a.id = b.aid",
+            "a IN (1, 2 + 3) AND b NOT IN ('x')",
+            "(a IN (1)) = TRUE",
+            "(a = 1) = (b < 2)",
+            "(a = 1) IN (TRUE, FALSE)",
+            "-(a IN (1))",
+            "nextval('s') + 1",
+        ] {
+            let expr = Expr::parse_from_raw(sql).unwrap().1;
+            assert_eq!(expr.to_string(), sql);
+            assert_eq!(Expr::parse_from_raw(&expr.to_string()).unwrap().1, expr);
+        }
+    }
+
+    #[test]
+    fn test_parentheses() {
+        let expected = Expr::binary(
+            Expr::binary(
+                Expr::binary(col("a"), BinaryOp::Eq, int(1)),
+                BinaryOp::Or,
+                Expr::binary(col("b"), BinaryOp::Eq, int(2)),
+            ),
+            BinaryOp::And,
+            Expr::binary(col("orders"), BinaryOp::Gt, int(3)),
+        );
+        assert_eq!(
+            Expr::parse_from_raw("(a = 1 OR b = 2) AND orders > 3").unwrap().1,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_qualified_column() {
+        assert_eq!(
+            Expr::parse_from_raw("a.id = id").unwrap().1,
+            Expr::binary(
+                Expr::Column(ColumnRef::qualified("a", "id")),
+                BinaryOp::Eq,
+                col("id"),
+            )
+        );
+    }
+}
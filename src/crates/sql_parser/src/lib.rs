@@ -2,5 +2,6 @@ pub mod parse;
 pub mod commands;
 pub mod ast;
 pub mod error;
+pub mod expr;
 
 pub use commands::{SqlTypeInfo,Column};
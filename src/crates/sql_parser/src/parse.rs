@@ -1,5 +1,7 @@
 use nom::{IResult, combinator::{map, peek}, character::complete::{char, multispace0}, bytes::complete::{take_while1, tag_no_case}, sequence::{tuple, pair}, multi::separated_list1, Finish};
-use nom::combinator::all_consuming;
+use nom::combinator::{all_consuming, not};
+use nom::character::complete::satisfy;
+use nom::sequence::terminated;
 use nom_locate::LocatedSpan;
 use nom_supreme::error::ErrorTree;
 use crate::error::{format_parse_error, FormattedError};
@@ -39,6 +41,16 @@ pub trait Parse<'a>: Sized {
 
 }
 
+/// 匹配一个关键字（忽略大小写），并确保其后不紧跟标识符字符
+///
+/// 避免把 `orders` 之类的列名当作 `or` 关键字
+pub(crate) fn keyword<'a>(kw: &'static str) -> impl FnMut(RawSpan<'a>) -> ParseResult<'a, RawSpan<'a>> {
+    terminated(
+        tag_no_case(kw),
+        not(peek(satisfy(|c: char| c.is_alphanumeric())))
+    )
+}
+
 /// 构造出解析逗号分割语句的函数
 pub(crate) fn comma_sep<'a, O, E, F>(f: F) -> impl FnMut(RawSpan<'a>) -> IResult<RawSpan<'a>, Vec<O>, E>
     where
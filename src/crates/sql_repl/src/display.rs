@@ -4,7 +4,10 @@ use sql_execution::ExecResponse;
 pub fn display_response(res: ExecResponse) {
     match res {
         ExecResponse::Select(rows) => {
-            let row = rows.get(0).expect("For now assuming we et data back");
+            let Some(row) = rows.first() else {
+                println!("(0 rows)");
+                return
+            };
             let columns: Vec<String> = row
                 .columns()
                 .iter()
@@ -13,11 +16,11 @@ pub fn display_response(res: ExecResponse) {
             let mut builder = Builder::default();
             builder.set_columns(&columns);
             for row in rows.into_iter() {
-                builder.add_record(columns.iter().map(|col| row.get(col)));
+                builder.add_record(row.values().iter().map(|value| value.to_string()));
             }
             println!("{}", builder.build());
         },
-        _ => println!("res")
+        other => println!("{other}")
     }
 }
 
@@ -37,7 +37,7 @@ fn main() -> Result<()> {
                         println!("{s}");
                     }
                 }
-                match SqlQuery::parse_format_error(line.as_ref()) {
+                match SqlQuery::parse_format_error(line) {
                     Ok(query) => println!("{query:?}"),
                     Err(err) => {
                         let mut s = String::new();